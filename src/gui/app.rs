@@ -1,10 +1,14 @@
 use crate::gui::canvas::model::Canvas;
+use crate::gui::console::model::CommandLine;
 use crate::gui::editor::model::Editor;
 use crate::interpreter::event::InputEvent;
 use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::language::token::Token;
 use crate::interpreter::state::canvas::CanvasState;
 use crate::interpreter::state::object::Point;
+use crate::interpreter::util::error::{describe_error, error_report, is_interrupt};
 use eframe::egui::*;
+use rfd::FileDialog;
 use std::collections::HashSet;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
@@ -14,6 +18,7 @@ pub struct App {
     pub interpreter: Arc<Mutex<Interpreter>>,
     pub canvas: Arc<Mutex<Canvas>>,
     pub editor: Editor,
+    pub command_line: CommandLine,
     pub input_sender: mpsc::Sender<InputEvent>,
     pub current_keys: HashSet<String>,
     pub is_running: Arc<Mutex<bool>>,
@@ -22,6 +27,7 @@ pub struct App {
 impl App {
     pub const EDITOR_WIDTH: f32 = 480.0;
     pub const CONSOLE_HEIGHT: f32 = 160.0;
+    pub const GUTTER_WIDTH: f32 = 28.0;
 
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let (input_sender, input_receiver) = mpsc::channel::<InputEvent>();
@@ -32,6 +38,7 @@ impl App {
             interpreter: Arc::from(Mutex::from(interpreter)),
             canvas: Arc::from(Mutex::from(Canvas::new(canvas_size))),
             editor: Editor::new(FontId::monospace(16.0)),
+            command_line: CommandLine::new(),
             input_sender,
             is_running: Arc::from(Mutex::from(false)),
             current_keys: HashSet::new(),
@@ -47,13 +54,23 @@ impl App {
         let ctx_mutex = Arc::from(Mutex::from(ctx.clone())).clone();
         let is_running_mutex = self.is_running.clone();
         let code = self.editor.current_code().to_string();
+        let (file_name, breakpoints) = match self.editor.current_file() {
+            Some(file) => (file.name.clone(), file.breakpoints.clone()),
+            None => (String::from("untitled"), HashSet::new()),
+        };
         thread::spawn(move || {
             let mut interpreter = interpreter_mutex.lock().unwrap();
 
             // Clear any events in the channel so stale key presses do not immediatley trigger.
             interpreter.clear_input_events();
             interpreter.bind_ui_handler(canvas_mutex, ctx_mutex);
-            interpreter.interpret_main(&code);
+            interpreter.set_source_name(&file_name);
+            interpreter.state.debug.set_breakpoints(breakpoints);
+            match interpreter.interpret_main(&code) {
+                Ok(..) => println!("Done!"),
+                Err(err) if is_interrupt(&err) => println!("Program Ended"),
+                Err(err) => println!("{}", error_report(err.as_ref())),
+            }
             interpreter.clear_ui_handler();
 
             // Signal program no longer running.
@@ -66,10 +83,129 @@ impl App {
         *is_running = true;
     }
 
+    pub fn run_command(&mut self, ctx: &Context) {
+        let Some(command) = self.command_line.submit() else {
+            return;
+        };
+        let mut canvas = self.canvas.lock().unwrap();
+        if *self.is_running.lock().unwrap() {
+            canvas.print_to_console(String::from("a program is already running"));
+            return;
+        }
+        canvas.print_to_console(format!("> {}", command));
+        drop(canvas);
+
+        // Run against the current workspace on a background thread, like a program.
+        let interpreter_mutex = self.interpreter.clone();
+        let canvas_mutex = self.canvas.clone();
+        let ctx_mutex = Arc::from(Mutex::from(ctx.clone())).clone();
+        let is_running_mutex = self.is_running.clone();
+        let ctx = ctx.clone();
+        *self.is_running.lock().unwrap() = true;
+        thread::spawn(move || {
+            let mut interpreter = interpreter_mutex.lock().unwrap();
+            interpreter.clear_input_events();
+            interpreter.bind_ui_handler(canvas_mutex.clone(), ctx_mutex);
+            let result = interpreter.interpret_command(&command);
+            interpreter.clear_ui_handler();
+
+            // Echo the result of the command back to the console.
+            let output = match result {
+                Ok(Token::Void) => None,
+                Ok(value) => Some(value.to_string()),
+                Err(err) if is_interrupt(&err) => Some(String::from("stopped")),
+                Err(err) => Some(describe_error(err.as_ref())),
+            };
+            if let Some(output) = output {
+                let mut canvas = canvas_mutex.lock().unwrap();
+                canvas.print_to_console(output);
+            }
+            ctx.request_repaint();
+
+            let mut is_running = is_running_mutex.lock().unwrap();
+            *is_running = false;
+        });
+    }
+
+    pub fn save_project(&mut self) {
+        if *self.is_running.lock().unwrap() {
+            let mut canvas = self.canvas.lock().unwrap();
+            canvas.print_to_console(String::from("a program is already running"));
+            return;
+        }
+        let file_path = FileDialog::new()
+            .add_filter("project", &["mwx"])
+            .set_file_name("untitled.mwx")
+            .set_directory(".")
+            .save_file();
+        let Some(file_path) = file_path else {
+            return;
+        };
+        let interpreter = self.interpreter.lock().unwrap();
+        let code = self.editor.current_code();
+        if let Err(err) = interpreter.save_project(&file_path.to_string_lossy(), code) {
+            let mut canvas = self.canvas.lock().unwrap();
+            canvas.print_to_console(format!("failed to save project: {}", err));
+        }
+    }
+
+    pub fn open_project(&mut self, ctx: &Context) {
+        if *self.is_running.lock().unwrap() {
+            let mut canvas = self.canvas.lock().unwrap();
+            canvas.print_to_console(String::from("a program is already running"));
+            return;
+        }
+        let file_path = FileDialog::new()
+            .add_filter("project", &["mwx"])
+            .set_directory(".")
+            .pick_file();
+        let Some(file_path) = file_path else {
+            return;
+        };
+        let mut interpreter = self.interpreter.lock().unwrap();
+        let ctx_mutex = Arc::from(Mutex::from(ctx.clone()));
+        interpreter.bind_ui_handler(self.canvas.clone(), ctx_mutex);
+        let result = interpreter.load_project(&file_path.to_string_lossy());
+        interpreter.clear_ui_handler();
+        if let Err(err) = result {
+            let mut canvas = self.canvas.lock().unwrap();
+            canvas.print_to_console(format!("failed to open project: {}", err));
+        }
+    }
+
     pub fn interrupt_code(&mut self) {
         let _ = self.input_sender.send(InputEvent::Interrupt);
     }
 
+    pub fn pause_code(&mut self) {
+        let _ = self.input_sender.send(InputEvent::Pause);
+    }
+
+    pub fn resume_code(&mut self) {
+        let _ = self.input_sender.send(InputEvent::Resume);
+    }
+
+    pub fn step_code(&mut self) {
+        let _ = self.input_sender.send(InputEvent::Step);
+    }
+
+    pub fn toggle_breakpoint(&mut self, line: usize) {
+        let Some(file) = self.editor.current_file_mut() else {
+            return;
+        };
+        if !file.breakpoints.remove(&line) {
+            file.breakpoints.insert(line);
+        }
+
+        // A running program picks up the change straight away.
+        let breakpoints = file.breakpoints.clone();
+        if *self.is_running.lock().unwrap() {
+            let _ = self
+                .input_sender
+                .send(InputEvent::SetBreakpoints(breakpoints));
+        }
+    }
+
     pub fn reset_state(&mut self) {
         // If a program is running, stop with an interrupt.
         let is_running = *self.is_running.lock().unwrap();
@@ -161,6 +297,12 @@ impl App {
 impl eframe::App for App {
     // Build Main UI
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        // Open source from a project loaded by the interpreter.
+        let opened_source = self.canvas.lock().unwrap().opened_source.take();
+        if let Some((name, code)) = opened_source {
+            self.editor.open_source(name, code);
+        }
+
         // Left Side
         self.canvas_view(ctx);
 
@@ -1,6 +1,7 @@
-use crate::gui::canvas::model::{Canvas, PathConfig};
+use crate::gui::canvas::model::{Canvas, DrawnShape, FillConfig, PathConfig};
 use crate::gui::canvas::object::{ObjectView, TextView, TurtleView};
 use crate::interpreter::event::{UiContext, UiEvent, UiEventHandler};
+use crate::interpreter::state::pen::PenMode;
 use eframe::egui::*;
 use std::any::Any;
 use std::error::Error;
@@ -12,6 +13,7 @@ impl UiEventHandler for Canvas {
     fn handle_ui_event(&mut self, ctx: Arc<Mutex<dyn UiContext>>, event: UiEvent) {
         match event {
             UiEvent::Done => {
+                self.debug_snapshot = None;
                 let ctx = ctx.lock().unwrap();
                 ctx.update_ui();
             }
@@ -19,7 +21,17 @@ impl UiEventHandler for Canvas {
                 let ctx = ctx.lock().unwrap();
                 ctx.update_ui();
             }
-            UiEvent::ConsolePrint(text) => {
+            UiEvent::Paused(snapshot) => {
+                self.debug_snapshot = Some(snapshot);
+                let ctx = ctx.lock().unwrap();
+                ctx.update_ui();
+            }
+            UiEvent::Resumed => {
+                self.debug_snapshot = None;
+                let ctx = ctx.lock().unwrap();
+                ctx.update_ui();
+            }
+            UiEvent::ConsolePrint(text) | UiEvent::ConsoleError(text) => {
                 self.print_to_console(text);
             }
             UiEvent::Announce(text) => {
@@ -53,8 +65,8 @@ impl UiEventHandler for Canvas {
                     self.print_to_console(format!("object named {} does not exist", name));
                 }
             }
-            UiEvent::ObjectColor(name, hue) => {
-                let color = self.to_canvas_color(hue);
+            UiEvent::ObjectColor(name, color) => {
+                let color = self.to_canvas_color(color);
                 if let Some(obj) = self.objects.get_mut(&name) {
                     obj.set_color(color)
                 } else {
@@ -122,9 +134,8 @@ impl UiEventHandler for Canvas {
                 self.size.x = width;
                 self.size.y = height;
             }
-            UiEvent::BgColor(hue) => {
-                let color = self.to_canvas_color(hue);
-                self.bg_color = color;
+            UiEvent::BgColor(color) => {
+                self.bg_color = self.to_canvas_color(color);
             }
             UiEvent::BgPicture(path) => {
                 let path_ptr = path.clone().into_boxed_str();
@@ -181,23 +192,68 @@ impl UiEventHandler for Canvas {
                 let end = pos2(line.end.x, line.end.y);
                 let color = self.to_canvas_color(line.color);
                 if let Some(path) = self.current_turtle_paths.get_mut(&name) {
-                    if path.color == color {
-                        if let Some(point) = path.points.last() {
-                            if *point == start {
-                                path.points.push(end);
-                            }
-                        }
-                    }
-                    if let Some(path) = self.current_turtle_paths.remove(&name) {
-                        self.drawn_paths.push(path);
+                    let is_joined = path.points.last() == Some(&start)
+                        && path.color == color
+                        && path.stroke == line.stroke_width
+                        && path.pen == line.pen
+                        && line.pen.mode != PenMode::Reverse;
+                    if is_joined {
+                        path.points.push(end);
+                        return;
                     }
                 }
+                if let Some(path) = self.current_turtle_paths.remove(&name) {
+                    self.drawn_shapes.push(DrawnShape::Path(path));
+                }
                 let path = PathConfig {
                     points: vec![start, end],
                     color,
                     stroke: line.stroke_width,
+                    pen: line.pen,
+                    dash_offset: line.dash_offset,
+                    is_closed: false,
+                };
+                match path.pen.mode {
+                    PenMode::Reverse => self.add_reversed_path(path),
+                    PenMode::Paint | PenMode::Erase => {
+                        self.current_turtle_paths.insert(name, path);
+                    }
+                }
+            }
+            UiEvent::AddCurve(name, curve) => {
+                // A curve is never joined to the lines around it.
+                if let Some(path) = self.current_turtle_paths.remove(&name) {
+                    self.drawn_shapes.push(DrawnShape::Path(path));
+                }
+                let path = PathConfig {
+                    points: curve
+                        .points
+                        .iter()
+                        .map(|point| pos2(point.x, point.y))
+                        .collect(),
+                    color: self.to_canvas_color(curve.color),
+                    stroke: curve.stroke_width,
+                    pen: curve.pen,
+                    dash_offset: curve.dash_offset,
+                    is_closed: curve.is_closed,
+                };
+                match path.pen.mode {
+                    PenMode::Reverse => self.add_reversed_path(path),
+                    PenMode::Paint | PenMode::Erase => {
+                        self.drawn_shapes.push(DrawnShape::Path(path));
+                    }
+                }
+            }
+            UiEvent::AddFill(fill) => {
+                // Paths drawn so far go underneath the fill.
+                for (_, path) in self.current_turtle_paths.drain() {
+                    self.drawn_shapes.push(DrawnShape::Path(path));
+                }
+                let config = FillConfig {
+                    spans: fill.spans,
+                    color: self.to_canvas_color(fill.color),
                 };
-                self.current_turtle_paths.insert(name, path);
+                self.drawn_shapes.push(DrawnShape::Fill(config));
             }
             UiEvent::AddShape(name, path) => {
                 let ctx = ctx.lock().unwrap();
@@ -217,11 +273,22 @@ impl UiEventHandler for Canvas {
             UiEvent::Clean => {
                 self.pictures.clear();
                 self.current_turtle_paths.clear();
-                self.drawn_paths.clear();
+                self.drawn_shapes.clear();
             }
             UiEvent::ClearConsole => {
                 self.console_text = String::new();
             }
+            UiEvent::Reset => {
+                self.objects.clear();
+                self.bg_color = Color32::from_gray(255);
+                self.bg_picture = None;
+                self.pictures.clear();
+                self.current_turtle_paths.clear();
+                self.drawn_shapes.clear();
+            }
+            UiEvent::OpenSource(name, code) => {
+                self.opened_source = Some((name, code));
+            }
         };
     }
 }
@@ -1,7 +1,9 @@
 use crate::gui::canvas::object::{ObjectView, TurtleView};
-use crate::interpreter::state::object::TurtleShape;
+use crate::interpreter::state::debug::DebugSnapshot;
+use crate::interpreter::state::object::{Color, Point, Span, TurtleShape};
+use crate::interpreter::state::pen::{Pen, PenMode, StrokePiece};
 use eframe::egui::*;
-use eframe::epaint::{CircleShape, Hsva, PathShape, RectShape};
+use eframe::epaint::{CircleShape, PathShape, RectShape};
 use std::collections::HashMap;
 
 pub struct Canvas {
@@ -13,10 +15,14 @@ pub struct Canvas {
     pub bg_picture: Option<TextureHandle>,
     pub pictures: Vec<PictureConfig>,
     pub current_turtle_paths: HashMap<Box<str>, PathConfig>,
-    pub drawn_paths: Vec<PathConfig>,
+    pub drawn_shapes: Vec<DrawnShape>,
     pub console_text: String,
     pub announce_text: String,
     pub is_window_open: bool,
+    // Source from a loaded project, waiting to be opened in the editor.
+    pub opened_source: Option<(String, String)>,
+    // Set while the program is paused in the debugger.
+    pub debug_snapshot: Option<DebugSnapshot>,
 }
 
 impl Canvas {
@@ -30,10 +36,12 @@ impl Canvas {
             bg_picture: None,
             pictures: vec![],
             current_turtle_paths: HashMap::new(),
-            drawn_paths: vec![],
+            drawn_shapes: vec![],
             console_text: String::new(),
             announce_text: String::new(),
             is_window_open: false,
+            opened_source: None,
+            debug_snapshot: None,
         }
     }
 
@@ -65,14 +73,9 @@ impl Canvas {
         (-angle - 90.0) % 360.0
     }
 
-    pub fn to_canvas_color(&self, hue: f32) -> Color32 {
-        if hue == 1.0 {
-            Color32::from_gray(0)
-        } else if hue == 0.0 || hue == 255.0 {
-            Color32::from_gray(255)
-        } else {
-            Color32::from(Hsva::new((hue % 256.0) / 255.0, 1.0, 1.0, 1.0))
-        }
+    pub fn to_canvas_color(&self, color: Color) -> Color32 {
+        let [r, g, b, a] = color.to_rgba();
+        Color32::from_rgba_unmultiplied(r, g, b, a)
     }
 
     pub fn print_to_console(&mut self, text: String) {
@@ -132,15 +135,105 @@ impl Canvas {
         Some(shape)
     }
 
-    pub fn path_for_config(&self, config: &PathConfig) -> PathShape {
-        PathShape::line(
-            config
+    pub fn shapes_for_fill(&self, config: &FillConfig) -> Vec<Shape> {
+        // Spans are rows of pixels, from the center of the first to the last.
+        config
+            .spans
+            .iter()
+            .map(|span| {
+                let top_left = self.to_canvas_coordinates(pos2(span.left - 0.5, span.y + 0.5));
+                let bottom_right = self.to_canvas_coordinates(pos2(span.right + 0.5, span.y - 0.5));
+                Shape::rect_filled(
+                    Rect::from_min_max(top_left, bottom_right),
+                    Rounding::ZERO,
+                    config.color,
+                )
+            })
+            .collect()
+    }
+
+    pub fn shapes_for_path(&self, config: &PathConfig) -> Vec<Shape> {
+        if config.pen.is_plain() {
+            let points = config
                 .points
                 .iter()
                 .map(|point: &Pos2| self.to_canvas_coordinates(*point))
-                .collect(),
-            Stroke::new(config.stroke, config.color),
-        )
+                .collect();
+            let stroke = Stroke::new(config.stroke, config.color);
+            return match config.is_closed {
+                true => vec![Shape::Path(PathShape::closed_line(points, stroke))],
+                false => vec![Shape::Path(PathShape::line(points, stroke))],
+            };
+        }
+
+        // Other pens are drawn in pieces, the same way as in the interpreter.
+        let color = match config.pen.mode {
+            PenMode::Erase => self.bg_color,
+            PenMode::Paint | PenMode::Reverse => config.color,
+        };
+        let to_pos = |point: &Point| self.to_canvas_coordinates(pos2(point.x, point.y));
+        let points: Vec<Point> = config
+            .points
+            .iter()
+            .map(|pos| Point::new(pos.x, pos.y))
+            .collect();
+        let mut shapes = vec![];
+        let mut dash_offset = config.dash_offset;
+        for (index, pair) in points.windows(2).enumerate() {
+            // The start of a closed path is a corner like any other.
+            let joined_from = match index.checked_sub(1) {
+                Some(previous) => Some(&points[previous]),
+                None if config.is_closed && points.len() > 2 => Some(&points[points.len() - 2]),
+                None => None,
+            };
+            let pieces = config.pen.stroke_pieces(
+                &pair[0],
+                &pair[1],
+                dash_offset,
+                joined_from,
+                config.stroke / 2.0,
+            );
+            for piece in pieces {
+                match piece {
+                    StrokePiece::Round(start, end, radius) => {
+                        let ends = [to_pos(&start), to_pos(&end)];
+                        shapes.push(Shape::line_segment(ends, Stroke::new(radius * 2.0, color)));
+                        shapes.push(Shape::circle_filled(ends[0], radius, color));
+                        shapes.push(Shape::circle_filled(ends[1], radius, color));
+                    }
+                    StrokePiece::Polygon(corners) => {
+                        let corners = corners.iter().map(to_pos).collect();
+                        shapes.push(Shape::convex_polygon(corners, color, Stroke::NONE));
+                    }
+                }
+            }
+            dash_offset += pair[0].distance_to(&pair[1]);
+        }
+        shapes
+    }
+
+    pub fn add_reversed_path(&mut self, path: PathConfig) {
+        // Reversing the same path again takes it away, like it does in the
+        // interpreter.
+        let is_same = |shape: &DrawnShape| {
+            let DrawnShape::Path(other) = shape else {
+                return false;
+            };
+            let is_same_pen =
+                other.color == path.color && other.stroke == path.stroke && other.pen == path.pen;
+            let is_same_dashes = if path.pen.pattern.is_empty() {
+                other.points == path.points || other.points.iter().eq(path.points.iter().rev())
+            } else {
+                other.points == path.points && other.dash_offset == path.dash_offset
+            };
+            is_same_pen && is_same_dashes && other.is_closed == path.is_closed
+        };
+        match self.drawn_shapes.iter().rposition(is_same) {
+            Some(index) => {
+                self.drawn_shapes.remove(index);
+            }
+            None => self.drawn_shapes.push(DrawnShape::Path(path)),
+        }
     }
 }
 
@@ -154,4 +247,18 @@ pub struct PathConfig {
     pub points: Vec<Pos2>,
     pub color: Color32,
     pub stroke: f32,
+    pub pen: Pen,
+    pub dash_offset: f32,
+    pub is_closed: bool,
+}
+
+pub struct FillConfig {
+    pub spans: Vec<Span>,
+    pub color: Color32,
+}
+
+// Finished drawing, in the order it was drawn.
+pub enum DrawnShape {
+    Path(PathConfig),
+    Fill(FillConfig),
 }
@@ -1,4 +1,5 @@
 use crate::gui::app::App;
+use crate::gui::canvas::model::DrawnShape;
 use crate::gui::canvas::object::ObjectView;
 use eframe::egui::text::LayoutJob;
 use eframe::egui::*;
@@ -61,11 +62,18 @@ impl App {
                         }
 
                         // Lines
-                        for config in &canvas.drawn_paths {
-                            content_painter.add(canvas.path_for_config(config));
+                        for shape in &canvas.drawn_shapes {
+                            match shape {
+                                DrawnShape::Path(config) => {
+                                    content_painter.extend(canvas.shapes_for_path(config));
+                                }
+                                DrawnShape::Fill(config) => {
+                                    content_painter.extend(canvas.shapes_for_fill(config));
+                                }
+                            }
                         }
                         for (_, config) in &canvas.current_turtle_paths {
-                            content_painter.add(canvas.path_for_config(config));
+                            content_painter.extend(canvas.shapes_for_path(config));
                         }
 
                         // Turtles and Text
@@ -179,14 +187,42 @@ impl App {
                     .show_inside(ui, |ui: &mut Ui| {
                         ui.add_space(6.0);
 
-                        ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
-                            let canvas = self.canvas.lock().unwrap();
+                        ui.with_layout(Layout::bottom_up(Align::LEFT), |ui: &mut Ui| {
+                            // Command Line
                             ui.add_space(6.0);
-                            let print_output = RichText::new(canvas.console_text.clone())
-                                .font(FontId::proportional(16.0))
-                                .color(Color32::from_gray(255));
-                            let print_output_label = Label::new(print_output);
-                            ui.add(print_output_label);
+                            ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+                                ui.add_space(6.0);
+                                let command_input =
+                                    TextEdit::singleline(&mut self.command_line.input)
+                                        .font(FontId::monospace(14.0))
+                                        .hint_text("Type a command and press enter")
+                                        .desired_width(main_frame_width - 12.0);
+                                let command_response = ui.add(command_input);
+                                if command_response.lost_focus()
+                                    && ui.input(|i| i.key_pressed(Key::Enter))
+                                {
+                                    self.run_command(ctx);
+                                    command_response.request_focus();
+                                } else if command_response.has_focus() {
+                                    if ui.input(|i| i.key_pressed(Key::ArrowUp)) {
+                                        self.command_line.previous();
+                                    } else if ui.input(|i| i.key_pressed(Key::ArrowDown)) {
+                                        self.command_line.next();
+                                    }
+                                }
+                            });
+                            ui.add_space(6.0);
+
+                            // Output
+                            ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+                                let canvas = self.canvas.lock().unwrap();
+                                ui.add_space(6.0);
+                                let print_output = RichText::new(canvas.console_text.clone())
+                                    .font(FontId::proportional(16.0))
+                                    .color(Color32::from_gray(255));
+                                let print_output_label = Label::new(print_output);
+                                ui.add(print_output_label);
+                            });
                         });
                     });
             });
@@ -0,0 +1,52 @@
+pub struct CommandLine {
+    pub input: String,
+    history: Vec<String>,
+    // Position in the history while browsing with the arrow keys.
+    history_index: Option<usize>,
+}
+
+impl CommandLine {
+    pub fn new() -> Self {
+        CommandLine {
+            input: String::new(),
+            history: vec![],
+            history_index: None,
+        }
+    }
+
+    pub fn submit(&mut self) -> Option<String> {
+        let command = self.input.trim().to_string();
+        self.input.clear();
+        self.history_index = None;
+        if command.is_empty() {
+            return None;
+        }
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+        }
+        Some(command)
+    }
+
+    pub fn previous(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if !self.history.is_empty() => self.history.len() - 1,
+            None => return,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    pub fn next(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.history_index = None;
+            self.input.clear();
+        }
+    }
+}
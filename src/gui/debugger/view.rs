@@ -0,0 +1,81 @@
+use crate::gui::app::App;
+use crate::interpreter::state::debug::DebugSnapshot;
+use eframe::egui::*;
+
+impl App {
+    pub const DEBUGGER_HEIGHT: f32 = 220.0;
+
+    pub fn debugger_view(&mut self, ui: &mut Ui, snapshot: &DebugSnapshot) {
+        TopBottomPanel::bottom("debugger")
+            .frame(Frame::default().fill(Color32::from_gray(30)))
+            .exact_height(Self::DEBUGGER_HEIGHT)
+            .resizable(false)
+            .show_inside(ui, |ui: &mut Ui| {
+                ScrollArea::vertical().show(ui, |ui: &mut Ui| {
+                    ui.set_width(ui.available_width());
+                    ui.add_space(10.0);
+
+                    // Title
+                    let title_text = match snapshot.line {
+                        Some(line) => format!("Paused at line {}", line),
+                        None => String::from("Paused"),
+                    };
+                    self.debugger_heading(ui, title_text, 16.0);
+
+                    // Call Stack
+                    self.debugger_heading(ui, String::from("Call Stack"), 14.0);
+                    for procedure in snapshot.call_stack.iter().rev() {
+                        self.debugger_entry(ui, procedure.clone());
+                    }
+                    self.debugger_entry(ui, String::from("(top level)"));
+
+                    // Variables
+                    let last_index = snapshot.scopes.len().saturating_sub(1);
+                    for (index, scope) in snapshot.scopes.iter().enumerate() {
+                        if scope.is_empty() {
+                            continue;
+                        }
+                        let scope_name = if index == last_index {
+                            String::from("Global Variables")
+                        } else {
+                            String::from("Local Variables")
+                        };
+                        self.debugger_heading(ui, scope_name, 14.0);
+                        for (name, value) in scope {
+                            self.debugger_entry(ui, format!("{} = {}", name, value));
+                        }
+                    }
+
+                    // Turtle
+                    if !snapshot.turtle.is_empty() {
+                        self.debugger_heading(ui, String::from("Turtle"), 14.0);
+                        for (name, value) in &snapshot.turtle {
+                            self.debugger_entry(ui, format!("{} = {}", name, value));
+                        }
+                    }
+                    ui.add_space(10.0);
+                });
+            });
+    }
+
+    fn debugger_heading(&self, ui: &mut Ui, text: String, size: f32) {
+        ui.add_space(4.0);
+        ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+            ui.add_space(10.0);
+            let heading = RichText::new(text)
+                .font(FontId::proportional(size))
+                .color(Color32::from_gray(255));
+            ui.add(Label::new(heading));
+        });
+    }
+
+    fn debugger_entry(&self, ui: &mut Ui, text: String) {
+        ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+            ui.add_space(20.0);
+            let entry = RichText::new(text)
+                .font(FontId::monospace(12.0))
+                .color(Color32::from_gray(180));
+            ui.add(Label::new(entry).truncate(true));
+        });
+    }
+}
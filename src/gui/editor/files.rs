@@ -1,4 +1,5 @@
 use eframe::egui::TextBuffer;
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
@@ -7,6 +8,7 @@ pub struct FileHandle {
     pub path: Option<PathBuf>,
     pub content: String,
     pub is_edited: bool,
+    pub breakpoints: HashSet<usize>,
 }
 
 impl FileHandle {
@@ -16,6 +18,7 @@ impl FileHandle {
             path: None,
             content: String::new(),
             is_edited: false,
+            breakpoints: HashSet::new(),
         }
     }
 
@@ -25,8 +28,30 @@ impl FileHandle {
             path: Some(path),
             content,
             is_edited: false,
+            breakpoints: HashSet::new(),
         }
     }
+
+    fn line_at(&self, char_index: usize) -> usize {
+        let newlines = self.content.chars().take(char_index);
+        newlines.filter(|chr| *chr == '\n').count() + 1
+    }
+
+    fn shift_breakpoints(&mut self, after: usize, by: isize) {
+        // Breakpoints stay with their statements when lines are added or
+        // removed above them.
+        self.breakpoints = self
+            .breakpoints
+            .iter()
+            .map(|line| {
+                if *line > after {
+                    line.saturating_add_signed(by)
+                } else {
+                    *line
+                }
+            })
+            .collect();
+    }
 }
 
 impl TextBuffer for FileHandle {
@@ -40,11 +65,33 @@ impl TextBuffer for FileHandle {
 
     fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
         self.is_edited = true;
+        let added = text.chars().filter(|chr| *chr == '\n').count();
+        if added > 0 {
+            // Text put at the start of a line pushes that line down too.
+            let line = self.line_at(char_index);
+            let at_line_start =
+                char_index == 0 || self.content.chars().nth(char_index - 1) == Some('\n');
+            let after = if at_line_start { line - 1 } else { line };
+            self.shift_breakpoints(after, added as isize);
+        }
         self.content.insert_text(text, char_index)
     }
 
     fn delete_char_range(&mut self, char_range: std::ops::Range<usize>) {
         self.is_edited = true;
+        let (first, last) = (self.line_at(char_range.start), self.line_at(char_range.end));
+        if last > first {
+            // The deleted lines join the first one, and lose their breakpoints.
+            self.breakpoints
+                .retain(|line| *line <= first || *line > last);
+            self.shift_breakpoints(last, first as isize - last as isize);
+        }
         self.content.delete_char_range(char_range)
     }
+
+    fn replace_with(&mut self, text: &str) {
+        self.breakpoints.clear();
+        self.content.replace_with(text);
+        self.is_edited = true;
+    }
 }
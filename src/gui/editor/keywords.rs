@@ -17,29 +17,50 @@ pub const DEFINITION_COMMANDS: phf::Set<&'static str> = phf_set!(
 pub const CONTROL_FLOW_COMMANDS: phf::Set<&'static str> = phf_set!(
     "again",
     "and",
+    "cancel",
     "carefully",
+    "catch",
+    "do.until",
+    "do.while",
     "dolist",
     "dotimes",
+    "for",
+    "foreach",
     "forever",
     "if",
     "ifelse",
+    "launch",
     "not",
     "op",
     "or",
     "output",
+    "repcount",
     "repeat",
     "run",
+    "runresult",
+    "stop",
+    "stopall",
+    "throw",
+    "until",
     "wait",
+    "when",
+    "while",
 );
 
 pub const OTHER_COMMANDS: phf::Set<&'static str> = phf_set!(
     "abs",
     "again",
     "announce",
+    "apply",
+    "arc",
     "arctan",
+    "array",
+    "array?",
+    "arraytolist",
     "ascii",
     "ask",
     "back",
+    "beginfill",
     "bf",
     "bg",
     "bk",
@@ -50,86 +71,137 @@ pub const OTHER_COMMANDS: phf::Set<&'static str> = phf_set!(
     "cg",
     "char",
     "chdir",
+    "circle",
     "clean",
     "cleartext",
     "clicked?",
     "color",
     "colorunder",
+    "copydef",
     "cos",
     "count",
     "ct",
     "currentdir",
+    "curveto",
+    "define",
     "difference",
     "directories",
     "distance",
+    "ellipse",
     "empty?",
+    "endfill",
     "equal?",
+    "erall",
+    "erase",
+    "erpl",
+    "error",
     "errormessage",
     "exp",
     "fd",
     "files",
+    "fill",
+    "filter",
+    "find",
     "first",
     "fontsize",
     "forward",
     "fput",
     "freeze",
+    "gprop",
     "greater?",
+    "greaterequal?",
     "heading",
     "home",
     "ht",
     "int",
+    "invoke",
     "item",
     "key?",
     "keydown?",
     "last",
     "left",
     "less?",
+    "lessequal?",
+    "linecap",
+    "linejoin",
     "list",
     "list?",
+    "listtoarray",
     "ln",
     "loadpict",
+    "loadproject",
     "loadshape",
     "loadtext",
     "log",
     "lput",
     "lt",
+    "map",
+    "mdarray",
+    "mditem",
+    "mdsetitem",
     "member?",
     "minus",
+    "notequal?",
     "number?",
     "on?",
     "pd",
+    "pe",
+    "penerase",
+    "penmode",
+    "penpaint",
+    "penpattern",
+    "penreverse",
     "pensize",
     "pi",
     "pick",
     "pictlist",
     "placepict",
+    "plist",
+    "po",
     "pos",
     "power",
+    "ppt",
+    "pprop",
     "pr",
     "print",
+    "printout",
     "procedures",
+    "processes",
     "product",
     "projectsize",
     "pu",
+    "px",
     "quotient",
     "random",
     "readchar",
     "readclick",
+    "reduce",
     "remainder",
     "remove",
+    "remprop",
     "resett",
     "right",
     "round",
     "rt",
     "run",
+    "savepict",
+    "saveproject",
+    "savesvg",
     "se",
     "sentence",
     "setbg",
     "setc",
     "setcolor",
     "setfontsize",
+    "setitem",
+    "setlinecap",
+    "setlinejoin",
+    "setonclick",
+    "setoncolor",
+    "setontouching",
     "seth",
     "setheading",
+    "setpenpattern",
     "setpensize",
     "setpos",
     "setprojectsize",
@@ -145,6 +217,7 @@ pub const OTHER_COMMANDS: phf::Set<&'static str> = phf_set!(
     "size",
     "sqrt",
     "st",
+    "step",
     "sum",
     "talkto",
     "tan",
@@ -153,8 +226,11 @@ pub const OTHER_COMMANDS: phf::Set<&'static str> = phf_set!(
     "timer",
     "touching?",
     "towards",
+    "trace",
     "tto",
     "unfreeze",
+    "unstep",
+    "untrace",
     "visible?",
     "who",
     "word",
@@ -82,6 +82,13 @@ impl Editor {
         self.current_file_index = Some(self.open_files.len() - 1);
     }
 
+    pub fn open_source(&mut self, name: String, content: String) {
+        let mut file = FileHandle::new(name);
+        file.content = content;
+        self.open_files.push(file);
+        self.current_file_index = Some(self.open_files.len() - 1);
+    }
+
     pub fn open_file(&mut self) {
         let file_path = FileDialog::new()
             .add_filter("logo", &["txt", "logo"])
@@ -1,6 +1,8 @@
 use crate::gui::app::App;
 use crate::gui::editor::files::FileHandle;
+use eframe::egui::text_edit::TextEditOutput;
 use eframe::egui::*;
+use std::collections::HashSet;
 
 impl App {
     pub fn code_editor_view(&mut self, ctx: &Context) {
@@ -93,6 +95,21 @@ impl App {
                                 if new_button_response.clicked() {
                                     self.editor.new_file();
                                 }
+
+                                // Project Menu
+                                let file_menu_label = RichText::new(String::from("File"))
+                                    .font(FontId::proportional(14.0))
+                                    .color(Color32::from_gray(255));
+                                ui.menu_button(file_menu_label, |ui: &mut Ui| {
+                                    if ui.button("Save Project...").clicked() {
+                                        ui.close_menu();
+                                        self.save_project();
+                                    }
+                                    if ui.button("Open Project...").clicked() {
+                                        ui.close_menu();
+                                        self.open_project(ctx);
+                                    }
+                                });
                             });
                         });
 
@@ -120,25 +137,41 @@ impl App {
                     .resizable(false)
                     .show_inside(ui, |ui: &mut Ui| {
                         let is_running = *self.is_running.lock().unwrap();
-                        let button_text = if is_running {
-                            String::from("Stop")
-                        } else {
-                            String::from("Run Code")
-                        };
-                        let button_label = RichText::new(button_text)
-                            .font(FontId::proportional(16.0))
-                            .color(Color32::from_gray(255));
-                        let button = Button::new(button_label).fill(Color32::from_gray(60));
-                        let button_response = ui.add_sized(ui.available_size(), button);
-                        if button_response.clicked() {
-                            if is_running {
-                                self.interrupt_code();
-                            } else {
+                        if !is_running {
+                            if self.control_button(ui, "Run Code", ui.available_size()) {
                                 self.run_code(ctx);
                             }
+                            return;
                         }
+
+                        // Debugger Controls
+                        let is_paused = self.canvas.lock().unwrap().debug_snapshot.is_some();
+                        ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+                            let spacing = ui.spacing().item_spacing.x;
+                            let width = (ui.available_width() - spacing * 2.0) / 3.0;
+                            let size = vec2(width, ui.available_height());
+                            if is_paused {
+                                if self.control_button(ui, "Resume", size) {
+                                    self.resume_code();
+                                }
+                            } else if self.control_button(ui, "Pause", size) {
+                                self.pause_code();
+                            }
+                            if self.control_button(ui, "Step", size) {
+                                self.step_code();
+                            }
+                            if self.control_button(ui, "Stop", size) {
+                                self.interrupt_code();
+                            }
+                        });
                     });
 
+                // Debugger
+                let snapshot = self.canvas.lock().unwrap().debug_snapshot.clone();
+                if let Some(snapshot) = snapshot {
+                    self.debugger_view(ui, &snapshot);
+                }
+
                 // Text Area
                 CentralPanel::default()
                     .frame(Frame::default().fill(Color32::from_gray(20)))
@@ -146,29 +179,41 @@ impl App {
                         if let Some(index) = current_file_index {
                             ScrollArea::vertical().show(ui, |ui: &mut Ui| {
                                 ui.style_mut().visuals.extreme_bg_color = Color32::from_gray(0);
-                                let font = self.editor.font.clone();
-                                let size = vec2(ui.available_width() - 2.0, ui.available_height());
-                                if self.editor.should_highlight {
+                                ui.spacing_mut().item_spacing = vec2(0.0, 0.0);
+                                let mut clicked_line = None;
+                                ui.with_layout(Layout::left_to_right(Align::TOP), |ui: &mut Ui| {
+                                    // Leave room for the gutter, which is drawn to
+                                    // match the lines once the text is laid out.
+                                    let gutter_size = vec2(Self::GUTTER_WIDTH, 0.0);
+                                    let (gutter, _) =
+                                        ui.allocate_exact_size(gutter_size, Sense::hover());
+                                    let font = self.editor.font.clone();
+                                    let size =
+                                        vec2(ui.available_width() - 2.0, ui.available_height());
                                     let highlighter = self.editor.highlighter.clone();
+                                    let should_highlight = self.editor.should_highlight;
                                     let Some(file) = self.editor.get_file_mut(index) else {
                                         return;
                                     };
+                                    let breakpoints = file.breakpoints.clone();
                                     let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
                                         let job = highlighter.highlight(ui.ctx(), text, wrap_width);
                                         ui.fonts(|font| font.layout_job(job))
                                     };
-                                    let text_field = TextEdit::multiline(file)
+                                    let mut text_field = TextEdit::multiline(file)
                                         .code_editor()
                                         .font(font)
-                                        .layouter(&mut layouter);
-                                    ui.add_sized(size, text_field);
-                                } else {
-                                    let Some(file) = self.editor.get_file_mut(index) else {
-                                        return;
-                                    };
-                                    let text_field =
-                                        TextEdit::multiline(file).code_editor().font(font);
-                                    ui.add_sized(size, text_field);
+                                        .desired_width(size.x)
+                                        .min_size(size);
+                                    if should_highlight {
+                                        text_field = text_field.layouter(&mut layouter);
+                                    }
+                                    let output = text_field.show(ui);
+                                    clicked_line =
+                                        self.gutter_view(ui, gutter, &output, &breakpoints);
+                                });
+                                if let Some(line) = clicked_line {
+                                    self.toggle_breakpoint(line);
                                 }
                             });
                         }
@@ -176,6 +221,62 @@ impl App {
             });
     }
 
+    fn control_button(&self, ui: &mut Ui, text: &str, size: Vec2) -> bool {
+        let button_label = RichText::new(String::from(text))
+            .font(FontId::proportional(16.0))
+            .color(Color32::from_gray(255));
+        let button = Button::new(button_label).fill(Color32::from_gray(60));
+        ui.add_sized(size, button).clicked()
+    }
+
+    fn gutter_view(
+        &self,
+        ui: &mut Ui,
+        gutter: Rect,
+        output: &TextEditOutput,
+        breakpoints: &HashSet<usize>,
+    ) -> Option<usize> {
+        // Line numbers beside the code, which set a breakpoint when clicked.
+        let text_rect = output.response.rect;
+        let rect = Rect::from_x_y_ranges(gutter.x_range(), text_rect.y_range());
+        let response = ui.interact(rect, ui.id().with("gutter"), Sense::click());
+        let click_pos = response
+            .clicked()
+            .then(|| response.interact_pointer_pos())
+            .flatten();
+        let painter = ui.painter_at(rect);
+        let mut clicked_line = None;
+        let mut line = 1;
+        let mut starts_line = true;
+        for row in &output.galley.rows {
+            let row_rect = row.rect.translate(output.galley_pos.to_vec2());
+            if starts_line {
+                let center = pos2(rect.center().x, row_rect.center().y);
+                if breakpoints.contains(&line) {
+                    painter.circle_filled(center, 5.0, Color32::from_rgb(200, 40, 40));
+                } else {
+                    painter.text(
+                        center,
+                        Align2::CENTER_CENTER,
+                        line.to_string(),
+                        FontId::monospace(10.0),
+                        Color32::from_gray(100),
+                    );
+                }
+            }
+            if let Some(pos) = click_pos {
+                if row_rect.y_range().contains(pos.y) {
+                    clicked_line = Some(line);
+                }
+            }
+            starts_line = row.ends_with_newline;
+            if row.ends_with_newline {
+                line += 1;
+            }
+        }
+        clicked_line
+    }
+
     fn file_tab_view(
         &mut self,
         ui: &mut Ui,
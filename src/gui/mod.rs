@@ -1,3 +1,5 @@
 pub mod app;
 pub mod canvas;
+pub mod console;
+pub mod debugger;
 pub mod editor;
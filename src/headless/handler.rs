@@ -0,0 +1,37 @@
+use crate::interpreter::event::{UiContext, UiEvent, UiEventHandler};
+use std::any::Any;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+// Handles events from the interpreter when there is no window, by
+// writing anything meant for the console to standard output. The error
+// that ends a program is left for the runner to report.
+pub struct ConsoleHandler;
+
+impl UiEventHandler for ConsoleHandler {
+    fn handle_ui_event(&mut self, _ctx: Arc<Mutex<dyn UiContext>>, event: UiEvent) {
+        match event {
+            UiEvent::ConsolePrint(text) => println!("{}", text),
+            UiEvent::Announce(text) => println!("{}", text),
+            _ => (),
+        }
+    }
+}
+
+pub struct HeadlessContext;
+
+impl UiContext for HeadlessContext {
+    fn update_ui(&self) {
+        // Nothing to redraw.
+    }
+
+    fn load_image(&self, _name: Box<str>, path: String) -> Result<Box<dyn Any>, Box<dyn Error>> {
+        let Ok(reader) = image::io::Reader::open(path.clone()) else {
+            return Err(Box::from(format!("could not open path: {}", path)));
+        };
+        let Ok(image) = reader.decode() else {
+            return Err(Box::from("invalid image format"));
+        };
+        Ok(Box::from(image))
+    }
+}
@@ -1,20 +1,28 @@
-use crate::interpreter::state::object::{Line, Point, Size, TextStyle, TurtleShape};
+use crate::interpreter::state::curve::Curve;
+use crate::interpreter::state::debug::DebugSnapshot;
+use crate::interpreter::state::object::{Color, Fill, Line, Point, Size, TextStyle, TurtleShape};
 use std::any::Any;
 use std::collections::HashSet;
 use std::error::Error;
 use std::sync::{mpsc, Arc, Mutex};
 
+// Only the GUI reads the event payloads.
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum UiEvent {
     Done,
     Wait(u64),
+    Paused(DebugSnapshot),
+    Resumed,
     ConsolePrint(String),
+    // The error that ended the program.
+    ConsoleError(String),
     Announce(String),
     NewTurtle(Box<str>),
     NewText(Box<str>),
     RemoveObject(Box<str>),
     ObjectPos(Box<str>, Point),
-    ObjectColor(Box<str>, f32),
+    ObjectColor(Box<str>, Color),
     ObjectVisible(Box<str>, bool),
     ObjectSize(Box<str>, Size),
     TurtleHeading(Box<str>, f32),
@@ -24,23 +32,35 @@ pub enum UiEvent {
     TextSize(Box<str>, f32),
     TextStyle(Box<str>, HashSet<TextStyle>),
     CanvasSize(f32, f32),
-    BgColor(f32),
+    BgColor(Color),
     BgPicture(String),
     PlacePicture(String, Point, Size),
     AddLine(Box<str>, Line),
+    AddCurve(Box<str>, Curve),
+    AddFill(Fill),
     AddShape(Box<str>, String),
     Clean,
     ClearConsole,
+    Reset,
+    OpenSource(String, String),
 }
 
+// Only the GUI sends input events.
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum InputEvent {
     Interrupt,
     KeyDown(String),
     KeyUp(String),
     Click(Point),
+    Pause,
+    Resume,
+    Step,
+    SetBreakpoints(HashSet<usize>),
 }
 
+// Only the GUI calls back into its context.
+#[allow(dead_code)]
 pub trait UiContext: Send + Sync {
     fn update_ui(&self);
     fn load_image(&self, name: Box<str>, path: String) -> Result<Box<dyn Any>, Box<dyn Error>>;
@@ -80,4 +100,13 @@ impl EventHandler {
         }
         Err(mpsc::TryRecvError::Empty)
     }
+
+    pub fn has_input(&self) -> bool {
+        self.input_receiver.is_some()
+    }
+
+    pub fn wait_for_input(&self) -> Option<InputEvent> {
+        // Blocks until there is input, unless nothing can send any.
+        self.input_receiver.as_ref()?.recv().ok()
+    }
 }
@@ -1,26 +1,58 @@
 use crate::interpreter::event::{EventHandler, InputEvent, UiEvent};
 use crate::interpreter::event::{UiContext, UiEventHandler};
+use crate::interpreter::language::dictionary::flow::loop_condition;
 use crate::interpreter::language::lexer::Lexer;
-use crate::interpreter::language::structure::{Params, Procedure};
-use crate::interpreter::language::token::Token;
+use crate::interpreter::language::structure::{BlockCache, Params, ParsedBlock, Procedure};
+use crate::interpreter::language::token::{List, Token};
 use crate::interpreter::language::util::decode;
-use crate::interpreter::state::object::Object;
+use crate::interpreter::state::datastore::Scope;
+use crate::interpreter::state::debug::DebugSnapshot;
+use crate::interpreter::state::object::{Drawing, Object, TurtleShape};
+use crate::interpreter::state::process::{Frame, FrameKind, Process};
 use crate::interpreter::state::state::State;
-use crate::interpreter::util::error::{interrupt_error, is_eof, is_interrupt};
+use crate::interpreter::util::error::{catch_return, describe_error, interrupt_error};
+use crate::interpreter::util::error::{is_interrupt, is_return, is_signal, is_stop_all};
+use crate::interpreter::util::error::{LogoError, SourceLocation, StackFrame};
 use crate::interpreter::util::performance::PerformanceTracker;
+use crate::interpreter::util::project;
+use std::collections::VecDeque;
 use std::error::Error;
+use std::path::Path;
 use std::sync::{mpsc, Arc, Mutex};
-
-#[cfg(feature = "performance")]
-use std::time::Instant;
-
-static DEBUG: bool = false;
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub struct Interpreter {
     pub lexer: Lexer,
     pub state: State,
     pub event: EventHandler,
     pub performance: PerformanceTracker,
+    source_name: Box<str>,
+    source: String,
+    call_stack: Vec<CallFrame>,
+    block_depth: usize,
+    // Counts for the "repeat" loops that are running, innermost last.
+    repeat_counts: Vec<usize>,
+    // The statement running in the body of the current procedure, or at the
+    // top level, so that processes know where they were launched from.
+    current_statement: Option<(Arc<ParsedBlock>, usize)>,
+    // Where the process that is running was launched from.
+    process_origin: Option<StackFrame>,
+}
+
+// A procedure that is currently running, used to report where errors happen.
+struct CallFrame {
+    procedure: Box<str>,
+    line: usize,
+    // The depth of the block that holds the body of the procedure.
+    root_depth: usize,
+}
+
+// The inputs of a call paired with the parameters that take them, and the
+// optional parameters left to their defaults.
+struct BoundInputs {
+    local_params: Vec<(String, Token)>,
+    defaults: Vec<(String, String)>,
 }
 
 impl Interpreter {
@@ -30,105 +62,853 @@ impl Interpreter {
             state: State::new(),
             event: EventHandler::new(),
             performance: PerformanceTracker::new(),
+            source_name: Box::from("untitled"),
+            source: String::new(),
+            call_stack: vec![],
+            block_depth: 0,
+            repeat_counts: vec![],
+            current_statement: None,
+            process_origin: None,
         }
     }
 
-    pub fn interpret_main(&mut self, code: &str) {
+    pub fn set_source_name(&mut self, name: &str) {
+        self.source_name = Box::from(name);
+    }
+
+    pub fn interpret_main(&mut self, code: &str) -> Result<Token, Box<dyn Error>> {
         self.state.reset_timer();
-        let _ = self.execute_code(code, false, true);
+        self.source = code.to_string();
+        self.execute_code(code, true)
     }
 
+    #[allow(dead_code)]
     pub fn interpret(&mut self, code: &str) -> Result<Token, Box<dyn Error>> {
-        self.execute_code(code, false, false)
+        self.execute_code(code, false)
+    }
+
+    pub fn interpret_list(&mut self, list: &List) -> Result<Token, Box<dyn Error>> {
+        let block = self.lexer.parse_list(list, false)?;
+        self.execute_block(block, false)
     }
 
-    pub fn interpret_in_parenthesis(&mut self, code: &str) -> Result<Token, Box<dyn Error>> {
-        self.execute_code(code, true, false)
+    #[allow(dead_code)]
+    pub fn interpret_command(&mut self, code: &str) -> Result<Token, Box<dyn Error>> {
+        // Commands typed into the console run against the current workspace,
+        // along with any processes they start.
+        let result = catch_return(self.interpret(code)).and_then(|value| {
+            self.run_processes()?;
+            Ok(value)
+        });
+        let result = match result {
+            Err(err) if is_stop_all(err.as_ref()) => Ok(Token::Void),
+            result => result,
+        };
+        if result.is_err() {
+            self.stop_processes();
+        }
+        result
+    }
+
+    pub fn evaluate_in_parenthesis(&mut self, list: &List) -> Result<Token, Box<dyn Error>> {
+        let block = self.lexer.parse_list(list, true)?;
+        self.execute_block(block, true)
+    }
+
+    pub fn evaluate(&mut self, code: &str) -> Result<Token, Box<dyn Error>> {
+        // Like interpret, but reports the value of the last statement,
+        // so that [:x + 1] has a value.
+        let block = self.lexer.parse(code, false)?;
+        self.execute_block(block, true)
+    }
+
+    pub fn evaluate_list(&mut self, list: &List) -> Result<Token, Box<dyn Error>> {
+        let block = self.lexer.parse_list(list, false)?;
+        self.execute_block(block, true)
+    }
+
+    pub fn interpret_repeatedly(
+        &mut self,
+        list: &List,
+        count: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        self.repeat_counts.push(0);
+        let mut result = Ok(());
+        for index in 1..=count {
+            *self.repeat_counts.last_mut().unwrap() = index;
+            if let Err(err) = self.interpret_list(list) {
+                result = Err(err);
+                break;
+            }
+        }
+        self.repeat_counts.pop();
+        result
+    }
+
+    pub fn repeat_count(&self) -> Option<usize> {
+        self.repeat_counts.last().copied()
     }
 
     pub fn interpret_in_new_scope(
         &mut self,
-        code: &str,
+        list: &List,
+        local_params: Vec<(String, Token)>,
+    ) -> Result<Token, Box<dyn Error>> {
+        self.in_new_scope(local_params, |int| int.interpret_list(list))
+    }
+
+    pub fn evaluate_in_new_scope(
+        &mut self,
+        list: &List,
         local_params: Vec<(String, Token)>,
+    ) -> Result<Token, Box<dyn Error>> {
+        self.in_new_scope(local_params, |int| int.evaluate_list(list))
+    }
+
+    fn in_new_scope(
+        &mut self,
+        local_params: Vec<(String, Token)>,
+        action: impl FnOnce(&mut Self) -> Result<Token, Box<dyn Error>>,
     ) -> Result<Token, Box<dyn Error>> {
         if self.state.data.reached_max_scope_depth() {
             return Err(Box::from("maximum stack depth exceeded"));
         }
         self.state.data.push_scope();
-        for (param, arg) in &local_params {
-            self.state.data.set_variable(param, arg.clone());
-        }
-        let return_value = self.interpret(code);
-        for (param, _) in &local_params {
-            self.state.data.remove_variable(&param);
+        for (param, arg) in local_params {
+            self.state.data.init_local(&param, arg);
         }
+        let return_value = action(self);
         self.state.data.pop_scope();
         return_value
     }
 
-    fn execute_code(
+    fn execute_code(&mut self, code: &str, handle_error: bool) -> Result<Token, Box<dyn Error>> {
+        let mut result = match self.lexer.parse(code, false) {
+            Ok(block) => self.execute_block(block, false),
+            Err(err) => Err(err),
+        };
+        if handle_error {
+            result = match catch_return(result) {
+                // Stopping everything is a clean end of the program.
+                Err(err) if is_stop_all(err.as_ref()) => Ok(Token::Void),
+                result => result,
+            };
+            // Processes started by the program keep running after it ends.
+            if result.is_ok() {
+                if let Err(err) = self.run_processes() {
+                    result = Err(err);
+                }
+            }
+            if let Err(err) = &result {
+                if !is_interrupt(err) {
+                    let message = describe_error(err.as_ref());
+                    self.event.send_ui(UiEvent::ConsoleError(message));
+                }
+            }
+            self.terminate_program();
+        }
+        result
+    }
+
+    fn execute_block(
         &mut self,
-        code: &str,
-        in_paren: bool,
-        handle_error: bool,
+        block: Arc<ParsedBlock>,
+        report_last: bool,
     ) -> Result<Token, Box<dyn Error>> {
-        if code.is_empty() {
-            return Ok(Token::Void);
+        self.block_depth += 1;
+        let result = self.execute_statements(block, report_last);
+        self.block_depth -= 1;
+        result
+    }
+
+    fn execute_statements(
+        &mut self,
+        mut block: Arc<ParsedBlock>,
+        report_last: bool,
+    ) -> Result<Token, Box<dyn Error>> {
+        let mut index = 0;
+        let mut last_value = Token::Void;
+        while let Some(statement) = block.statements.get(index) {
+            while let Ok(input_event) = self.event.receive_input() {
+                self.handle_input(input_event)?;
+            }
+            self.step_processes()?;
+            if self.state.debug.is_active() {
+                self.check_for_pause(&block, index)?;
+            }
+            let is_root = self.block_depth == self.root_depth();
+            let outer_statement =
+                is_root.then(|| self.current_statement.replace((block.clone(), index)));
+            let result = self.execute_command(statement);
+            if let Some(outer_statement) = outer_statement {
+                self.current_statement = outer_statement;
+            }
+            let return_value = match result {
+                Ok(value) => value,
+                Err(err) => return Err(self.locate_error(err, &block, index)),
+            };
+            if let Token::Command(command, _) = statement {
+                if command.is("again") {
+                    index = 0;
+                    continue;
+                }
+            }
+            if report_last {
+                last_value = return_value;
+            }
+            if !self.lexer.is_current(&block) {
+                // A new definition may change how the rest of the block
+                // is read, so parse the remaining code again.
+                let end_position = block.end_positions[index];
+                block = self.lexer.parse_from(&block, end_position)?;
+                index = 0;
+            } else {
+                index += 1;
+            }
+        }
+        Ok(last_value)
+    }
+
+    fn is_stepping(&self) -> bool {
+        // Only statements in the body of the procedure itself are stepped
+        // through, not those inside its loops or other blocks.
+        match self.call_stack.last() {
+            Some(frame) => {
+                self.block_depth == frame.root_depth
+                    && self.state.debug.is_stepped(&frame.procedure)
+            }
+            None => false,
+        }
+    }
+
+    fn check_for_pause(&mut self, block: &ParsedBlock, index: usize) -> Result<(), Box<dyn Error>> {
+        let line = self
+            .source_location(block, index)
+            .map(|location| location.line);
+        let at_breakpoint = match line {
+            // Pause once for a line, even when it holds several statements.
+            Some(line) if self.state.debug.is_breakpoint(line) => {
+                index == 0
+                    || self
+                        .source_location(block, index - 1)
+                        .map_or(true, |location| location.line != line)
+            }
+            _ => false,
+        };
+        let is_stepping = self.is_stepping();
+        if !at_breakpoint && !is_stepping && !self.state.debug.pause_requested {
+            return Ok(());
+        }
+        if is_stepping {
+            let start = block.start_positions[index];
+            let end = block.end_positions[index];
+            let text: String = block.chars[start..end].iter().collect();
+            let indent = "  ".repeat(self.call_stack.len() - 1);
+            let name = &self.call_stack.last().unwrap().procedure;
+            let message = format!("{}[{}] {}", indent, name, text.trim());
+            self.event.send_ui(UiEvent::ConsolePrint(message));
+        }
+        self.pause(line, is_stepping)
+    }
+
+    fn pause(&mut self, line: Option<usize>, is_stepping: bool) -> Result<(), Box<dyn Error>> {
+        self.state.debug.pause_requested = false;
+        let snapshot = self.debug_snapshot(line);
+        self.event.send_ui(UiEvent::Paused(snapshot));
+        let result = self.wait_to_resume(is_stepping);
+        self.event.send_ui(UiEvent::Resumed);
+        result
+    }
+
+    fn wait_to_resume(&mut self, is_stepping: bool) -> Result<(), Box<dyn Error>> {
+        // Blocks until told to go on, while still handling other input. Enter
+        // only goes on from a stepped line, and is a key for the program
+        // at other pauses.
+        while let Some(input_event) = self.event.wait_for_input() {
+            match input_event {
+                InputEvent::Resume => break,
+                InputEvent::KeyDown(key) if is_stepping && key == "enter" => break,
+                InputEvent::Step => {
+                    self.state.debug.pause_requested = true;
+                    break;
+                }
+                input_event => self.handle_input(input_event)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn debug_snapshot(&self, line: Option<usize>) -> DebugSnapshot {
+        let call_stack = self
+            .call_stack
+            .iter()
+            .map(|frame| frame.procedure.to_string())
+            .collect();
+        let scopes = self
+            .state
+            .data
+            .get_scopes()
+            .into_iter()
+            .map(|scope| {
+                let mut variables: Vec<(String, String)> = scope
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+                variables.sort();
+                variables
+            })
+            .collect();
+        let turtle = match self.state.canvas.current_object() {
+            Ok(Object::Turtle(turtle)) => DebugSnapshot::turtle_properties(turtle),
+            _ => vec![],
+        };
+        DebugSnapshot {
+            line,
+            call_stack,
+            scopes,
+            turtle,
+        }
+    }
+
+    pub fn launch_process(&mut self, name: &str, code: String, is_forever: bool) {
+        let origin = self.statement_origin();
+        // The process can see the local variables of the procedure that
+        // launched it.
+        let scope = self.state.data.copy_local_scopes();
+        let object = self.state.canvas.current_object_name().to_string();
+        let processes = &mut self.state.processes;
+        processes.launch(name, code, is_forever, &object, origin, scope);
+    }
+
+    pub fn add_trigger(&mut self, condition: List, action: &str) {
+        let origin = self.statement_origin();
+        let object = self.state.canvas.current_object_name().to_string();
+        let triggers = &mut self.state.triggers;
+        triggers.add_when(condition, action, &object, origin);
+    }
+
+    fn statement_origin(&self) -> Option<StackFrame> {
+        // Code started by a process shares the origin of the process.
+        match &self.current_statement {
+            Some((block, index)) => Some(StackFrame {
+                procedure: self.call_stack.last().map(|frame| frame.procedure.clone()),
+                location: self.statement_location(block, *index),
+            }),
+            None => self.process_origin.clone(),
+        }
+    }
+
+    pub fn run_processes(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.run_processes_to_end() {
+            Err(err) if is_stop_all(err.as_ref()) => Ok(()),
+            result => result,
         }
-        self.lexer.push_block(code, in_paren);
-        loop {
+    }
+
+    fn run_processes_to_end(&mut self) -> Result<(), Box<dyn Error>> {
+        while !self.state.processes.is_empty() || !self.state.triggers.is_empty() {
+            // With nothing left running and no input that could set off a
+            // trigger, the program is done.
+            if self.state.processes.is_empty() && !self.event.has_input() {
+                break;
+            }
             while let Ok(input_event) = self.event.receive_input() {
                 self.handle_input(input_event)?;
             }
-            let token = match self.lexer.read_token() {
-                Ok(token) => token,
+            if !self.state.processes.has_awake() {
+                // Only waiting on triggers or a wait, so give the rest of
+                // the app a turn.
+                thread::sleep(Duration::from_millis(1));
+            }
+            self.step_processes()?;
+        }
+        Ok(())
+    }
+
+    pub fn wait(&mut self, duration: u64) -> Result<(), Box<dyn Error>> {
+        self.event.send_ui(UiEvent::Wait(duration));
+        let duration = Duration::from_millis(duration);
+        // A process waiting inside an expression can't give up its turn
+        // there, so it holds up the others.
+        if self.state.processes.is_running {
+            thread::sleep(duration);
+            return Ok(());
+        }
+        // Processes keep running while the main program waits.
+        let end = Instant::now() + duration;
+        while let Some(left) = end.checked_duration_since(Instant::now()) {
+            while let Ok(input_event) = self.event.receive_input() {
+                self.handle_input(input_event)?;
+            }
+            self.step_processes()?;
+            if !self.state.processes.has_awake() {
+                thread::sleep(left.min(Duration::from_millis(1)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop_processes(&mut self) {
+        self.state.processes.clear();
+        self.state.triggers.clear();
+    }
+
+    fn step_processes(&mut self) -> Result<(), Box<dyn Error>> {
+        // Processes and triggers take turns between statements of the code that
+        // is running, but never start another turn from inside one of their own.
+        if self.state.processes.is_running
+            || (self.state.processes.is_empty() && self.state.triggers.is_empty())
+        {
+            return Ok(());
+        }
+        self.state.processes.is_running = true;
+        let result = self.check_triggers().and_then(|_| self.step_each_process());
+        self.state.processes.is_running = false;
+        result
+    }
+
+    fn step_each_process(&mut self) -> Result<(), Box<dyn Error>> {
+        for id in self.state.processes.ids() {
+            let Some(mut process) = self.state.processes.take(id) else {
+                continue;
+            };
+            let origin = std::mem::replace(&mut self.process_origin, process.origin.clone());
+            let result = self.step_process(&mut process);
+            self.process_origin = origin;
+            match result {
+                Ok(true) => self.state.processes.put_back(process),
+                Ok(false) => self.state.processes.finish(),
                 Err(err) => {
-                    if is_eof(&err) {
-                        self.exit_scope();
-                        break;
+                    // An error in a process ends the program, and is
+                    // reported where the process was launched.
+                    self.state.processes.finish();
+                    let mut error = LogoError::from_error(err);
+                    if let Some(origin) = process.origin {
+                        error.push_frame(origin.procedure.as_deref(), origin.location);
                     }
-                    if handle_error {
-                        println!("error: {}", err);
-                        self.event.send_ui(UiEvent::ConsolePrint(err.to_string()));
-                        self.terminate_program();
-                    } else {
-                        self.exit_scope();
+                    return Err(Box::from(error));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn step_process(&mut self, process: &mut Process) -> Result<bool, Box<dyn Error>> {
+        if let Some(wake_at) = process.wake_at {
+            if Instant::now() < wake_at {
+                return Ok(true);
+            }
+            process.wake_at = None;
+        }
+        let frames = &mut process.frames;
+        let wake_at = &mut process.wake_at;
+        self.with_context(&mut process.object, &mut process.scopes, |int| {
+            int.step_frames(frames, wake_at)
+        })
+    }
+
+    fn step_frames(
+        &mut self,
+        frames: &mut Vec<Frame>,
+        wake_at: &mut Option<Instant>,
+    ) -> Result<bool, Box<dyn Error>> {
+        // Runs the next statement of the innermost block, or finishes
+        // the block when it has run them all.
+        let Some(frame) = frames.last_mut() else {
+            return Ok(false);
+        };
+        let block = self.frame_block(frame)?;
+        let Some(statement) = block.statements.get(frame.index) else {
+            return self.end_frame(frames, block.statements.is_empty());
+        };
+        frame.index += 1;
+
+        self.block_depth = frames.len();
+        self.call_stack = frames
+            .iter()
+            .enumerate()
+            .filter_map(|(depth, frame)| match &frame.kind {
+                FrameKind::Procedure { name, line } => Some(CallFrame {
+                    procedure: name.clone(),
+                    line: *line,
+                    root_depth: depth + 1,
+                }),
+                _ => None,
+            })
+            .collect();
+        self.repeat_counts = frames
+            .iter()
+            .filter_map(|frame| match frame.kind {
+                FrameKind::Repeat { count, .. } | FrameKind::Forever { count } => Some(count),
+                _ => None,
+            })
+            .collect();
+        if self.state.debug.pause_requested {
+            self.pause(None, false)?;
+        }
+        match self.execute_in_frame(frames, statement, wake_at) {
+            Ok(()) => Ok(true),
+            // Stop and output leave the procedure they are in, or end the
+            // process at its top level.
+            Err(err) if is_return(err.as_ref()) => {
+                while let Some(frame) = frames.pop() {
+                    if let FrameKind::Procedure { .. } = frame.kind {
+                        self.state.data.pop_scope();
+                        return Ok(true);
                     }
-                    return Err(err);
                 }
-            };
-            let is_return = if let Token::Command(command, _) = &token {
-                command.is("output")
-            } else {
+                Ok(false)
+            }
+            Err(err) if is_signal(err.as_ref()) => Err(err),
+            Err(err) => Err(self.locate_frame_error(err, frames)),
+        }
+    }
+
+    fn frame_block(&mut self, frame: &mut Frame) -> Result<Arc<ParsedBlock>, Box<dyn Error>> {
+        let block = match &frame.block {
+            Some(block) if self.lexer.is_current(block) => return Ok(block.clone()),
+            Some(block) => {
+                // Read the rest of the block again after a new definition.
+                let position = match block.start_positions.get(frame.index) {
+                    Some(position) => *position,
+                    None => block.chars.len(),
+                };
+                frame.index = 0;
+                self.lexer.parse_from(block, position)?
+            }
+            None => self.lexer.parse_cached(&frame.code, false, &frame.cache)?,
+        };
+        frame.block = Some(block.clone());
+        Ok(block)
+    }
+
+    fn end_frame(
+        &mut self,
+        frames: &mut Vec<Frame>,
+        is_empty: bool,
+    ) -> Result<bool, Box<dyn Error>> {
+        let frame = frames.last_mut().unwrap();
+        let is_again = match &mut frame.kind {
+            FrameKind::Block => false,
+            FrameKind::Repeat { count, total } => {
+                *count += 1;
+                count <= total
+            }
+            FrameKind::Forever { count } => {
+                *count += 1;
+                !is_empty
+            }
+            FrameKind::While { condition, until } => {
+                let com = if *until { "until" } else { "while" };
+                let until = *until;
+                let condition = condition.clone();
+                loop_condition(self, com, &condition)? != until
+            }
+            FrameKind::Procedure { .. } => {
+                self.state.data.pop_scope();
                 false
-            };
-            match self.execute_command(token) {
-                Ok(token) => {
-                    if is_return {
-                        self.exit_scope();
-                        return Ok(token);
+            }
+        };
+        let frame = frames.last_mut().unwrap();
+        if is_again {
+            frame.index = 0;
+            frame.block = None;
+        } else {
+            frames.pop();
+        }
+        Ok(!frames.is_empty())
+    }
+
+    fn execute_in_frame(
+        &mut self,
+        frames: &mut Vec<Frame>,
+        statement: &Token,
+        wake_at: &mut Option<Instant>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Blocks and procedures run as frames of their own, so that the
+        // process can take turns inside them. Everything else runs whole.
+        let Token::Command(command, args) = statement else {
+            return self.execute_command(statement).map(|_| ());
+        };
+        let com = &*command.name;
+        let is_block = ["repeat", "if", "ifelse", "while", "until", "wait"].contains(&com);
+        let is_procedure = !command.is_reserved
+            && self.state.data.get_procedure(com).is_some()
+            && !self.state.debug.is_traced(com)
+            && !self.state.debug.is_stepped(com);
+        if command.is("again") {
+            frames.last_mut().unwrap().index = 0;
+            return Ok(());
+        }
+        if !is_block && !is_procedure {
+            return self.execute_command(statement).map(|_| ());
+        }
+        let mut inputs = vec![];
+        for arg in args {
+            inputs.push(self.execute_command(arg)?);
+        }
+        let frame = match com {
+            "repeat" => {
+                let total = decode::number(com, &inputs, 0)? as usize;
+                let code = decode::list(com, &inputs, 1)?;
+                let kind = FrameKind::Repeat { count: 1, total };
+                (total > 0).then(|| Frame::from_list(&code, kind))
+            }
+            "if" => {
+                let condition = decode::boolean(com, &inputs, 0)?;
+                let code = decode::list(com, &inputs, 1)?;
+                condition.then(|| Frame::from_list(&code, FrameKind::Block))
+            }
+            "ifelse" => {
+                let condition = decode::boolean(com, &inputs, 0)?;
+                let index = if condition { 1 } else { 2 };
+                let code = decode::list(com, &inputs, index)?;
+                Some(Frame::from_list(&code, FrameKind::Block))
+            }
+            "while" | "until" => {
+                let condition = decode::list(com, &inputs, 0)?;
+                let code = decode::list(com, &inputs, 1)?;
+                let until = com == "until";
+                let is_looping = loop_condition(self, com, &condition)? != until;
+                let kind = FrameKind::While { condition, until };
+                is_looping.then(|| Frame::from_list(&code, kind))
+            }
+            "wait" => {
+                // Only this process waits, the others go on.
+                let duration = decode::number(com, &inputs, 0)? as u64;
+                self.event.send_ui(UiEvent::Wait(duration));
+                *wake_at = Some(Instant::now() + Duration::from_millis(duration));
+                None
+            }
+            _ => Some(self.enter_procedure(com, inputs)?),
+        };
+        frames.extend(frame);
+        Ok(())
+    }
+
+    fn enter_procedure(&mut self, name: &str, args: Vec<Token>) -> Result<Frame, Box<dyn Error>> {
+        let Some(proc) = self.state.data.get_procedure(name) else {
+            return Err(Box::from(LogoError::unknown_procedure(format!(
+                "I don't know how to {}",
+                name
+            ))));
+        };
+        let BoundInputs {
+            local_params,
+            defaults,
+        } = Self::bind_inputs(proc, args)?;
+        let kind = FrameKind::Procedure {
+            name: proc.name.clone(),
+            line: proc.line,
+        };
+        let frame = Frame::new(proc.code.clone(), proc.block.clone(), kind);
+        // The scope stays until the frame ends.
+        if self.state.data.reached_max_scope_depth() {
+            return Err(Box::from("maximum stack depth exceeded"));
+        }
+        self.state.data.push_scope();
+        for (param, arg) in local_params {
+            self.state.data.init_local(&param, arg);
+        }
+        for (param, default) in defaults {
+            match self.evaluate(&default) {
+                Ok(value) => self.state.data.init_local(&param, value),
+                Err(err) => {
+                    self.state.data.pop_scope();
+                    return Err(err);
+                }
+            }
+        }
+        Ok(frame)
+    }
+
+    fn locate_frame_error(&self, err: Box<dyn Error>, frames: &[Frame]) -> Box<dyn Error> {
+        // Each procedure the process is inside adds where it was, at the
+        // statement it is running.
+        let mut error = LogoError::from_error(err);
+        for frame in frames.iter().rev() {
+            if let (FrameKind::Procedure { name, line }, Some(block)) = (&frame.kind, &frame.block)
+            {
+                let (block_line, column) = block.location(frame.index.saturating_sub(1));
+                let location = SourceLocation {
+                    file: self.source_name.clone(),
+                    line: line + block_line - 1,
+                    column,
+                };
+                error.push_frame(Some(name), location);
+            }
+        }
+        Box::from(error)
+    }
+
+    fn check_triggers(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut index = 0;
+        while let Some(trigger) = self.state.triggers.whens.get(index) {
+            let mut trigger = trigger.clone();
+            let mut scopes = VecDeque::new();
+            let result = self.with_context(&mut trigger.object, &mut scopes, |int| {
+                int.evaluate_at_top_level(&trigger.condition)
+            });
+            let err = match result {
+                Ok(Token::Boolean(is_true)) => {
+                    // Only fire when the condition changes to true.
+                    if is_true && !trigger.was_true {
+                        let action = &trigger.action;
+                        let processes = &mut self.state.processes;
+                        let object = &trigger.object;
+                        let code = action.to_string();
+                        let origin = trigger.origin.clone();
+                        processes.launch(action, code, false, object, origin, Scope::new());
                     }
+                    if let Some(trigger) = self.state.triggers.whens.get_mut(index) {
+                        trigger.was_true = is_true;
+                    }
+                    index += 1;
+                    continue;
                 }
-                Err(err) => {
-                    if handle_error {
-                        if is_interrupt(&err) {
-                            println!("Program Ended");
-                        } else {
-                            println!("error: {}", err);
-                            self.event.send_ui(UiEvent::ConsolePrint(err.to_string()));
-                        }
-                        self.terminate_program();
-                    } else {
-                        self.exit_scope();
+                Ok(..) => Box::from("when expected true or false for its condition"),
+                Err(err) if is_signal(err.as_ref()) => return Err(err),
+                Err(err) => err,
+            };
+            // Like an error in a process, an error in a condition ends the
+            // program, and is reported where the trigger was set up.
+            let mut error = LogoError::from_error(err);
+            if let Some(origin) = trigger.origin {
+                error.push_frame(origin.procedure.as_deref(), origin.location);
+            }
+            return Err(Box::from(error));
+        }
+        if self.state.triggers.has_turtle_events {
+            self.check_turtle_events();
+        }
+        Ok(())
+    }
+
+    fn check_turtle_events(&mut self) {
+        let canvas = &self.state.canvas;
+        let triggers = &mut self.state.triggers;
+        let mut actions = vec![];
+        for name in std::mem::take(&mut triggers.clicked) {
+            if let Ok(Object::Turtle(turtle)) = canvas.get_object(&name) {
+                if let Some(code) = &turtle.onclick {
+                    actions.push((turtle.name.clone(), code.clone()));
+                }
+            }
+        }
+        for turtle in canvas.turtles() {
+            if let Some(code) = &turtle.ontouching {
+                let is_touching = canvas
+                    .turtles()
+                    .any(|other| other.name != turtle.name && turtle.is_touching(other));
+                if !is_touching {
+                    triggers.touching.remove(&turtle.name);
+                } else if triggers.touching.insert(turtle.name.clone()) {
+                    actions.push((turtle.name.clone(), code.clone()));
+                }
+            }
+            if !turtle.oncolor.is_empty() {
+                let color = canvas.color_at_point(&turtle.pos);
+                let last_color = triggers.colors_under.insert(turtle.name.clone(), color);
+                if last_color != Some(color) {
+                    // Colors match by what they look like, so a color
+                    // number matches the same color as a list.
+                    let rgba = color.to_rgba();
+                    let code = turtle
+                        .oncolor
+                        .iter()
+                        .find(|(watched, _)| watched.to_rgba() == rgba);
+                    if let Some((_, code)) = code {
+                        actions.push((turtle.name.clone(), code.clone()));
                     }
-                    return Err(err);
                 }
             }
         }
-        Ok(Token::Void)
+        for (object, code) in actions {
+            self.state
+                .processes
+                .launch(&code, code.clone(), false, &object, None, Scope::new());
+        }
+    }
+
+    // Runs code at the top level of the program, as the given object and
+    // with the given local variables, rather than wherever it is called from.
+    fn with_context<T>(
+        &mut self,
+        object: &mut Box<str>,
+        scopes: &mut VecDeque<Scope>,
+        action: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let main_scopes = self.state.data.swap_local_scopes(std::mem::take(scopes));
+        let main_object = self.state.canvas.current_object_name().to_string();
+        self.state.canvas.set_current_object(object);
+        let main_call_stack = std::mem::take(&mut self.call_stack);
+        let main_block_depth = std::mem::replace(&mut self.block_depth, 1);
+        let main_repeat_counts = std::mem::take(&mut self.repeat_counts);
+        let main_statement = self.current_statement.take();
+
+        let result = action(self);
+
+        self.current_statement = main_statement;
+        self.repeat_counts = main_repeat_counts;
+        self.block_depth = main_block_depth;
+        self.call_stack = main_call_stack;
+        *object = Box::from(self.state.canvas.current_object_name());
+        self.state.canvas.set_current_object(&main_object);
+        *scopes = self.state.data.swap_local_scopes(main_scopes);
+        result
+    }
+
+    fn evaluate_at_top_level(&mut self, code: &List) -> Result<Token, Box<dyn Error>> {
+        // Reports the value of the last statement in the code.
+        let block = self.lexer.parse_list(code, false)?;
+        let mut value = Token::Void;
+        for statement in &block.statements {
+            value = catch_return(self.execute_command(statement))?;
+        }
+        Ok(value)
+    }
+
+    fn locate_error(
+        &self,
+        err: Box<dyn Error>,
+        block: &ParsedBlock,
+        index: usize,
+    ) -> Box<dyn Error> {
+        let mut error = LogoError::from_error(err);
+
+        // Errors pick up a frame as they pass through the body of each
+        // running procedure, and finally the top level of the program.
+        if let Some(location) = self.source_location(block, index) {
+            let frame = self.call_stack.last();
+            error.push_frame(frame.map(|frame| &*frame.procedure), location);
+        }
+        Box::from(error)
+    }
+
+    fn source_location(&self, block: &ParsedBlock, index: usize) -> Option<SourceLocation> {
+        // Only statements in the body of a procedure or at the top level of
+        // the program know where they are in the source.
+        if self.block_depth != self.root_depth() {
+            return None;
+        }
+        Some(self.statement_location(block, index))
+    }
+
+    fn statement_location(&self, block: &ParsedBlock, index: usize) -> SourceLocation {
+        let (line, column) = block.location(index);
+        let start_line = self.call_stack.last().map_or(1, |frame| frame.line);
+        SourceLocation {
+            file: self.source_name.clone(),
+            line: start_line + line - 1,
+            column,
+        }
+    }
+
+    fn root_depth(&self) -> usize {
+        self.call_stack.last().map_or(1, |frame| frame.root_depth)
     }
 
-    fn execute_command(&mut self, token: Token) -> Result<Token, Box<dyn Error>> {
+    fn execute_command(&mut self, token: &Token) -> Result<Token, Box<dyn Error>> {
         match token {
             Token::Command(command, args) => {
                 let mut computed_args = vec![];
@@ -136,9 +916,6 @@ impl Interpreter {
                     let computed_arg = self.execute_command(arg)?;
                     computed_args.push(computed_arg);
                 }
-                if DEBUG {
-                    println!("{} {:?}", command.name, computed_args);
-                }
                 #[cfg(not(feature = "performance"))]
                 {
                     (command.action)(self, &command.name, computed_args)
@@ -156,43 +933,215 @@ impl Interpreter {
                     Ok(result)
                 }
             }
-            Token::Variable(variable) => match self.state.data.get_variable(&variable) {
+            Token::Variable(variable) => match self.state.data.get_variable(variable) {
                 Some(stored) => Ok(stored.clone()),
-                None => Err(Box::from(format!("{} has no value", variable))),
+                None => Err(Box::from(LogoError::unbound_variable(format!(
+                    "{} has no value",
+                    variable
+                )))),
             },
-            Token::Undefined(undefined) => {
-                Err(Box::from(format!("I don't know how to {}", undefined)))
-            }
-            other => Ok(other),
+            Token::Undefined(undefined) => Err(Box::from(LogoError::unknown_procedure(format!(
+                "I don't know how to {}",
+                undefined
+            )))),
+            other => Ok(other.clone()),
         }
     }
 
     pub fn define_procedure(&mut self, procedure: Procedure) -> Result<(), Box<dyn Error>> {
         self.lexer.define(
             &procedure.name,
-            Params::Fixed(procedure.params.len()),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
-                let proc = int.state.data.get_procedure(com).unwrap();
-                if proc.params.len() != args.len() {
-                    return Err(Box::from(format!(
-                        "{} expected {} inputs",
-                        proc.name,
-                        proc.params.len()
-                    )));
-                }
-                let mut local_params = vec![];
-                for i in 0..proc.params.len() {
-                    local_params.push((proc.params[i].clone(), args[i].clone()));
-                }
-                let code = proc.code.clone();
-                int.interpret_in_new_scope(&code, local_params)
-            },
+            procedure.params.arity(),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| int.call_procedure(com, args),
         )?;
         self.state.data.set_procedure(procedure);
         Ok(())
     }
 
+    pub fn erase_procedure(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.lexer.undefine(name)?;
+        self.state.data.remove_procedure(name);
+        Ok(())
+    }
+
+    pub fn copy_definition(&mut self, new_name: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        if let Some(procedure) = self.state.data.get_procedure(name) {
+            let procedure = Procedure {
+                name: Box::from(new_name),
+                block: BlockCache::default(),
+                ..procedure.clone()
+            };
+            return self.define_procedure(procedure);
+        }
+        // Primitives are copied by sharing their action under the new name.
+        match self.lexer.lookup(name) {
+            Some(command) => self.lexer.define_copy(new_name, command),
+            None => Err(Box::from(LogoError::unknown_procedure(format!(
+                "I don't know how to {}",
+                name
+            )))),
+        }
+    }
+
+    pub fn call_procedure(
+        &mut self,
+        name: &str,
+        args: Vec<Token>,
+    ) -> Result<Token, Box<dyn Error>> {
+        let Some(proc) = self.state.data.get_procedure(name) else {
+            return Err(Box::from(LogoError::unknown_procedure(format!(
+                "I don't know how to {}",
+                name
+            ))));
+        };
+        let BoundInputs {
+            local_params,
+            defaults,
+        } = Self::bind_inputs(proc, args)?;
+        let block = self.lexer.parse_cached(&proc.code, false, &proc.block)?;
+        let is_traced = self.state.debug.is_traced(&proc.name);
+        let indent = "  ".repeat(self.call_stack.len());
+        if is_traced {
+            let mut message = format!("{}( {}", indent, proc.name);
+            for (_, arg) in &local_params {
+                message += &format!(" {}", arg.to_string());
+            }
+            self.event.send_ui(UiEvent::ConsolePrint(message + " )"));
+        }
+        let frame = CallFrame {
+            procedure: proc.name.clone(),
+            line: proc.line,
+            root_depth: self.block_depth + 1,
+        };
+        self.call_stack.push(frame);
+        let result = catch_return(self.in_new_scope(local_params, |int| {
+            // Defaults are worked out for each call, so they can use the
+            // inputs before them.
+            for (param, default) in defaults {
+                let value = int.evaluate(&default)?;
+                int.state.data.init_local(&param, value);
+            }
+            int.execute_block(block, false)
+        }));
+        let name = self.call_stack.pop().unwrap().procedure;
+        if is_traced {
+            let message = match &result {
+                Ok(Token::Void) => Some(format!("{}{} stops", indent, name)),
+                Ok(value) => Some(format!("{}{} outputs {}", indent, name, value.to_string())),
+                Err(..) => None,
+            };
+            if let Some(message) = message {
+                self.event.send_ui(UiEvent::ConsolePrint(message));
+            }
+        }
+        result
+    }
+
+    fn bind_inputs(proc: &Procedure, args: Vec<Token>) -> Result<BoundInputs, Box<dyn Error>> {
+        let params = &proc.params;
+        let least = params.required.len();
+        let most = least + params.optional.len();
+        if args.len() < least || (params.rest.is_none() && args.len() > most) {
+            let expected = if params.rest.is_some() {
+                format!("at least {}", least)
+            } else if most > least {
+                format!("{} to {}", least, most)
+            } else {
+                least.to_string()
+            };
+            return Err(Box::from(format!(
+                "{} expected {} inputs",
+                proc.name, expected
+            )));
+        }
+        let mut args = args.into_iter();
+        let mut local_params = vec![];
+        for param in &params.required {
+            local_params.push((param.clone(), args.next().unwrap()));
+        }
+        let mut defaults = vec![];
+        for (param, default) in &params.optional {
+            match args.next() {
+                Some(arg) => local_params.push((param.clone(), arg)),
+                None => defaults.push((param.clone(), default.clone())),
+            }
+        }
+        if let Some(param) = &params.rest {
+            local_params.push((param.clone(), Token::List(List::new(args.collect()))));
+        }
+        Ok(BoundInputs {
+            local_params,
+            defaults,
+        })
+    }
+
+    pub fn apply_template(
+        &mut self,
+        com: &str,
+        template: &Token,
+        inputs: Vec<Token>,
+    ) -> Result<Token, Box<dyn Error>> {
+        match template {
+            // The name of a procedure or primitive, like "sum
+            Token::Word(name) => {
+                if self.state.data.get_procedure(name).is_some() {
+                    return self.call_procedure(name, inputs);
+                }
+                let Some(command) = self.lexer.lookup(name) else {
+                    return Err(Box::from(LogoError::unknown_procedure(format!(
+                        "I don't know how to {}",
+                        name
+                    ))));
+                };
+                (command.action)(self, &command.name, inputs)
+            }
+            Token::List(list) => {
+                // Named slots, like [[x y] :x + :y]
+                if let Some(Token::List(names)) = list.first() {
+                    if names.len() != inputs.len() {
+                        return Err(Box::from(format!(
+                            "{} expected a template with {} inputs",
+                            com,
+                            inputs.len()
+                        )));
+                    }
+                    let mut local_params = vec![];
+                    for (name, input) in names.items().iter().zip(inputs) {
+                        let Token::Word(name) = name else {
+                            return Err(Box::from(format!(
+                                "{} expected words for input names",
+                                com
+                            )));
+                        };
+                        local_params.push((name.clone(), input));
+                    }
+                    return self.evaluate_in_new_scope(&list.butfirst(), local_params);
+                }
+
+                // Numbered slots, like [?1 + ?2], where ? is the same as ?1
+                let local_params = inputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, input)| (format!("?{}", index + 1), input))
+                    .collect();
+                self.evaluate_in_new_scope(list, local_params)
+            }
+            _ => Err(Box::from(format!(
+                "{} expected a word or list for a template",
+                com
+            ))),
+        }
+    }
+
     pub fn define_object_property(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        self.define_object_accessors(name)?;
+
+        // Add to Backpack
+        self.state.canvas.init_backpack_property(name);
+        Ok(())
+    }
+
+    fn define_object_accessors(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
         // Getter
         self.lexer.define(
             name,
@@ -222,69 +1171,42 @@ impl Interpreter {
                 turtle.backpack.insert(item_name, token);
                 Ok(Token::Void)
             },
-        )?;
-
-        // Add to Backpack
-        self.state.canvas.init_backpack_property(name);
-        Ok(())
+        )
     }
 
-    pub fn parse_list(
-        &mut self,
-        list: &String,
-        parse_numbers: bool,
-    ) -> Result<Vec<Token>, Box<dyn Error>> {
-        let mut items = vec![];
-        let mut current_item = String::new();
-        let mut bracket_count = 0;
-        let mut allow_whitespace = false;
-
-        for chr in list.chars() {
-            if chr == '[' {
-                bracket_count += 1;
-            } else if chr == ']' {
-                bracket_count -= 1;
-                if bracket_count == 0 {
-                    let token = Token::List(current_item.clone());
-                    items.push(token);
-                    current_item = String::new();
+    pub fn resolve_list_inputs(&self, args: Vec<Token>) -> Result<Vec<Token>, Box<dyn Error>> {
+        // Lists given to graphics commands, like setpos [:x 10], may also
+        // refer to variables.
+        args.into_iter()
+            .map(|arg| match arg {
+                Token::List(list) => {
+                    let items = list.items().iter().map(|item| self.resolve_list_item(item));
+                    Ok(Token::List(List::new(items.collect::<Result<_, _>>()?)))
                 }
-            } else if chr == '|' {
-                if allow_whitespace {
-                    let token = self.parse_list_token(current_item, parse_numbers)?;
-                    items.push(token);
-                    current_item = String::new();
-                }
-                allow_whitespace = !allow_whitespace;
-            } else if bracket_count != 0 || allow_whitespace || !chr.is_whitespace() {
-                current_item.push(chr);
-            } else if !current_item.is_empty() {
-                let token = self.parse_list_token(current_item, parse_numbers)?;
-                items.push(token);
-                current_item = String::new();
-            }
-        }
-        if !current_item.is_empty() {
-            let token = self.parse_list_token(current_item, parse_numbers)?;
-            items.push(token);
-        }
-        Ok(items)
+                arg => Ok(arg),
+            })
+            .collect()
     }
 
-    fn parse_list_token(&self, text: String, parse_numbers: bool) -> Result<Token, Box<dyn Error>> {
-        if text.starts_with(':') {
-            let var_name = text[1..].to_string();
-            if let Some(var) = self.state.data.get_variable(&var_name) {
-                return Ok(var.clone());
-            } else {
-                return Err(Box::from(format!("{} has no value", var_name)));
-            }
-        } else if parse_numbers {
-            if let Ok(number) = text.parse::<f32>() {
-                return Ok(Token::Number(number));
+    pub fn resolve_list_item(&self, item: &Token) -> Result<Token, Box<dyn Error>> {
+        // Items in lists that configure a command, like the inputs to "let"
+        // or "dotimes", may refer to variables or quoted words.
+        let Token::Word(word) = item else {
+            return Ok(item.clone());
+        };
+        if let Some(var_name) = word.strip_prefix(':') {
+            match self.state.data.get_variable(var_name) {
+                Some(var) => Ok(var.clone()),
+                None => Err(Box::from(LogoError::unbound_variable(format!(
+                    "{} has no value",
+                    var_name
+                )))),
             }
+        } else if let Some(quoted) = word.strip_prefix('"') {
+            Ok(Token::Word(quoted.to_string()))
+        } else {
+            Ok(item.clone())
         }
-        Ok(Token::Word(text.clone()))
     }
 
     pub fn bind_ui_handler(
@@ -296,11 +1218,13 @@ impl Interpreter {
         self.event.ui_context = Some(context);
     }
 
+    #[allow(dead_code)]
     pub fn clear_ui_handler(&mut self) {
         self.event.ui_handler = None;
         self.event.ui_context = None;
     }
 
+    #[allow(dead_code)]
     pub fn bind_input_receiver(&mut self, receiver: mpsc::Receiver<InputEvent>) {
         self.event.input_receiver = Some(receiver);
     }
@@ -317,33 +1241,168 @@ impl Interpreter {
                 self.state.input.set_key_up(&key);
                 Ok(())
             }
+            InputEvent::Pause | InputEvent::Step => {
+                self.state.debug.pause_requested = true;
+                Ok(())
+            }
+            InputEvent::Resume => Ok(()),
+            InputEvent::SetBreakpoints(lines) => {
+                self.state.debug.set_breakpoints(lines);
+                Ok(())
+            }
             InputEvent::Click(pos) => {
+                if self.state.triggers.has_turtle_events {
+                    for turtle in self.state.canvas.turtles() {
+                        if turtle.onclick.is_some() && turtle.contains_point(&pos) {
+                            self.state.triggers.clicked.push(turtle.name.clone());
+                        }
+                    }
+                }
                 self.state.input.add_click_to_buffer(pos);
                 Ok(())
             }
         }
     }
 
+    #[allow(dead_code)]
     pub fn clear_input_events(&self) {
         while self.event.receive_input().is_ok() {
             // Consume remaining events.
         }
     }
 
-    fn exit_scope(&mut self) {
-        let exited_main = self.lexer.pop_block();
-        if exited_main {
-            println!("Done!");
-            self.terminate_program();
-        }
-    }
-
     fn terminate_program(&mut self) {
         self.lexer.clear_blocks();
+        self.call_stack.clear();
+        self.block_depth = 0;
+        self.stop_processes();
         self.state.data.reset_scope();
+        self.state.debug.pause_requested = false;
         self.event.send_ui(UiEvent::Done);
     }
 
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn save_project(&self, path: &str, source: &str) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, project::write_project(&self.state, source))?;
+        Ok(())
+    }
+
+    pub fn load_project(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)?;
+        let project = project::read_project(&text)?;
+
+        // The loaded workspace replaces everything, including anything still
+        // running, apart from the inputs of the procedure that loaded it.
+        self.stop_processes();
+        self.state.data.load_workspace(project.data);
+        self.state.canvas = project.canvas;
+        self.source = project.source;
+
+        // Procedures and turtle properties are part of the language, so they
+        // have to be defined again.
+        self.lexer.reset_language();
+        let procedures: Vec<Procedure> = self
+            .state
+            .data
+            .get_all_procedures()
+            .into_iter()
+            .cloned()
+            .collect();
+        for procedure in procedures {
+            self.define_procedure(procedure)?;
+        }
+        let mut properties: Vec<Box<str>> = self
+            .state
+            .canvas
+            .turtles()
+            .flat_map(|turtle| turtle.backpack.keys().cloned())
+            .collect();
+        properties.sort();
+        properties.dedup();
+        for property in properties {
+            self.define_object_accessors(&property)?;
+        }
+
+        let source_name = match Path::new(path).file_stem() {
+            Some(stem) => format!("{}.logo", stem.to_string_lossy()),
+            None => String::from("untitled.logo"),
+        };
+        self.show_workspace(source_name);
+        Ok(())
+    }
+
+    fn show_workspace(&self, source_name: String) {
+        // Rebuild the canvas in the UI from scratch to match the state.
+        let canvas = &self.state.canvas;
+        self.event.send_ui(UiEvent::Reset);
+        let size = canvas.get_size();
+        self.event.send_ui(UiEvent::CanvasSize(size.w, size.h));
+        self.event.send_ui(UiEvent::BgColor(canvas.get_bg_color()));
+        if let Some(path) = canvas.get_bg_picture() {
+            self.event.send_ui(UiEvent::BgPicture(path.clone()));
+        }
+        for picture in canvas.get_pictures() {
+            self.event.send_ui(UiEvent::PlacePicture(
+                picture.path.clone(),
+                picture.pos.clone(),
+                picture.size.clone(),
+            ));
+        }
+        for (name, shape) in self.state.data.get_all_shapes() {
+            if let TurtleShape::Image(_, path) = shape {
+                self.event
+                    .send_ui(UiEvent::AddShape(Box::from(name), path.clone()));
+            }
+        }
+        for object in canvas.objects() {
+            let name: Box<str> = Box::from(object.name());
+            match object {
+                Object::Turtle(turtle) => {
+                    self.event.send_ui(UiEvent::NewTurtle(name.clone()));
+                    self.event
+                        .send_ui(UiEvent::ObjectSize(name.clone(), turtle.size.clone()));
+                    self.event
+                        .send_ui(UiEvent::TurtleHeading(name.clone(), turtle.heading));
+                    self.event
+                        .send_ui(UiEvent::TurtleShape(name.clone(), turtle.shape.clone()));
+                }
+                Object::Text(text) => {
+                    self.event.send_ui(UiEvent::NewText(name.clone()));
+                    self.event.send_ui(UiEvent::TextClear(name.clone()));
+                    self.event
+                        .send_ui(UiEvent::TextPrint(name.clone(), text.text.clone()));
+                    self.event
+                        .send_ui(UiEvent::TextSize(name.clone(), text.font_size));
+                    self.event
+                        .send_ui(UiEvent::TextStyle(name.clone(), text.style.clone()));
+                }
+            }
+            self.event
+                .send_ui(UiEvent::ObjectPos(name.clone(), object.pos().clone()));
+            self.event
+                .send_ui(UiEvent::ObjectColor(name.clone(), object.color()));
+            self.event
+                .send_ui(UiEvent::ObjectVisible(name, object.is_visible()));
+        }
+        for drawing in canvas.get_drawings() {
+            match drawing {
+                Drawing::Line(line) => self
+                    .event
+                    .send_ui(UiEvent::AddLine(Box::from(""), line.clone())),
+                Drawing::Curve(curve) => self
+                    .event
+                    .send_ui(UiEvent::AddCurve(Box::from(""), curve.clone())),
+                Drawing::Fill(fill) => self.event.send_ui(UiEvent::AddFill(fill.clone())),
+            }
+        }
+        self.event
+            .send_ui(UiEvent::OpenSource(source_name, self.source.clone()));
+    }
+
+    #[allow(dead_code)]
     pub fn reset(&mut self) {
         self.lexer = Lexer::new();
         self.state = State::new();
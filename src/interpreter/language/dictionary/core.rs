@@ -1,9 +1,13 @@
+use crate::interpreter::event::UiEvent;
 use crate::interpreter::interpreter::Interpreter;
-use crate::interpreter::language::structure::{Command, Params};
-use crate::interpreter::language::token::{Token, TokenVec};
+use crate::interpreter::language::structure::{BlockCache, Command, Params};
+use crate::interpreter::language::structure::{Procedure, ProcedureParams};
+use crate::interpreter::language::token::{List, Token};
 use crate::interpreter::language::util::decode;
 use crate::interpreter::language::util::io::KeyCode;
+use crate::interpreter::util::error::output_signal;
 use rand::Rng;
+use std::error::Error;
 
 impl Command {
     pub fn make() -> Self {
@@ -12,15 +16,7 @@ impl Command {
             Params::Fixed(2),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let name = decode::word(com, &args, 0)?;
-                let token = decode::token(com, &args, 1)?;
-                let value = match token {
-                    Token::List(list) => {
-                        let tokens = int.parse_list(&list, false)?;
-                        let joined = tokens.join_to_list_string();
-                        Token::List(joined)
-                    }
-                    token => token,
-                };
+                let value = decode::token(com, &args, 1)?;
                 int.state.data.set_variable(&name, value);
                 Ok(Token::Void)
             },
@@ -39,6 +35,164 @@ impl Command {
         )
     }
 
+    pub fn define() -> Self {
+        Command::reserved(
+            "define",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                // The definition is a list of inputs followed by a list for each line.
+                let name = decode::word(com, &args, 0)?;
+                let definition = decode::list(com, &args, 1)?;
+                let Some(Token::List(inputs)) = definition.first() else {
+                    return Err(Box::from(format!("{} expected a list of inputs", com)));
+                };
+                let params = match ProcedureParams::from_list(inputs.items()) {
+                    Ok(params) => params,
+                    Err(problem) => {
+                        return Err(Box::from(format!("{} found {}", com, problem)));
+                    }
+                };
+                let mut code = String::new();
+                for line in definition.butfirst().items() {
+                    let Token::List(line) = line else {
+                        return Err(Box::from(format!("{} expected a list for each line", com)));
+                    };
+                    code += &line.source();
+                    code += "\n";
+                }
+                int.define_procedure(Procedure {
+                    name: Box::from(name.as_str()),
+                    params,
+                    code,
+                    line: 1,
+                    block: BlockCache::default(),
+                })?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn printout() -> Self {
+        Command::reserved(
+            "printout",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let mut definitions = vec![];
+                for name in procedure_names(com, &args, 0)? {
+                    let Some(procedure) = int.state.data.get_procedure(&name) else {
+                        return Err(Box::from(format!(
+                            "{} found no procedure named {}",
+                            com, name
+                        )));
+                    };
+                    definitions.push(procedure.printout());
+                }
+                int.event
+                    .send_ui(UiEvent::ConsolePrint(definitions.join("\n\n")));
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn erase() -> Self {
+        Command::reserved(
+            "erase",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                for name in procedure_names(com, &args, 0)? {
+                    int.erase_procedure(&name)?;
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn erall() -> Self {
+        Command::reserved(
+            "erall",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                // Primitives copied with "copydef" are erased along with
+                // the procedures.
+                let procedures = int.state.data.get_all_procedures();
+                let mut names: Vec<String> = procedures
+                    .iter()
+                    .map(|proc| proc.name.to_string())
+                    .collect();
+                names.extend(int.lexer.copied_names());
+                for name in names {
+                    int.erase_procedure(&name)?;
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn copydef() -> Self {
+        Command::reserved(
+            "copydef",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let new_name = decode::word(com, &args, 0)?;
+                let name = decode::word(com, &args, 1)?;
+                int.copy_definition(&new_name, &name)?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn trace() -> Self {
+        Command::reserved(
+            "trace",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                for name in procedure_names(com, &args, 0)? {
+                    int.state.debug.set_traced(&name, true);
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn untrace() -> Self {
+        Command::reserved(
+            "untrace",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                for name in procedure_names(com, &args, 0)? {
+                    int.state.debug.set_traced(&name, false);
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn step() -> Self {
+        Command::reserved(
+            "step",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                for name in procedure_names(com, &args, 0)? {
+                    int.state.debug.set_stepped(&name, true);
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn unstep() -> Self {
+        Command::reserved(
+            "unstep",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                for name in procedure_names(com, &args, 0)? {
+                    int.state.debug.set_stepped(&name, false);
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn local() -> Self {
         Command::reserved(
             "local",
@@ -57,12 +211,10 @@ impl Command {
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let list = decode::list(com, &args, 0)?;
-                let list_items = int.parse_list(&list, true)?;
-                for index in (0..list_items.len()).step_by(2) {
-                    if let Some(Token::Word(name)) = list_items.get(index) {
-                        if let Some(value) = list_items.get(index + 1) {
-                            int.state.data.init_local(&name, value.clone());
-                        }
+                for pair in list.items().chunks(2) {
+                    if let [Token::Word(name), value] = pair {
+                        let value = int.resolve_list_item(value)?;
+                        int.state.data.init_local(name, value);
                     }
                 }
                 Ok(Token::Void)
@@ -79,10 +231,9 @@ impl Command {
                 match token {
                     Token::Word(word) => int.state.data.remove_variable(&word),
                     Token::List(list) => {
-                        let list_items = int.parse_list(&list, false)?;
-                        for item in list_items {
+                        for item in list.items() {
                             match item {
-                                Token::Word(word) => int.state.data.remove_variable(&word),
+                                Token::Word(word) => int.state.data.remove_variable(word),
                                 _ => return Err(Box::from("clearname expected a list of words")),
                             }
                         }
@@ -105,13 +256,86 @@ impl Command {
         )
     }
 
+    pub fn pprop() -> Self {
+        Command::reserved(
+            "pprop",
+            Params::Fixed(3),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let property = property_key(com, &args, 1)?;
+                let value = decode::token(com, &args, 2)?;
+                int.state.data.set_property(&name, &property, value);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn gprop() -> Self {
+        Command::reserved(
+            "gprop",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let property = property_key(com, &args, 1)?;
+                match int.state.data.get_property(&name, &property) {
+                    Some(value) => Ok(value.clone()),
+                    None => Ok(Token::List(List::empty())),
+                }
+            },
+        )
+    }
+
+    pub fn remprop() -> Self {
+        Command::reserved(
+            "remprop",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let property = property_key(com, &args, 1)?;
+                int.state.data.remove_property(&name, &property);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn plist() -> Self {
+        Command::reserved(
+            "plist",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let mut items = vec![];
+                for (property, value) in int.state.data.get_property_list(&name) {
+                    // Properties named by numbers come back as numbers.
+                    match property.parse::<f32>() {
+                        Ok(number) => items.push(Token::Number(number)),
+                        Err(..) => items.push(Token::Word(property.to_string())),
+                    }
+                    items.push(value.clone());
+                }
+                Ok(Token::List(List::new(items)))
+            },
+        )
+    }
+
+    pub fn erpl() -> Self {
+        Command::reserved(
+            "erpl",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                int.state.data.remove_all_property_lists();
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn output() -> Self {
         Command::reserved(
             "output",
             Params::Fixed(1),
             |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let return_value = decode::token(com, &args, 0)?;
-                Ok(return_value.clone())
+                Err(output_signal(return_value.clone()))
             },
         )
     }
@@ -120,14 +344,29 @@ impl Command {
         Command::reserved(
             "run",
             Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| match decode::token(com, &args, 0)?
+            {
+                Token::Word(word) => int.evaluate(&word),
+                Token::List(list) => int.evaluate_list(&list),
+                _ => Err(Box::from("run expected a word or list as input")),
+            },
+        )
+    }
+
+    pub fn runresult() -> Self {
+        Command::reserved(
+            "runresult",
+            Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
-                let code = match decode::token(com, &args, 0)? {
-                    Token::Word(word) => word,
-                    Token::List(list) => list,
-                    _ => return Err(Box::from("run expected a word or list as input")),
+                let value = match decode::token(com, &args, 0)? {
+                    Token::Word(word) => int.evaluate(&word)?,
+                    Token::List(list) => int.evaluate_list(&list)?,
+                    _ => return Err(Box::from("runresult expected a word or list as input")),
                 };
-                int.interpret(&code)?;
-                Ok(Token::Void)
+                match value {
+                    Token::Void => Ok(Token::List(List::empty())),
+                    value => Ok(Token::List(List::new(vec![value]))),
+                }
             },
         )
     }
@@ -138,15 +377,11 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 let procs = int.state.data.get_all_procedures();
-                let mut list_string = String::new();
-                for index in 0..procs.len() {
-                    if index == 0 {
-                        list_string += &format!("{}", procs[index].name);
-                    } else {
-                        list_string += &format!(" {}", procs[index].name);
-                    }
-                }
-                Ok(Token::List(list_string))
+                let names = procs
+                    .iter()
+                    .map(|proc| Token::Word(proc.name.to_string()))
+                    .collect();
+                Ok(Token::List(List::new(names)))
             },
         )
     }
@@ -185,17 +420,13 @@ impl Command {
                 let name = decode::word(com, &args, 0)?;
                 let token = decode::token(com, &args, 1)?;
                 match token {
-                    Token::Word(word) => {
-                        let code = format!("op {}", word);
-                        let token = int.interpret(&code)?;
-                        Ok(token)
-                    }
+                    Token::Word(word) => int.evaluate(&word),
                     Token::List(list) => {
                         let current_obj_name =
                             int.state.canvas.current_object()?.name().to_string();
                         let success = int.state.canvas.set_current_object(&name);
                         if success {
-                            let _ = int.interpret(&list);
+                            let _ = int.interpret_list(&list);
                             int.state.canvas.set_current_object(&current_obj_name);
                             Ok(Token::Void)
                         } else {
@@ -308,9 +539,10 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 if let Some(click) = int.state.input.get_one_click() {
-                    Ok(Token::List(format!("{} {}", click.x, click.y)))
+                    let items = vec![Token::Number(click.x), Token::Number(click.y)];
+                    Ok(Token::List(List::new(items)))
                 } else {
-                    Ok(Token::List(String::new()))
+                    Ok(Token::List(List::empty()))
                 }
             },
         )
@@ -332,7 +564,7 @@ impl Command {
         Command::reserved(
             "pick",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let token = decode::token(com, &args, 0)?;
                 match token {
                     Token::Word(word) => {
@@ -341,9 +573,11 @@ impl Command {
                         Ok(Token::Word(chr))
                     }
                     Token::List(list) => {
-                        let items = int.parse_list(&list, true)?;
-                        let random = rand::thread_rng().gen_range(0..items.len());
-                        let item = items.get(random).unwrap().clone();
+                        if list.is_empty() {
+                            return Err(Box::from("pick cannot get from empty list"));
+                        }
+                        let random = rand::thread_rng().gen_range(0..list.len());
+                        let item = list.get(random).unwrap().clone();
                         Ok(item)
                     }
                     _ => Err(Box::from("expected a word or list")),
@@ -352,3 +586,30 @@ impl Command {
         )
     }
 }
+
+fn property_key(com: &str, args: &Vec<Token>, index: usize) -> Result<String, Box<dyn Error>> {
+    // Properties can be named by numbers too, such as the cells of a board.
+    match decode::token(com, args, index)? {
+        Token::Number(number) => Ok(number.to_string()),
+        _ => decode::word(com, args, index),
+    }
+}
+
+fn procedure_names(
+    com: &str,
+    args: &Vec<Token>,
+    index: usize,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    // Takes the name of one procedure, or a list of names.
+    match decode::token(com, args, index)? {
+        Token::List(list) => list
+            .items()
+            .iter()
+            .map(|item| match item {
+                Token::Word(name) => Ok(name.clone()),
+                _ => Err(Box::from(format!("{} expected a list of names", com))),
+            })
+            .collect(),
+        _ => Ok(vec![decode::word(com, args, index)?]),
+    }
+}
@@ -4,7 +4,8 @@ use crate::interpreter::language::structure::{Command, Params};
 use crate::interpreter::language::token::Token;
 use crate::interpreter::language::util::decode;
 use crate::interpreter::language::util::io::query_files;
-use crate::interpreter::state::object::{Point, Size, TurtleShape};
+use crate::interpreter::state::object::{Picture, Point, Size, TurtleShape};
+use crate::interpreter::util::export;
 use std::fs::{DirEntry, File};
 use std::io::Read;
 
@@ -128,6 +129,7 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let path = decode::word(com, &args, 0)?;
                 let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
+                int.state.canvas.set_bg_picture(full_path.clone());
                 int.event.send_ui(UiEvent::BgPicture(full_path));
                 Ok(Token::Void)
             },
@@ -139,30 +141,34 @@ impl Command {
             "placepict",
             Params::Fixed(3),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
                 let path = decode::word(com, &args, 0)?;
                 let pos = decode::list(com, &args, 1)?;
                 let size = decode::list(com, &args, 2)?;
                 let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
-                let pos_items = int.parse_list(&pos, true)?;
-                if pos_items.len() != 2 {
+                if pos.len() != 2 {
                     return Err(Box::from("placepict expected 2 coordinates in input 1"));
                 }
-                let size_items = int.parse_list(&size, true)?;
-                if size_items.len() != 2 {
+                if size.len() != 2 {
                     return Err(Box::from("placepict expected 2 dimensions in input 2"));
                 }
-                let Some(Token::Number(x)) = pos_items.get(0) else {
+                let Some(Token::Number(x)) = pos.first() else {
                     return Err(Box::from("placepict expected number for x-coordinate"));
                 };
-                let Some(Token::Number(y)) = pos_items.get(1) else {
+                let Some(Token::Number(y)) = pos.get(1) else {
                     return Err(Box::from("placepict expected number for y-coordinate"));
                 };
-                let Some(Token::Number(w)) = size_items.get(0) else {
+                let Some(Token::Number(w)) = size.first() else {
                     return Err(Box::from("placepict expected number for width"));
                 };
-                let Some(Token::Number(h)) = size_items.get(1) else {
+                let Some(Token::Number(h)) = size.get(1) else {
                     return Err(Box::from("placepict expected number for height"));
                 };
+                int.state.canvas.add_picture(Picture {
+                    path: full_path.clone(),
+                    pos: Point::new(*x, *y),
+                    size: Size::new(*w, *h),
+                });
                 int.event.send_ui(UiEvent::PlacePicture(
                     full_path,
                     Point::new(*x, *y),
@@ -173,6 +179,59 @@ impl Command {
         )
     }
 
+    pub fn savepict() -> Self {
+        Command::reserved(
+            "savepict",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let path = decode::word(com, &args, 0)?;
+                let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
+                export::save_png(&int.state.canvas, &full_path)?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn savesvg() -> Self {
+        Command::reserved(
+            "savesvg",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let path = decode::word(com, &args, 0)?;
+                let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
+                let document = export::svg_document(&int.state.canvas);
+                std::fs::write(full_path, document)?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn saveproject() -> Self {
+        Command::reserved(
+            "saveproject",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let path = decode::word(com, &args, 0)?;
+                let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
+                int.save_project(&full_path, int.source())?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn loadproject() -> Self {
+        Command::reserved(
+            "loadproject",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let path = decode::word(com, &args, 0)?;
+                let full_path = format!("{}{}", int.state.data.get_base_directory(), path);
+                int.load_project(&full_path)?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn loadtext() -> Self {
         Command::reserved(
             "loadtext",
@@ -1,7 +1,10 @@
 use crate::interpreter::interpreter::Interpreter;
 use crate::interpreter::language::structure::{Command, Params};
-use crate::interpreter::language::token::Token;
+use crate::interpreter::language::token::{List, Token};
 use crate::interpreter::language::util::decode;
+use crate::interpreter::util::error::{is_signal, stop_all_signal, LogoError};
+use crate::interpreter::util::error::{stop_signal, throw_signal};
+use std::error::Error;
 
 impl Command {
     pub fn ifthen() -> Self {
@@ -12,7 +15,7 @@ impl Command {
                 let condition = decode::boolean(com, &args, 0)?;
                 let true_code = decode::list(com, &args, 1)?;
                 if condition {
-                    int.interpret(&true_code)?;
+                    return int.evaluate_list(&true_code);
                 }
                 Ok(Token::Void)
             },
@@ -28,11 +31,10 @@ impl Command {
                 let true_code = decode::list(com, &args, 1)?;
                 let false_code = decode::list(com, &args, 2)?;
                 if condition {
-                    int.interpret(&true_code)?;
+                    int.evaluate_list(&true_code)
                 } else {
-                    int.interpret(&false_code)?;
+                    int.evaluate_list(&false_code)
                 }
-                Ok(Token::Void)
             },
         )
     }
@@ -44,23 +46,175 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let count = decode::number(com, &args, 0)? as usize;
                 let code = decode::list(com, &args, 1)?;
-                for _ in 0..count {
-                    int.interpret(&code)?;
+                int.interpret_repeatedly(&code, count)?;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn repcount() -> Self {
+        Command::reserved(
+            "repcount",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                // Outside of a loop there is no count, which is shown as -1.
+                let count = match int.repeat_count() {
+                    Some(count) => count as f32,
+                    None => -1.0,
+                };
+                Ok(Token::Number(count))
+            },
+        )
+    }
+
+    pub fn whileloop() -> Self {
+        Command::reserved(
+            "while",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let condition = decode::list(com, &args, 0)?;
+                let code = decode::list(com, &args, 1)?;
+                while loop_condition(int, com, &condition)? {
+                    int.interpret_list(&code)?;
                 }
                 Ok(Token::Void)
             },
         )
     }
 
-    pub fn forever() -> Self {
+    pub fn until() -> Self {
         Command::reserved(
-            "forever",
-            Params::Fixed(1),
+            "until",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let condition = decode::list(com, &args, 0)?;
+                let code = decode::list(com, &args, 1)?;
+                while !loop_condition(int, com, &condition)? {
+                    int.interpret_list(&code)?;
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn dowhile() -> Self {
+        Command::reserved(
+            "do.while",
+            Params::Fixed(2),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let code = decode::list(com, &args, 0)?;
+                let condition = decode::list(com, &args, 1)?;
                 loop {
-                    int.interpret(&code)?;
+                    int.interpret_list(&code)?;
+                    if !loop_condition(int, com, &condition)? {
+                        break;
+                    }
                 }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn dountil() -> Self {
+        Command::reserved(
+            "do.until",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let code = decode::list(com, &args, 0)?;
+                let condition = decode::list(com, &args, 1)?;
+                loop {
+                    int.interpret_list(&code)?;
+                    if loop_condition(int, com, &condition)? {
+                        break;
+                    }
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn forever() -> Self {
+        Command::reserved(
+            "forever",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                // Runs in the background as a process named after its code.
+                let code = decode::list(com, &args, 0)?.source().trim().to_string();
+                int.launch_process(&code, code.clone(), true);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn launch() -> Self {
+        Command::reserved(
+            "launch",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let code = decode::list(com, &args, 0)?.source().trim().to_string();
+                int.launch_process(&code, code.clone(), false);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn cancel() -> Self {
+        Command::reserved(
+            "cancel",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = match decode::token(com, &args, 0)? {
+                    Token::List(list) => list.source().trim().to_string(),
+                    Token::Word(word) => word,
+                    _ => return Err(Box::from("cancel expected a list or word for input 0")),
+                };
+                int.state.processes.cancel(&name);
+                int.state.triggers.cancel(&name);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn when() -> Self {
+        Command::reserved(
+            "when",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let condition = decode::list(com, &args, 0)?;
+                let action = decode::list(com, &args, 1)?;
+                int.add_trigger(condition, action.source().trim());
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn stop() -> Self {
+        Command::reserved(
+            "stop",
+            Params::None,
+            |_int: &mut Interpreter, _com: &str, _args: Vec<Token>| Err(stop_signal()),
+        )
+    }
+
+    pub fn stopall() -> Self {
+        Command::reserved(
+            "stopall",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                int.stop_processes();
+                Err(stop_all_signal())
+            },
+        )
+    }
+
+    pub fn processes() -> Self {
+        Command::reserved(
+            "processes",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                let names = int.state.processes.names();
+                let words = names.iter().map(|name| Token::Word(name.to_string()));
+                Ok(Token::List(List::new(words.collect())))
             },
         )
     }
@@ -72,16 +226,19 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let loop_config = decode::list(com, &args, 0)?;
                 let code = decode::list(com, &args, 1)?;
-                let config_items = int.parse_list(&loop_config, true)?;
-                let Some(Token::Word(var_name)) = config_items.get(0) else {
+                let Some(Token::Word(var_name)) = loop_config.first() else {
                     return Err(Box::from("dotimes expected a word for input 0 in input 0"));
                 };
-                let Some(Token::Number(count)) = config_items.get(1) else {
+                let count = match loop_config.get(1) {
+                    Some(item) => int.resolve_list_item(item)?,
+                    None => Token::Void,
+                };
+                let Token::Number(count) = count else {
                     return Err(Box::from(
                         "dotimes expected a number for input 1 in input 0",
                     ));
                 };
-                for index in 0..(*count as usize) {
+                for index in 0..(count as usize) {
                     let local_params = vec![(var_name.clone(), Token::Number(index as f32))];
                     int.interpret_in_new_scope(&code, local_params)?;
                 }
@@ -90,6 +247,52 @@ impl Command {
         )
     }
 
+    pub fn forloop() -> Self {
+        Command::reserved(
+            "for",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let loop_config = decode::list(com, &args, 0)?;
+                let code = decode::list(com, &args, 1)?;
+                let Some(Token::Word(var_name)) = loop_config.first() else {
+                    return Err(Box::from("for expected a word for input 0 in input 0"));
+                };
+                let mut bounds = vec![];
+                for index in 1..loop_config.len().clamp(3, 4) {
+                    let bound = match loop_config.get(index) {
+                        Some(item) => int.resolve_list_item(item)?,
+                        None => Token::Void,
+                    };
+                    let Token::Number(bound) = bound else {
+                        return Err(Box::from(format!(
+                            "for expected a number for input {} in input 0",
+                            index
+                        )));
+                    };
+                    bounds.push(bound);
+                }
+                let (start, end) = (bounds[0], bounds[1]);
+                // Without a step, count towards the end one at a time.
+                let step = match bounds.get(2) {
+                    Some(step) => *step,
+                    None if end < start => -1.0,
+                    None => 1.0,
+                };
+                if step == 0.0 {
+                    return Err(Box::from("for expected a step that is not 0"));
+                }
+                // Leave some room for rounding, so [i 0 1 0.1] reaches 1.
+                let count = ((end - start) / step + 0.0001).floor() as i64 + 1;
+                for index in 0..count {
+                    let value = start + index as f32 * step;
+                    let local_params = vec![(var_name.clone(), Token::Number(value))];
+                    int.interpret_in_new_scope(&code, local_params)?;
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn dolist() -> Self {
         Command::reserved(
             "dolist",
@@ -97,16 +300,18 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let loop_config = decode::list(com, &args, 0)?;
                 let code = decode::list(com, &args, 1)?;
-                let config_items = int.parse_list(&loop_config, false)?;
-                let Some(Token::Word(var_name)) = config_items.get(0) else {
+                let Some(Token::Word(var_name)) = loop_config.first() else {
                     return Err(Box::from("dolist expected a word for input 0 in input 0"));
                 };
-                let Some(Token::List(list)) = config_items.get(1) else {
+                let list = match loop_config.get(1) {
+                    Some(item) => int.resolve_list_item(item)?,
+                    None => Token::Void,
+                };
+                let Token::List(list) = list else {
                     return Err(Box::from("dolist expected a list for input 1 in input 0"));
                 };
-                let list_items = int.parse_list(&list, true)?;
-                for item in list_items {
-                    let local_params = vec![(var_name.clone(), item)];
+                for item in list.items() {
+                    let local_params = vec![(var_name.clone(), item.clone())];
                     int.interpret_in_new_scope(&code, local_params)?;
                 }
                 Ok(Token::Void)
@@ -121,9 +326,12 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let check_code = decode::list(com, &args, 0)?;
                 let error_code = decode::list(com, &args, 1)?;
-                if let Err(err) = int.interpret(&check_code) {
-                    int.state.data.set_last_error_message(err.to_string());
-                    int.interpret(&error_code)?;
+                if let Err(err) = int.interpret_list(&check_code) {
+                    if is_signal(err.as_ref()) {
+                        return Err(err);
+                    }
+                    int.state.data.set_last_error(LogoError::from_error(err));
+                    int.interpret_list(&error_code)?;
                 }
                 Ok(Token::Void)
             },
@@ -135,8 +343,89 @@ impl Command {
             "errormessage",
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
-                let error = int.state.data.get_last_error_message();
-                Ok(Token::Word(error))
+                let message = match int.state.data.get_last_error() {
+                    Some(error) => error.describe(),
+                    None => String::new(),
+                };
+                Ok(Token::Word(message))
+            },
+        )
+    }
+
+    pub fn catch() -> Self {
+        Command::reserved(
+            "catch",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let tag = decode::word(com, &args, 0)?;
+                let code = decode::list(com, &args, 1)?;
+                let err = match int.evaluate_list(&code) {
+                    Ok(value) => return Ok(value),
+                    Err(err) => err,
+                };
+                // The "error tag catches errors, like carefully does.
+                if tag.eq_ignore_ascii_case("error") && !is_signal(err.as_ref()) {
+                    int.state.data.set_last_error(LogoError::from_error(err));
+                    return Ok(Token::Void);
+                }
+                match LogoError::from_error(err) {
+                    LogoError::Throw(thrown, value) if thrown.eq_ignore_ascii_case(&tag) => {
+                        Ok(value)
+                    }
+                    other => Err(Box::from(other)),
+                }
+            },
+        )
+    }
+
+    pub fn throw() -> Self {
+        Command::reserved(
+            "throw",
+            Params::Variadic(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let tag = decode::word(com, &args, 0)?;
+                let value = args.get(1).cloned().unwrap_or(Token::Void);
+                if tag.eq_ignore_ascii_case("error") {
+                    let message = match value {
+                        Token::List(list) => list.to_text(),
+                        Token::Void => String::from("throw \"error"),
+                        value => value.to_string(),
+                    };
+                    return Err(Box::from(LogoError::user(message)));
+                }
+                Err(throw_signal(tag, value))
+            },
+        )
+    }
+
+    pub fn error() -> Self {
+        Command::reserved(
+            "error",
+            Params::None,
+            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                // Reports [kind message procedure line] for the last error caught.
+                let nothing = || Token::List(List::empty());
+                let Some(error) = int.state.data.get_last_error() else {
+                    return Ok(nothing());
+                };
+                let Some(info) = error.info() else {
+                    return Ok(nothing());
+                };
+                let frame = info.stack.first();
+                let procedure = match frame.and_then(|frame| frame.procedure.as_deref()) {
+                    Some(procedure) => Token::Word(procedure.to_string()),
+                    None => nothing(),
+                };
+                let line = match frame {
+                    Some(frame) => Token::Number(frame.location.line as f32),
+                    None => nothing(),
+                };
+                Ok(Token::List(List::new(vec![
+                    Token::Word(error.kind().to_string()),
+                    Token::Word(info.message.clone()),
+                    procedure,
+                    line,
+                ])))
             },
         )
     }
@@ -145,8 +434,8 @@ impl Command {
         Command::reserved(
             "again",
             Params::None,
-            |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
-                int.lexer.return_to_start_of_block();
+            |_int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
+                // The interpreter restarts the current block after this command.
                 Ok(Token::Void)
             },
         )
@@ -158,9 +447,22 @@ impl Command {
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let code = decode::list(com, &args, 0)?;
-                let code_with_return = format!("op {}", code);
-                int.interpret_in_parenthesis(&code_with_return)
+                int.evaluate_in_parenthesis(&code)
             },
         )
     }
 }
+
+pub fn loop_condition(
+    int: &mut Interpreter,
+    com: &str,
+    code: &List,
+) -> Result<bool, Box<dyn Error>> {
+    match int.evaluate_list(code)? {
+        Token::Boolean(result) => Ok(result),
+        _ => Err(Box::from(LogoError::wrong_input_type(format!(
+            "{} expected a condition that outputs true or false",
+            com
+        )))),
+    }
+}
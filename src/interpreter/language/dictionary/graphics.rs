@@ -1,12 +1,13 @@
 use crate::interpreter::event::UiEvent;
 use crate::interpreter::interpreter::Interpreter;
 use crate::interpreter::language::structure::{Command, Params};
-use crate::interpreter::language::token::{Token, TokenVec};
+use crate::interpreter::language::token::{List, Token};
 use crate::interpreter::language::util::decode;
-use crate::interpreter::state::object::{Line, Object, Point, Size, TextStyle};
+use crate::interpreter::state::curve::{arc_points, bezier_points};
+use crate::interpreter::state::object::{Object, Point, Size, TextStyle};
+use crate::interpreter::state::pen::{LineCap, LineJoin, PenMode};
 use std::collections::HashSet;
-use std::thread;
-use std::time::Duration;
+use std::error::Error;
 
 impl Command {
     pub fn forward() -> Self {
@@ -26,14 +27,12 @@ impl Command {
                 let x = dist * h.cos();
                 let y = dist * h.sin();
                 let new_pos = Point::new(original_pos.x + x, original_pos.y + y);
-                turtle.pos = new_pos.clone();
+                turtle.move_to(new_pos.clone());
                 int.event
                     .send_ui(UiEvent::ObjectPos(turtle.name.clone(), turtle.pos.clone()));
                 if turtle.is_drawing {
                     let name = turtle.name.clone();
-                    let color = turtle.color.clone();
-                    let pen_size = turtle.pen_size.clone();
-                    let line = Line::new(original_pos, new_pos, color, pen_size);
+                    let line = turtle.line_to(original_pos, new_pos);
                     int.state.canvas.add_line(line.clone());
                     int.event.send_ui(UiEvent::AddLine(name, line));
                 }
@@ -59,14 +58,12 @@ impl Command {
                 let x = -dist * h.cos();
                 let y = -dist * h.sin();
                 let new_pos = Point::new(original_pos.x + x, original_pos.y + y);
-                turtle.pos = new_pos.clone();
+                turtle.move_to(new_pos.clone());
                 int.event
                     .send_ui(UiEvent::ObjectPos(turtle.name.clone(), turtle.pos.clone()));
                 if turtle.is_drawing {
                     let name = turtle.name.clone();
-                    let color = turtle.color.clone();
-                    let pen_size = turtle.pen_size.clone();
-                    let line = Line::new(original_pos, new_pos, color, pen_size);
+                    let line = turtle.line_to(original_pos, new_pos);
                     int.state.canvas.add_line(line.clone());
                     int.event.send_ui(UiEvent::AddLine(name, line));
                 }
@@ -150,9 +147,7 @@ impl Command {
                 if let Object::Turtle(turtle) = object {
                     if turtle.is_drawing {
                         let name = turtle.name.clone();
-                        let color = turtle.color.clone();
-                        let stroke_width = turtle.pen_size.clone();
-                        let line = Line::new(original_pos, new_pos, color, stroke_width);
+                        let line = turtle.line_to(original_pos, new_pos);
                         int.state.canvas.add_line(line.clone());
                         int.event.send_ui(UiEvent::AddLine(name, line));
                     }
@@ -193,9 +188,7 @@ impl Command {
                 if let Object::Turtle(turtle) = object {
                     if turtle.is_drawing {
                         let name = turtle.name.clone();
-                        let color = turtle.color.clone();
-                        let stroke_width = turtle.pen_size.clone();
-                        let line = Line::new(original_pos, new_pos, color, stroke_width);
+                        let line = turtle.line_to(original_pos, new_pos);
                         int.state.canvas.add_line(line.clone());
                         int.event.send_ui(UiEvent::AddLine(name, line));
                     }
@@ -211,7 +204,8 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 let pos = int.state.canvas.current_object()?.pos();
-                Ok(Token::List(format!("{} {}", pos.x, pos.y)))
+                let items = vec![Token::Number(pos.x), Token::Number(pos.y)];
+                Ok(Token::List(List::new(items)))
             },
         )
     }
@@ -221,15 +215,15 @@ impl Command {
             "setpos",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
                 let list = decode::list(com, &args, 0)?;
-                let list_items = int.parse_list(&list, true)?;
-                if list_items.len() != 2 {
+                if list.len() != 2 {
                     return Err(Box::from("setpos expected 2 coordinates"));
                 }
-                let Some(Token::Number(x)) = list_items.get(0) else {
+                let Some(Token::Number(x)) = list.first() else {
                     return Err(Box::from("setpos expected number for x-coordinate"));
                 };
-                let Some(Token::Number(y)) = list_items.get(1) else {
+                let Some(Token::Number(y)) = list.get(1) else {
                     return Err(Box::from("setpos expected number for y-coordinate"));
                 };
                 let object = int.state.canvas.current_object_mut()?;
@@ -246,9 +240,7 @@ impl Command {
                 if let Object::Turtle(turtle) = object {
                     if turtle.is_drawing {
                         let name = turtle.name.clone();
-                        let color = turtle.color.clone();
-                        let stroke_width = turtle.pen_size.clone();
-                        let line = Line::new(original_pos, new_pos, color, stroke_width);
+                        let line = turtle.line_to(original_pos, new_pos);
                         int.state.canvas.add_line(line.clone());
                         int.event.send_ui(UiEvent::AddLine(name, line));
                     }
@@ -299,7 +291,7 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 let object = int.state.canvas.current_object()?;
-                Ok(Token::Number(object.color()))
+                Ok(object.color().to_token())
             },
         )
     }
@@ -309,7 +301,8 @@ impl Command {
             "setcolor",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
-                let color = decode::number(com, &args, 0)?;
+                let args = int.resolve_list_inputs(args)?;
+                let color = decode::color(com, &args, 0)?;
                 let object = int.state.canvas.current_object_mut()?;
                 if object.is_locked() {
                     return Ok(Token::Void);
@@ -317,7 +310,7 @@ impl Command {
                 object.set_color(color);
                 int.event.send_ui(UiEvent::ObjectColor(
                     Box::from(object.name()),
-                    object.color().clone(),
+                    object.color(),
                 ));
                 Ok(Token::Void)
             },
@@ -333,7 +326,8 @@ impl Command {
                     return Err(Box::from(format!("{} expected a turtle", com)));
                 };
                 let size = &turtle.size;
-                Ok(Token::List(format!("{} {}", size.w, size.h)))
+                let items = vec![Token::Number(size.w), Token::Number(size.h)];
+                Ok(Token::List(List::new(items)))
             },
         )
     }
@@ -343,15 +337,15 @@ impl Command {
             "setsize",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
                 let token = decode::token(com, &args, 0)?;
                 let size = match token {
                     Token::Number(number) => Size::new(number, number),
                     Token::List(list) => {
-                        let list_items = int.parse_list(&list, true)?;
-                        let Some(Token::Number(width)) = list_items.get(0) else {
+                        let Some(Token::Number(width)) = list.first() else {
                             return Err(Box::from("setsize expected number for input 0"));
                         };
-                        let Some(Token::Number(height)) = list_items.get(1) else {
+                        let Some(Token::Number(height)) = list.get(1) else {
                             return Err(Box::from("setsize expected number for input 1"));
                         };
                         Size::new(*width, *height)
@@ -405,6 +399,224 @@ impl Command {
         )
     }
 
+    pub fn penpaint() -> Self {
+        Command::reserved(
+            "penpaint",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                set_pen_mode(int, com, PenMode::Paint)
+            },
+        )
+    }
+
+    pub fn penerase() -> Self {
+        Command::reserved(
+            "penerase",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                set_pen_mode(int, com, PenMode::Erase)
+            },
+        )
+    }
+
+    pub fn penreverse() -> Self {
+        Command::reserved(
+            "penreverse",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                set_pen_mode(int, com, PenMode::Reverse)
+            },
+        )
+    }
+
+    pub fn penmode() -> Self {
+        Command::reserved(
+            "penmode",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                Ok(Token::Word(turtle.pen.mode.name().to_string()))
+            },
+        )
+    }
+
+    pub fn penpattern() -> Self {
+        Command::reserved(
+            "penpattern",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                let items = turtle
+                    .pen
+                    .pattern
+                    .iter()
+                    .map(|length| Token::Number(*length));
+                Ok(Token::List(List::new(items.collect())))
+            },
+        )
+    }
+
+    pub fn setpenpattern() -> Self {
+        Command::reserved(
+            "setpenpattern",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
+                // Lengths of dashes and gaps, taking turns.
+                let list = decode::list(com, &args, 0)?;
+                let mut pattern = vec![];
+                for item in list.items() {
+                    match item {
+                        Token::Number(length) if *length >= 0.0 => pattern.push(*length),
+                        _ => return Err(Box::from(format!("{} expected lengths", com))),
+                    }
+                }
+                if !pattern.is_empty() && pattern.iter().all(|length| *length == 0.0) {
+                    return Err(Box::from(format!("{} expected a length above 0", com)));
+                }
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                if turtle.is_locked {
+                    return Ok(Token::Void);
+                }
+                turtle.pen.pattern = pattern;
+                turtle.pen_distance = 0.0;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn linecap() -> Self {
+        Command::reserved(
+            "linecap",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                Ok(Token::Word(turtle.pen.cap.name().to_string()))
+            },
+        )
+    }
+
+    pub fn setlinecap() -> Self {
+        Command::reserved(
+            "setlinecap",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let Some(cap) = LineCap::from(&name) else {
+                    return Err(Box::from(format!("{} expected round, butt or square", com)));
+                };
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                if turtle.is_locked {
+                    return Ok(Token::Void);
+                }
+                turtle.pen.cap = cap;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn linejoin() -> Self {
+        Command::reserved(
+            "linejoin",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                Ok(Token::Word(turtle.pen.join.name().to_string()))
+            },
+        )
+    }
+
+    pub fn setlinejoin() -> Self {
+        Command::reserved(
+            "setlinejoin",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let name = decode::word(com, &args, 0)?;
+                let Some(join) = LineJoin::from(&name) else {
+                    return Err(Box::from(format!("{} expected round, miter or bevel", com)));
+                };
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                if turtle.is_locked {
+                    return Ok(Token::Void);
+                }
+                turtle.pen.join = join;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn setonclick() -> Self {
+        Command::reserved(
+            "setonclick",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let code = decode::list(com, &args, 0)?;
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                // An empty list removes the instruction.
+                turtle.onclick = (!code.is_empty()).then(|| code.source().trim().to_string());
+                int.state.triggers.has_turtle_events = true;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn setontouching() -> Self {
+        Command::reserved(
+            "setontouching",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let code = decode::list(com, &args, 0)?;
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                turtle.ontouching = (!code.is_empty()).then(|| code.source().trim().to_string());
+                int.state.triggers.has_turtle_events = true;
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn setoncolor() -> Self {
+        Command::reserved(
+            "setoncolor",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let color = decode::color(com, &args, 0)?;
+                let code = decode::list(com, &args, 1)?;
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                let rgba = color.to_rgba();
+                turtle
+                    .oncolor
+                    .retain(|(watched, _)| watched.to_rgba() != rgba);
+                if !code.is_empty() {
+                    turtle
+                        .oncolor
+                        .push((color, code.source().trim().to_string()));
+                }
+                int.state.triggers.has_turtle_events = true;
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn shape() -> Self {
         Command::reserved(
             "shape",
@@ -472,6 +684,7 @@ impl Command {
                     return Ok(Token::Void);
                 }
                 turtle.is_drawing = false;
+                turtle.pen_distance = 0.0;
                 Ok(Token::Void)
             },
         )
@@ -620,15 +833,7 @@ impl Command {
                         com
                     )));
                 };
-                if !turtle1.is_visible || !turtle2.is_visible {
-                    return Ok(Token::Boolean(false));
-                }
-                let result = turtle1.pos.x - turtle1.size.w / 2.0
-                    < turtle2.pos.x + turtle2.size.w / 2.0
-                    && turtle1.pos.x + turtle1.size.w / 2.0 > turtle2.pos.x - turtle2.size.w / 2.0
-                    && turtle1.pos.y - turtle1.size.h / 2.0 < turtle2.pos.y + turtle2.size.h / 2.0
-                    && turtle1.pos.y + turtle1.size.h / 2.0 > turtle2.pos.y - turtle2.size.h / 2.0;
-                Ok(Token::Boolean(result))
+                Ok(Token::Boolean(turtle1.is_touching(turtle2)))
             },
         )
     }
@@ -638,15 +843,15 @@ impl Command {
             "on?",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
                 let list = decode::list(com, &args, 0)?;
-                let list_items = int.parse_list(&list, true)?;
-                if list_items.len() != 2 {
+                if list.len() != 2 {
                     return Err(Box::from("on? expected 2 coordinates"));
                 }
-                let Some(Token::Number(x)) = list_items.get(0) else {
+                let Some(Token::Number(x)) = list.first() else {
                     return Err(Box::from("on? expected number for x-coordinate"));
                 };
-                let Some(Token::Number(y)) = list_items.get(1) else {
+                let Some(Token::Number(y)) = list.get(1) else {
                     return Err(Box::from("on? expected number for y-coordinate"));
                 };
                 let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
@@ -672,7 +877,7 @@ impl Command {
                 };
                 let point = turtle.pos.clone();
                 let color = int.state.canvas.color_at_point(&point);
-                Ok(Token::Number(color))
+                Ok(color.to_token())
             },
         )
     }
@@ -725,13 +930,12 @@ impl Command {
                         }
                     }
                     Token::List(list) => {
-                        let list_items = int.parse_list(&list, false)?;
                         let mut style_set = HashSet::new();
-                        for item in list_items {
+                        for item in list.items() {
                             let Token::Word(word) = item else {
                                 continue;
                             };
-                            let Some(style) = TextStyle::from(word) else {
+                            let Some(style) = TextStyle::from(word.clone()) else {
                                 continue;
                             };
                             style_set.insert(style);
@@ -756,8 +960,19 @@ impl Command {
     pub fn text() -> Self {
         Command::reserved(
             "text",
-            Params::None,
-            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+            Params::Variadic(0),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                // With a name in parentheses, reports the definition of a procedure.
+                if !args.is_empty() {
+                    let name = decode::word(com, &args, 0)?;
+                    return match int.state.data.get_procedure(&name) {
+                        Some(procedure) => Ok(Token::List(procedure.text())),
+                        None => Err(Box::from(format!(
+                            "{} found no procedure named {}",
+                            com, name
+                        ))),
+                    };
+                }
                 let Object::Text(text) = int.state.canvas.current_object()? else {
                     return Err(Box::from(format!("{} expected a text", com)));
                 };
@@ -776,10 +991,8 @@ impl Command {
                 let string = match token {
                     Token::Word(word) => word.clone(),
                     Token::Number(number) => number.to_string(),
-                    Token::List(list) => {
-                        let items = int.parse_list(&list, false)?;
-                        items.join_to_list_string()
-                    }
+                    Token::List(list) => list.to_text(),
+                    Token::Array(array) => array.to_text(),
                     _ => return Err(Box::from("expected word, number or list")),
                 };
                 let Object::Text(text) = int.state.canvas.current_object_mut()? else {
@@ -820,7 +1033,8 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 let size = int.state.canvas.get_size();
-                Ok(Token::List(format!("{} {}", size.w, size.h)))
+                let items = vec![Token::Number(size.w), Token::Number(size.h)];
+                Ok(Token::List(List::new(items)))
             },
         )
     }
@@ -830,13 +1044,12 @@ impl Command {
             "setprojectsize",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
                 let list = decode::list(com, &args, 0)?;
-                let size: Vec<&str> = list.split(' ').collect();
-                if size.len() != 2 {
+                let [Token::Number(width), Token::Number(height)] = list.items() else {
                     return Err(Box::from("invalid project size"));
-                }
-                let width = size[0].parse::<f32>()?;
-                let height = size[1].parse::<f32>()?;
+                };
+                let (width, height) = (*width, *height);
                 int.state.canvas.set_size(width, height);
                 int.event.send_ui(UiEvent::CanvasSize(width, height));
                 Ok(Token::Void)
@@ -850,7 +1063,7 @@ impl Command {
             Params::None,
             |int: &mut Interpreter, _com: &str, _args: Vec<Token>| {
                 let bg_color = int.state.canvas.get_bg_color();
-                Ok(Token::Number(bg_color as f32))
+                Ok(bg_color.to_token())
             },
         )
     }
@@ -860,8 +1073,9 @@ impl Command {
             "setbg",
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
-                let color = decode::number(com, &args, 0)?;
-                int.state.canvas.set_bg_color(color as u8);
+                let args = int.resolve_list_inputs(args)?;
+                let color = decode::color(com, &args, 0)?;
+                int.state.canvas.set_bg_color(color);
                 int.event.send_ui(UiEvent::BgColor(color));
                 Ok(Token::Void)
             },
@@ -881,7 +1095,7 @@ impl Command {
                 }
                 let original_pos = turtle.pos.clone();
                 let new_pos = Point::zero();
-                turtle.pos = new_pos.clone();
+                turtle.move_to(new_pos.clone());
                 turtle.heading = 0.0;
                 int.event
                     .send_ui(UiEvent::ObjectPos(turtle.name.clone(), turtle.pos.clone()));
@@ -891,9 +1105,7 @@ impl Command {
                 ));
                 if turtle.is_drawing {
                     let name = turtle.name.clone();
-                    let color = turtle.color.clone();
-                    let pen_size = turtle.pen_size.clone();
-                    let line = Line::new(original_pos, new_pos, color, pen_size);
+                    let line = turtle.line_to(original_pos, new_pos);
                     int.state.canvas.add_line(line.clone());
                     int.event.send_ui(UiEvent::AddLine(name, line));
                 }
@@ -939,6 +1151,131 @@ impl Command {
         )
     }
 
+    pub fn fill() -> Self {
+        Command::reserved(
+            "fill",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                let (pos, color) = (turtle.pos.clone(), turtle.color);
+                if let Some(fill) = int.state.canvas.fill_region(&pos, color) {
+                    int.state.canvas.add_fill(fill.clone());
+                    int.event.send_ui(UiEvent::AddFill(fill));
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn beginfill() -> Self {
+        Command::reserved(
+            "beginfill",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                turtle.fill_path = Some(vec![turtle.pos.clone()]);
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn endfill() -> Self {
+        Command::reserved(
+            "endfill",
+            Params::None,
+            |int: &mut Interpreter, com: &str, _args: Vec<Token>| {
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                let Some(path) = turtle.fill_path.take() else {
+                    return Err(Box::from(format!("{} expected beginfill first", com)));
+                };
+                let color = turtle.color;
+                let fill = int.state.canvas.fill_polygon(&path, color);
+                if !fill.spans.is_empty() {
+                    int.state.canvas.add_fill(fill.clone());
+                    int.event.send_ui(UiEvent::AddFill(fill));
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn arc() -> Self {
+        Command::reserved(
+            "arc",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let angle = decode::number(com, &args, 0)?;
+                let radius = decode::number(com, &args, 1)?;
+                draw_ellipse(int, com, (radius, radius), angle.clamp(-360.0, 360.0))
+            },
+        )
+    }
+
+    pub fn circle() -> Self {
+        Command::reserved(
+            "circle",
+            Params::Fixed(1),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let radius = decode::number(com, &args, 0)?;
+                draw_ellipse(int, com, (radius, radius), 360.0)
+            },
+        )
+    }
+
+    pub fn ellipse() -> Self {
+        Command::reserved(
+            "ellipse",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let across = decode::number(com, &args, 0)?;
+                let along = decode::number(com, &args, 1)?;
+                draw_ellipse(int, com, (across, along), 360.0)
+            },
+        )
+    }
+
+    pub fn curveto() -> Self {
+        Command::reserved(
+            "curveto",
+            Params::Fixed(3),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let args = int.resolve_list_inputs(args)?;
+                let first = decode::point(com, &args, 0)?;
+                let second = decode::point(com, &args, 1)?;
+                let end = decode::point(com, &args, 2)?;
+                let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+                    return Err(Box::from(format!("{} expected a turtle", com)));
+                };
+                if turtle.is_locked {
+                    return Ok(Token::Void);
+                }
+                // The turtle follows the curve, so a fill takes in its shape.
+                let mut points = bezier_points([&turtle.pos, &first, &second, &end]);
+                if let Some(last) = points.last_mut() {
+                    *last = end;
+                }
+                for point in points.iter().skip(1) {
+                    turtle.move_to(point.clone());
+                }
+                int.event
+                    .send_ui(UiEvent::ObjectPos(turtle.name.clone(), turtle.pos.clone()));
+                if turtle.is_drawing {
+                    let name = turtle.name.clone();
+                    let curve = turtle.curve_through(points, false);
+                    int.state.canvas.add_curve(curve.clone());
+                    int.event.send_ui(UiEvent::AddCurve(name, curve));
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
     pub fn newturtle() -> Self {
         Command::reserved(
             "newturtle",
@@ -984,8 +1321,7 @@ impl Command {
             Params::Fixed(1),
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let duration = decode::number(com, &args, 0)? as u64;
-                int.event.send_ui(UiEvent::Wait(duration.clone()));
-                thread::sleep(Duration::from_millis(duration));
+                int.wait(duration)?;
                 Ok(Token::Void)
             },
         )
@@ -1011,7 +1347,7 @@ impl Command {
             |int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let token = decode::token(com, &args, 0)?;
                 let text = match token {
-                    Token::List(list) => list.clone(),
+                    Token::List(list) => list.to_text(),
                     token => token.to_string(),
                 };
                 int.event.send_ui(UiEvent::Announce(text));
@@ -1031,3 +1367,44 @@ impl Command {
         )
     }
 }
+
+fn set_pen_mode(int: &mut Interpreter, com: &str, mode: PenMode) -> Result<Token, Box<dyn Error>> {
+    // Choosing how to draw also puts the pen down.
+    let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+        return Err(Box::from(format!("{} expected a turtle", com)));
+    };
+    if turtle.is_locked {
+        return Ok(Token::Void);
+    }
+    turtle.pen.mode = mode;
+    turtle.is_drawing = true;
+    Ok(Token::Void)
+}
+
+fn draw_ellipse(
+    int: &mut Interpreter,
+    com: &str,
+    radii: (f32, f32),
+    sweep: f32,
+) -> Result<Token, Box<dyn Error>> {
+    // The turtle stays where it is, at the center, and the curve starts
+    // straight ahead of it and goes clockwise.
+    let Object::Turtle(turtle) = int.state.canvas.current_object_mut()? else {
+        return Err(Box::from(format!("{} expected a turtle", com)));
+    };
+    if turtle.is_locked || !turtle.is_drawing || sweep == 0.0 {
+        return Ok(Token::Void);
+    }
+    let mut points = arc_points(&turtle.pos, radii, turtle.heading, 0.0, sweep);
+    let is_closed = sweep.abs() >= 360.0;
+    if is_closed {
+        // The curve ends exactly where it started.
+        let (first, last) = (points[0].clone(), points.len() - 1);
+        points[last] = first;
+    }
+    let name = turtle.name.clone();
+    let curve = turtle.curve_through(points, is_closed);
+    int.state.canvas.add_curve(curve.clone());
+    int.event.send_ui(UiEvent::AddCurve(name, curve));
+    Ok(Token::Void)
+}
@@ -1,9 +1,12 @@
 use crate::interpreter::interpreter::Interpreter;
 use crate::interpreter::language::structure::{Command, Params};
-use crate::interpreter::language::token::{Token, TokenVec};
+use crate::interpreter::language::token::{Array, List, Token};
 use crate::interpreter::language::util::decode;
 use crate::interpreter::language::util::io::{KeyCode, KeyName};
+use crate::interpreter::util::error::LogoError;
+use std::error::Error;
 use std::f32::consts::E;
+use std::ops::Range;
 
 impl Command {
     pub fn sum() -> Self {
@@ -267,6 +270,32 @@ impl Command {
         )
     }
 
+    pub fn notequal() -> Self {
+        Command::reserved(
+            "notequal?",
+            Params::Fixed(2),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let arg1 = decode::token(com, &args, 0)?;
+                let arg2 = decode::token(com, &args, 1)?;
+                let result = arg1 != arg2;
+                Ok(Token::Boolean(result))
+            },
+        )
+    }
+
+    pub fn greaterequal() -> Self {
+        Command::reserved(
+            "greaterequal?",
+            Params::Fixed(2),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let arg1 = decode::number(com, &args, 0)?;
+                let arg2 = decode::number(com, &args, 1)?;
+                let result = arg1 >= arg2;
+                Ok(Token::Boolean(result))
+            },
+        )
+    }
+
     pub fn less() -> Self {
         Command::reserved(
             "less?",
@@ -280,6 +309,19 @@ impl Command {
         )
     }
 
+    pub fn lessequal() -> Self {
+        Command::reserved(
+            "lessequal?",
+            Params::Fixed(2),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let arg1 = decode::number(com, &args, 0)?;
+                let arg2 = decode::number(com, &args, 1)?;
+                let result = arg1 <= arg2;
+                Ok(Token::Boolean(result))
+            },
+        )
+    }
+
     pub fn or() -> Self {
         Command::reserved(
             "or",
@@ -411,12 +453,27 @@ impl Command {
             "list",
             Params::Variadic(2),
             |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
-                let mut result = decode::token(com, &args, 0)?.to_string();
-                for index in 1..args.len() {
-                    let word = decode::token(com, &args, index)?.to_string();
-                    result += &format!(" {}", word);
+                decode::token(com, &args, 0)?;
+                Ok(Token::List(List::new(args)))
+            },
+        )
+    }
+
+    pub fn sentence() -> Self {
+        Command::reserved(
+            "sentence",
+            Params::Variadic(2),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                // Lists are joined together, while words are added as they are.
+                decode::token(com, &args, 0)?;
+                let mut items = vec![];
+                for arg in args {
+                    match arg {
+                        Token::List(list) => items.extend(list.items().iter().cloned()),
+                        arg => items.push(arg),
+                    }
                 }
-                Ok(Token::List(result))
+                Ok(Token::List(List::new(items)))
             },
         )
     }
@@ -425,13 +482,11 @@ impl Command {
         Command::reserved(
             "count",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let token = decode::token(com, &args, 0)?;
                 match token {
-                    Token::List(list) => {
-                        let items = int.parse_list(&list, false)?;
-                        Ok(Token::Number(items.len() as f32))
-                    }
+                    Token::List(list) => Ok(Token::Number(list.len() as f32)),
+                    Token::Array(array) => Ok(Token::Number(array.len() as f32)),
                     Token::Word(word) => Ok(Token::Number(word.len() as f32)),
                     _ => Err(Box::from("count expected a word or list as input")),
                 }
@@ -443,13 +498,12 @@ impl Command {
         Command::reserved(
             "item",
             Params::Fixed(2),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let index = decode::number(com, &args, 0)? as usize;
                 let token = decode::token(com, &args, 1)?;
                 match token {
                     Token::List(list) => {
-                        let items = int.parse_list(&list, true)?;
-                        if let Some(item) = items.get(index) {
+                        if let Some(item) = list.get(index) {
                             Ok(item.clone())
                         } else {
                             let message = format!("item couldn't find index {} in list", index);
@@ -464,20 +518,131 @@ impl Command {
                             Err(Box::from(message))
                         }
                     }
+                    Token::Array(array) => {
+                        let index = decode::number(com, &args, 0)? as i32;
+                        array_item(com, &array, index)
+                    }
                     _ => Err(Box::from("item expected a word or list for input 1")),
                 }
             },
         )
     }
 
+    pub fn array() -> Self {
+        Command::reserved(
+            "array",
+            Params::Variadic(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let size = array_size(com, decode::number(com, &args, 0)?, 1)?;
+                let origin = array_origin(com, &args, 1)?;
+                let items = vec![Token::List(List::empty()); size];
+                Ok(Token::Array(Array::new(items, origin)))
+            },
+        )
+    }
+
+    pub fn mdarray() -> Self {
+        Command::reserved(
+            "mdarray",
+            Params::Variadic(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let mut sizes = vec![];
+                let mut total = 1;
+                for size in decode::list(com, &args, 0)?.items() {
+                    let Token::Number(size) = size else {
+                        return Err(Box::from(format!("{} expected a list of sizes", com)));
+                    };
+                    let size = array_size(com, *size, total)?;
+                    total = total.saturating_mul(size.max(1));
+                    sizes.push(size);
+                }
+                let origin = array_origin(com, &args, 1)?;
+                Ok(nested_array(&sizes, origin))
+            },
+        )
+    }
+
+    pub fn listtoarray() -> Self {
+        Command::reserved(
+            "listtoarray",
+            Params::Variadic(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let list = decode::list(com, &args, 0)?;
+                let origin = array_origin(com, &args, 1)?;
+                Ok(Token::Array(Array::new(list.items().to_vec(), origin)))
+            },
+        )
+    }
+
+    pub fn arraytolist() -> Self {
+        Command::reserved(
+            "arraytolist",
+            Params::Fixed(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let array = decode::array(com, &args, 0)?;
+                Ok(Token::List(List::new(array.items())))
+            },
+        )
+    }
+
+    pub fn isarray() -> Self {
+        Command::reserved(
+            "array?",
+            Params::Fixed(1),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let token = decode::token(com, &args, 0)?;
+                Ok(Token::Boolean(matches!(token, Token::Array(..))))
+            },
+        )
+    }
+
+    pub fn setitem() -> Self {
+        Command::reserved(
+            "setitem",
+            Params::Fixed(3),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let index = decode::number(com, &args, 0)? as i32;
+                let array = decode::array(com, &args, 1)?;
+                let value = decode::token(com, &args, 2)?;
+                set_array_item(com, &array, index, value.clone())
+            },
+        )
+    }
+
+    pub fn mditem() -> Self {
+        Command::reserved(
+            "mditem",
+            Params::Fixed(2),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let indices = decode::list(com, &args, 0)?;
+                let array = decode::array(com, &args, 1)?;
+                let (array, index) = innermost_array(com, &indices, array)?;
+                array_item(com, &array, index)
+            },
+        )
+    }
+
+    pub fn mdsetitem() -> Self {
+        Command::reserved(
+            "mdsetitem",
+            Params::Fixed(3),
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let indices = decode::list(com, &args, 0)?;
+                let array = decode::array(com, &args, 1)?;
+                let value = decode::token(com, &args, 2)?;
+                let (array, index) = innermost_array(com, &indices, array)?;
+                set_array_item(com, &array, index, value.clone())
+            },
+        )
+    }
+
     pub fn first() -> Self {
         Command::reserved(
             "first",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let list = decode::list(com, &args, 0)?;
-                let items = int.parse_list(&list, true)?;
-                if let Some(first) = items.first() {
+                if let Some(first) = list.first() {
                     Ok(first.clone())
                 } else {
                     Err(Box::from("first cannot get from empty list"))
@@ -490,10 +655,9 @@ impl Command {
         Command::reserved(
             "last",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let list = decode::list(com, &args, 0)?;
-                let items = int.parse_list(&list, true)?;
-                if let Some(last) = items.last() {
+                if let Some(last) = list.last() {
                     Ok(last.clone())
                 } else {
                     Err(Box::from("last cannot get from empty list"))
@@ -506,15 +670,12 @@ impl Command {
         Command::reserved(
             "butfirst",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let list = decode::list(com, &args, 0)?;
-                let items = int.parse_list(&list, false)?;
-                if items.is_empty() {
+                if list.is_empty() {
                     return Err(Box::from("butfirst cannot get from empty list"));
                 }
-                let rest = &items[1..];
-                let joined = rest.to_vec().join_to_list_string();
-                Ok(Token::List(joined))
+                Ok(Token::List(list.butfirst()))
             },
         )
     }
@@ -523,15 +684,12 @@ impl Command {
         Command::reserved(
             "butlast",
             Params::Fixed(1),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let list = decode::list(com, &args, 0)?;
-                let items = int.parse_list(&list, false)?;
-                if items.is_empty() {
+                if list.is_empty() {
                     return Err(Box::from("butlast cannot get from empty list"));
                 }
-                let rest = &items[..items.len() - 1];
-                let joined = rest.to_vec().join_to_list_string();
-                Ok(Token::List(joined))
+                Ok(Token::List(list.butlast()))
             },
         )
     }
@@ -543,8 +701,7 @@ impl Command {
             |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let item = decode::token(com, &args, 0)?;
                 let list = decode::list(com, &args, 1)?;
-                let result = format!("{} {}", item.to_string(), list);
-                Ok(Token::List(result))
+                Ok(Token::List(list.with_first(item)))
             },
         )
     }
@@ -556,8 +713,7 @@ impl Command {
             |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let item = decode::token(com, &args, 0)?;
                 let list = decode::list(com, &args, 1)?;
-                let result = format!("{} {}", list, item.to_string());
-                Ok(Token::List(result))
+                Ok(Token::List(list.with_last(item)))
             },
         )
     }
@@ -566,12 +722,10 @@ impl Command {
         Command::reserved(
             "member?",
             Params::Fixed(2),
-            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+            |_int: &mut Interpreter, com: &str, args: Vec<Token>| {
                 let item = decode::token(com, &args, 0)?;
                 let list = decode::list(com, &args, 1)?;
-                let list_items = int.parse_list(&list, true)?;
-                let result = list_items.contains(&item);
-                Ok(Token::Boolean(result))
+                Ok(Token::Boolean(list.contains(&item)))
             },
         )
     }
@@ -591,4 +745,250 @@ impl Command {
             },
         )
     }
+
+    pub fn map() -> Self {
+        Command::reserved(
+            "map",
+            Params::Variadic(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let inputs = template_inputs(com, &args, 1..args.len())?;
+                let mut results = vec![];
+                for item_inputs in inputs {
+                    results.push(int.apply_template(com, &template, item_inputs)?);
+                }
+                Ok(Token::List(List::new(results)))
+            },
+        )
+    }
+
+    pub fn filter() -> Self {
+        Command::reserved(
+            "filter",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let list = decode::list(com, &args, 1)?;
+                let mut results = vec![];
+                for item in list.items() {
+                    if template_check(int, com, &template, item)? {
+                        results.push(item.clone());
+                    }
+                }
+                Ok(Token::List(List::new(results)))
+            },
+        )
+    }
+
+    pub fn find() -> Self {
+        Command::reserved(
+            "find",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let list = decode::list(com, &args, 1)?;
+                for item in list.items() {
+                    if template_check(int, com, &template, item)? {
+                        return Ok(item.clone());
+                    }
+                }
+                Ok(Token::List(List::empty()))
+            },
+        )
+    }
+
+    pub fn reduce() -> Self {
+        Command::reserved(
+            "reduce",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let list = decode::list(com, &args, 1)?;
+                // Combine from the right, so reduce [?1 - ?2] [1 2 3] is 1 - (2 - 3).
+                let mut items = list.items().iter().rev();
+                let Some(mut result) = items.next().cloned() else {
+                    return Err(Box::from("reduce cannot reduce an empty list"));
+                };
+                for item in items {
+                    result = int.apply_template(com, &template, vec![item.clone(), result])?;
+                }
+                Ok(result)
+            },
+        )
+    }
+
+    pub fn foreach() -> Self {
+        Command::reserved(
+            "foreach",
+            Params::Variadic(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                // The template comes last, after one or more lists.
+                let template = decode::token(com, &args, args.len().saturating_sub(1))?;
+                let inputs = template_inputs(com, &args, 0..args.len() - 1)?;
+                for item_inputs in inputs {
+                    int.apply_template(com, &template, item_inputs)?;
+                }
+                Ok(Token::Void)
+            },
+        )
+    }
+
+    pub fn apply() -> Self {
+        Command::reserved(
+            "apply",
+            Params::Fixed(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let inputs = decode::list(com, &args, 1)?;
+                int.apply_template(com, &template, inputs.items().to_vec())
+            },
+        )
+    }
+
+    pub fn invoke() -> Self {
+        Command::reserved(
+            "invoke",
+            Params::Variadic(2),
+            |int: &mut Interpreter, com: &str, args: Vec<Token>| {
+                let template = decode::token(com, &args, 0)?;
+                let inputs = args.iter().skip(1).cloned().collect();
+                int.apply_template(com, &template, inputs)
+            },
+        )
+    }
+}
+
+fn template_inputs(
+    com: &str,
+    args: &Vec<Token>,
+    list_indices: Range<usize>,
+) -> Result<Vec<Vec<Token>>, Box<dyn Error>> {
+    // Pairs up the items of each list, for templates that take an item from each.
+    let mut lists = vec![];
+    for index in list_indices {
+        lists.push(decode::list(com, args, index)?);
+    }
+    let Some(length) = lists.first().map(|list| list.len()) else {
+        return Err(Box::from(format!("{} expected a list", com)));
+    };
+    if lists.iter().any(|list| list.len() != length) {
+        return Err(Box::from(format!(
+            "{} expected lists of the same length",
+            com
+        )));
+    }
+    let inputs = (0..length)
+        .map(|index| {
+            lists
+                .iter()
+                .map(|list| list.items()[index].clone())
+                .collect()
+        })
+        .collect();
+    Ok(inputs)
+}
+
+fn template_check(
+    int: &mut Interpreter,
+    com: &str,
+    template: &Token,
+    item: &Token,
+) -> Result<bool, Box<dyn Error>> {
+    match int.apply_template(com, template, vec![item.clone()])? {
+        Token::Boolean(result) => Ok(result),
+        _ => Err(Box::from(format!(
+            "{} expected a template that outputs true or false",
+            com
+        ))),
+    }
+}
+
+fn array_origin(com: &str, args: &Vec<Token>, index: usize) -> Result<i32, Box<dyn Error>> {
+    // Arrays count from 1 unless given another origin.
+    match args.get(index) {
+        Some(..) => Ok(decode::number(com, args, index)? as i32),
+        None => Ok(1),
+    }
+}
+
+// The most items that all the arrays made by one command can hold together.
+const MAX_ARRAY_ITEMS: usize = 1_000_000;
+
+fn array_size(com: &str, size: f32, outer_items: usize) -> Result<usize, Box<dyn Error>> {
+    // Each array inside another is made once for every item of the outer one.
+    let is_whole = size >= 0.0 && size.fract() == 0.0;
+    if !is_whole || size as usize > MAX_ARRAY_ITEMS / outer_items {
+        return Err(Box::from(LogoError::wrong_input_type(format!(
+            "{} expected a whole number of items up to {}",
+            com, MAX_ARRAY_ITEMS
+        ))));
+    }
+    Ok(size as usize)
+}
+
+fn nested_array(sizes: &[usize], origin: i32) -> Token {
+    let Some((size, rest)) = sizes.split_first() else {
+        return Token::List(List::empty());
+    };
+    let items = (0..*size).map(|_| nested_array(rest, origin)).collect();
+    Token::Array(Array::new(items, origin))
+}
+
+fn array_item(com: &str, array: &Array, index: i32) -> Result<Token, Box<dyn Error>> {
+    match array.get(index) {
+        Some(item) => Ok(item),
+        None => Err(Box::from(format!(
+            "{} couldn't find index {} in array",
+            com, index
+        ))),
+    }
+}
+
+fn set_array_item(
+    com: &str,
+    array: &Array,
+    index: i32,
+    value: Token,
+) -> Result<Token, Box<dyn Error>> {
+    if let Token::Array(inner) = &value {
+        if inner.contains_array(array) {
+            return Err(Box::from(format!(
+                "{} can't put an array inside itself",
+                com
+            )));
+        }
+    }
+    if !array.set(index, value) {
+        return Err(Box::from(format!(
+            "{} couldn't find index {} in array",
+            com, index
+        )));
+    }
+    Ok(Token::Void)
+}
+
+fn innermost_array(
+    com: &str,
+    indices: &List,
+    array: Array,
+) -> Result<(Array, i32), Box<dyn Error>> {
+    // Follows all but the last index through nested arrays.
+    let mut numbers = vec![];
+    for index in indices.items() {
+        let Token::Number(index) = index else {
+            return Err(Box::from(format!("{} expected a list of indices", com)));
+        };
+        numbers.push(*index as i32);
+    }
+    let Some((last, path)) = numbers.split_last() else {
+        return Err(Box::from(format!("{} expected a list of indices", com)));
+    };
+    let mut array = array;
+    for index in path {
+        array = match array_item(com, &array, *index)? {
+            Token::Array(inner) => inner,
+            _ => return Err(Box::from(format!("{} expected nested arrays", com))),
+        };
+    }
+    Ok((array, *last))
 }
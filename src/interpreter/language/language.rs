@@ -6,7 +6,15 @@ use std::io::Write;
 
 pub struct Language {
     commands: HashMap<Box<str>, Command>,
-    infix_operators: HashMap<Box<str>, Command>,
+    infix_operators: HashMap<Box<str>, InfixOperator>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InfixOperator {
+    pub command: Command,
+    // Operators with higher precedence are applied first.
+    pub precedence: u8,
+    pub is_right_associative: bool,
 }
 
 impl Language {
@@ -21,18 +29,26 @@ impl Language {
         language.add(Command::again());
         language.add(Command::and());
         language.add(Command::announce());
+        language.add(Command::apply());
+        language.add(Command::arc());
         language.add(Command::arctan());
+        language.add(Command::array());
+        language.add(Command::arraytolist());
         language.add(Command::ascii());
         language.add(Command::ask());
         language.add(Command::back());
+        language.add(Command::beginfill());
         language.add(Command::bg());
         language.add(Command::butfirst());
         language.add(Command::butlast());
+        language.add(Command::cancel());
         language.add(Command::carefully());
+        language.add(Command::catch());
         language.add(Command::cc());
         language.add(Command::cg());
         language.add(Command::char());
         language.add(Command::chdir());
+        language.add(Command::circle());
         language.add(Command::clean());
         language.add(Command::clearname());
         language.add(Command::clearnames());
@@ -40,32 +56,52 @@ impl Language {
         language.add(Command::clicked());
         language.add(Command::color());
         language.add(Command::colorunder());
+        language.add(Command::copydef());
         language.add(Command::cos());
         language.add(Command::count());
         language.add(Command::currentdir());
+        language.add(Command::curveto());
+        language.add(Command::define());
         language.add(Command::difference());
         language.add(Command::directories());
         language.add(Command::distance());
         language.add(Command::dolist());
         language.add(Command::dotimes());
+        language.add(Command::dountil());
+        language.add(Command::dowhile());
+        language.add(Command::ellipse());
         language.add(Command::empty());
+        language.add(Command::endfill());
         language.add(Command::equal());
+        language.add(Command::erall());
+        language.add(Command::erase());
+        language.add(Command::erpl());
+        language.add(Command::error());
         language.add(Command::errormessage());
         language.add(Command::exp());
         language.add(Command::files());
+        language.add(Command::fill());
+        language.add(Command::filter());
+        language.add(Command::find());
         language.add(Command::first());
         language.add(Command::fontsize());
+        language.add(Command::foreach());
         language.add(Command::forever());
+        language.add(Command::forloop());
         language.add(Command::forward());
         language.add(Command::fput());
         language.add(Command::freeze());
+        language.add(Command::gprop());
         language.add(Command::greater());
+        language.add(Command::greaterequal());
         language.add(Command::heading());
         language.add(Command::home());
         language.add(Command::ht());
         language.add(Command::ifelse());
         language.add(Command::ifthen());
         language.add(Command::int());
+        language.add(Command::invoke());
+        language.add(Command::isarray());
         language.add(Command::islist());
         language.add(Command::isnumber());
         language.add(Command::isword());
@@ -73,36 +109,56 @@ impl Language {
         language.add(Command::key());
         language.add(Command::keydown());
         language.add(Command::last());
+        language.add(Command::launch());
         language.add(Command::left());
         language.add(Command::less());
+        language.add(Command::lessequal());
         language.add(Command::letvar());
+        language.add(Command::linecap());
+        language.add(Command::linejoin());
         language.add(Command::list());
+        language.add(Command::listtoarray());
         language.add(Command::ln());
         language.add(Command::loadpict());
+        language.add(Command::loadproject());
         language.add(Command::loadshape());
         language.add(Command::loadtext());
         language.add(Command::local());
         language.add(Command::log());
         language.add(Command::lput());
         language.add(Command::make());
+        language.add(Command::map());
+        language.add(Command::mdarray());
+        language.add(Command::mditem());
+        language.add(Command::mdsetitem());
         language.add(Command::member());
         language.add(Command::minus());
         language.add(Command::newtext());
         language.add(Command::newturtle());
         language.add(Command::not());
+        language.add(Command::notequal());
         language.add(Command::on());
         language.add(Command::or());
         language.add(Command::output());
         language.add(Command::pd());
+        language.add(Command::penerase());
+        language.add(Command::penmode());
+        language.add(Command::penpaint());
+        language.add(Command::penpattern());
+        language.add(Command::penreverse());
         language.add(Command::pensize());
         language.add(Command::pi());
         language.add(Command::pick());
         language.add(Command::pictlist());
         language.add(Command::placepict());
+        language.add(Command::plist());
         language.add(Command::pos());
         language.add(Command::power());
+        language.add(Command::pprop());
         language.add(Command::print());
+        language.add(Command::printout());
         language.add(Command::procedures());
+        language.add(Command::processes());
         language.add(Command::product());
         language.add(Command::projectsize());
         language.add(Command::pu());
@@ -110,17 +166,32 @@ impl Language {
         language.add(Command::random());
         language.add(Command::readchar());
         language.add(Command::readclick());
+        language.add(Command::reduce());
         language.add(Command::remainder());
         language.add(Command::remove());
+        language.add(Command::remprop());
+        language.add(Command::repcount());
         language.add(Command::repeat());
         language.add(Command::resett());
         language.add(Command::right());
         language.add(Command::round());
         language.add(Command::run());
+        language.add(Command::runresult());
+        language.add(Command::savepict());
+        language.add(Command::saveproject());
+        language.add(Command::savesvg());
+        language.add(Command::sentence());
         language.add(Command::setbg());
         language.add(Command::setcolor());
         language.add(Command::setfontsize());
+        language.add(Command::setitem());
+        language.add(Command::setlinecap());
+        language.add(Command::setlinejoin());
+        language.add(Command::setonclick());
+        language.add(Command::setoncolor());
+        language.add(Command::setontouching());
         language.add(Command::setheading());
+        language.add(Command::setpenpattern());
         language.add(Command::setpensize());
         language.add(Command::setpos());
         language.add(Command::setprojectsize());
@@ -134,26 +205,37 @@ impl Language {
         language.add(Command::sin());
         language.add(Command::size());
         language.add(Command::sqrt());
+        language.add(Command::step());
         language.add(Command::st());
+        language.add(Command::stop());
+        language.add(Command::stopall());
         language.add(Command::sum());
         language.add(Command::talkto());
         language.add(Command::tan());
         language.add(Command::text());
         language.add(Command::textlist());
+        language.add(Command::throw());
         language.add(Command::timer());
         language.add(Command::to());
         language.add(Command::touching());
         language.add(Command::towards());
+        language.add(Command::trace());
         language.add(Command::turtlesown());
         language.add(Command::unfreeze());
+        language.add(Command::until());
+        language.add(Command::unstep());
+        language.add(Command::untrace());
         language.add(Command::visible());
         language.add(Command::wait());
+        language.add(Command::when());
+        language.add(Command::whileloop());
         language.add(Command::who());
         language.add(Command::word());
         language.add(Command::xcor());
         language.add(Command::ycor());
 
         // Alias
+        language.add_alias("#", Command::repcount());
         language.add_alias("bf", Command::butfirst());
         language.add_alias("bk", Command::back());
         language.add_alias("bl", Command::butlast());
@@ -161,25 +243,31 @@ impl Language {
         language.add_alias("fd", Command::forward());
         language.add_alias("lt", Command::left());
         language.add_alias("op", Command::output());
+        language.add_alias("pe", Command::penerase());
+        language.add_alias("po", Command::printout());
+        language.add_alias("ppt", Command::penpaint());
         language.add_alias("pr", Command::print());
+        language.add_alias("px", Command::penreverse());
         language.add_alias("rt", Command::right());
-        language.add_alias("se", Command::list());
-        language.add_alias("sentence", Command::list());
+        language.add_alias("se", Command::sentence());
         language.add_alias("setc", Command::setcolor());
         language.add_alias("seth", Command::setheading());
         language.add_alias("setsh", Command::setshape());
         language.add_alias("tto", Command::talkto());
 
         // Infix Operators
-        language.add_infix("+", Command::sum());
-        language.add_infix("-", Command::difference());
-        language.add_infix("*", Command::product());
-        language.add_infix("/", Command::quotient());
-        language.add_infix("^", Command::power());
-        language.add_infix("%", Command::remainder());
-        language.add_infix("=", Command::equal());
-        language.add_infix(">", Command::greater());
-        language.add_infix("<", Command::less());
+        language.add_infix("^", 4, Command::power());
+        language.add_infix("*", 3, Command::product());
+        language.add_infix("/", 3, Command::quotient());
+        language.add_infix("%", 3, Command::remainder());
+        language.add_infix("+", 2, Command::sum());
+        language.add_infix("-", 2, Command::difference());
+        language.add_infix("=", 1, Command::equal());
+        language.add_infix("<>", 1, Command::notequal());
+        language.add_infix(">", 1, Command::greater());
+        language.add_infix("<", 1, Command::less());
+        language.add_infix(">=", 1, Command::greaterequal());
+        language.add_infix("<=", 1, Command::lessequal());
 
         // Hidden Commands
         language.add(Command::paren());
@@ -198,19 +286,29 @@ impl Language {
         self.commands.insert(command.name.clone(), command);
     }
 
+    pub fn remove(&mut self, command_name: &str) {
+        self.commands.remove(command_name);
+    }
+
     pub fn add_alias(&mut self, alias: &str, command: Command) {
         self.commands.insert(Box::from(alias), command);
     }
 
-    pub fn lookup_infix(&self, command_name: &str) -> Option<Command> {
-        if let Some(command) = self.infix_operators.get(command_name) {
-            return Some(command.clone());
+    pub fn lookup_infix(&self, operator_name: &str) -> Option<InfixOperator> {
+        if let Some(operator) = self.infix_operators.get(operator_name) {
+            return Some(operator.clone());
         }
         None
     }
 
-    pub fn add_infix(&mut self, name: &str, command: Command) {
-        self.infix_operators.insert(Box::from(name), command);
+    pub fn add_infix(&mut self, name: &str, precedence: u8, command: Command) {
+        let operator = InfixOperator {
+            command,
+            precedence,
+            // Powers group from the right, so 2 ^ 3 ^ 2 is 2 ^ 9.
+            is_right_associative: name == "^",
+        };
+        self.infix_operators.insert(Box::from(name), operator);
     }
 
     pub fn _export(&self) -> Result<(), Box<dyn Error>> {
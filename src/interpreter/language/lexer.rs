@@ -1,13 +1,26 @@
-use crate::interpreter::language::language::Language;
-use crate::interpreter::language::structure::{Command, CommandAction, Params};
-use crate::interpreter::language::token::Token;
-use crate::interpreter::util::error::eof_error;
-use std::collections::VecDeque;
+use crate::interpreter::language::language::{InfixOperator, Language};
+use crate::interpreter::language::structure::ProcedureParams;
+use crate::interpreter::language::structure::{BlockCache, Command, CommandAction};
+use crate::interpreter::language::structure::{Params, ParsedBlock};
+use crate::interpreter::language::token::{List, Token};
+use crate::interpreter::util::error::{eof_error, is_eof, LogoError};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 pub struct Lexer {
     language: Language,
     code_blocks: VecDeque<CodeBlock>,
+    // Names that primitives were copied to with "copydef".
+    copied_names: HashSet<Box<str>>,
+    // Counts definitions, and the count when each name last changed.
+    generation: usize,
+    changed_names: HashMap<Box<str>, usize>,
+    // When the language was last reset, which changes every name.
+    reset_generation: usize,
+    // The names looked up by the block being read.
+    read_names: Vec<Box<str>>,
 }
 
 impl Lexer {
@@ -15,35 +28,27 @@ impl Lexer {
         Lexer {
             language: Language::default(),
             code_blocks: VecDeque::new(),
+            copied_names: HashSet::new(),
+            generation: 0,
+            changed_names: HashMap::new(),
+            reset_generation: 0,
+            read_names: vec![],
         }
     }
 
     pub fn push_block(&mut self, text: &str, in_paren: bool) {
         let block = CodeBlock {
-            text: String::from(text),
+            chars: text.chars().collect(),
             position: 0,
             in_paren,
         };
         self.code_blocks.push_back(block);
     }
 
-    pub fn pop_block(&mut self) -> bool {
-        let exiting_main = self.code_blocks.len() == 1;
-        if self.code_blocks.len() > 0 {
-            self.code_blocks.pop_back();
-        }
-        exiting_main
-    }
-
     pub fn clear_blocks(&mut self) {
         self.code_blocks.clear();
     }
 
-    pub fn return_to_start_of_block(&mut self) {
-        let block = self.code_blocks.back_mut().unwrap();
-        block.position = 0;
-    }
-
     fn current_block(&mut self) -> &mut CodeBlock {
         self.code_blocks.back_mut().unwrap()
     }
@@ -61,10 +66,214 @@ impl Lexer {
         }
         let new_command = Command::user_defined(name, params, action);
         self.language.add(new_command);
+        self.copied_names.remove(name);
+        self.change_name(name);
         Ok(())
     }
 
+    pub fn define_copy(&mut self, name: &str, command: Command) -> Result<(), Box<dyn Error>> {
+        self.define(name, command.params, command.action)?;
+        self.copied_names.insert(Box::from(name));
+        Ok(())
+    }
+
+    pub fn copied_names(&self) -> Vec<String> {
+        self.copied_names
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    pub fn undefine(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        match self.language.lookup(name) {
+            Some(command) if command.is_reserved => Err(Box::from(format!("{} is reserved", name))),
+            Some(..) => {
+                self.language.remove(name);
+                self.copied_names.remove(name);
+                self.change_name(name);
+                Ok(())
+            }
+            None => Err(Box::from(LogoError::unknown_procedure(format!(
+                "I don't know how to {}",
+                name
+            )))),
+        }
+    }
+
+    pub fn reset_language(&mut self) {
+        // Forget everything defined by the program, keeping any code being read.
+        self.language = Language::default();
+        self.copied_names.clear();
+        self.changed_names.clear();
+        self.generation += 1;
+        self.reset_generation = self.generation;
+    }
+
+    fn change_name(&mut self, name: &str) {
+        // Blocks read with the name before may have read it differently,
+        // so they need to be read again.
+        self.generation += 1;
+        self.changed_names.insert(Box::from(name), self.generation);
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Command> {
+        self.language.lookup(name)
+    }
+
+    pub fn is_current(&self, block: &ParsedBlock) -> bool {
+        if block.checked.load(Ordering::Relaxed) == self.generation {
+            return true;
+        }
+        if block.generation < self.reset_generation {
+            return false;
+        }
+        let is_current = block.names.iter().all(|name| {
+            self.changed_names
+                .get(name)
+                .map_or(true, |changed| *changed <= block.generation)
+        });
+        if is_current {
+            block.checked.store(self.generation, Ordering::Relaxed);
+        }
+        is_current
+    }
+
+    pub fn parse(
+        &mut self,
+        code: &str,
+        in_paren: bool,
+    ) -> Result<Arc<ParsedBlock>, Box<dyn Error>> {
+        Ok(Arc::new(self.parse_block(code, in_paren, 0)?))
+    }
+
+    // Reads code through a cache, only reading it again when a name
+    // it used has changed.
+    pub fn parse_cached(
+        &mut self,
+        code: &str,
+        in_paren: bool,
+        cache: &BlockCache,
+    ) -> Result<Arc<ParsedBlock>, Box<dyn Error>> {
+        if let Some(block) = cache.get() {
+            if block.in_paren == in_paren && self.is_current(&block) {
+                return Ok(block);
+            }
+        }
+        let block = self.parse(code, in_paren)?;
+        cache.set(block.clone());
+        Ok(block)
+    }
+
+    pub fn parse_list(
+        &mut self,
+        list: &List,
+        in_paren: bool,
+    ) -> Result<Arc<ParsedBlock>, Box<dyn Error>> {
+        match list.block_cache() {
+            Some(cache) => self.parse_cached(&list.source(), in_paren, cache),
+            None => self.parse(&list.source(), in_paren),
+        }
+    }
+
+    // Parse the rest of a block from a position partway through, used
+    // when it has to be read again after a definition. Positions stay
+    // relative to the whole code so that error locations still line up
+    // with the source.
+    pub fn parse_from(
+        &mut self,
+        block: &ParsedBlock,
+        start: usize,
+    ) -> Result<Arc<ParsedBlock>, Box<dyn Error>> {
+        let code: String = block.chars.iter().collect();
+        Ok(Arc::new(self.parse_block(&code, block.in_paren, start)?))
+    }
+
+    fn parse_block(
+        &mut self,
+        code: &str,
+        in_paren: bool,
+        start: usize,
+    ) -> Result<ParsedBlock, Box<dyn Error>> {
+        self.push_block(code, in_paren);
+        self.current_block().position = start;
+        let outer_names = std::mem::take(&mut self.read_names);
+        let mut statements = vec![];
+        let mut start_positions = vec![];
+        let mut end_positions = vec![];
+        loop {
+            self.consume_whitespace();
+            let start_position = self.current_block().position;
+            match self.read_token() {
+                // Comments do not need to be executed.
+                Ok(Token::Void) => continue,
+                Ok(token) => {
+                    statements.push(token);
+                    start_positions.push(start_position);
+                    end_positions.push(self.current_block().position);
+                }
+                Err(err) => {
+                    let block = self.code_blocks.pop_back().unwrap();
+                    let mut names = std::mem::replace(&mut self.read_names, outer_names);
+                    if is_eof(&err) {
+                        names.sort();
+                        names.dedup();
+                        return Ok(ParsedBlock {
+                            chars: block.chars,
+                            statements,
+                            start_positions,
+                            end_positions,
+                            in_paren,
+                            generation: self.generation,
+                            names,
+                            checked: AtomicUsize::new(self.generation),
+                        });
+                    }
+                    return Err(err);
+                }
+            }
+        }
+    }
+
     pub fn read_token(&mut self) -> Result<Token, Box<dyn Error>> {
+        self.read_expression(0)
+    }
+
+    fn read_expression(&mut self, min_precedence: u8) -> Result<Token, Box<dyn Error>> {
+        let mut left = self.read_operand()?;
+
+        // Look for infix operators like +, -, * or / which come after the first
+        // argument but before the second. Operators that bind tighter than the
+        // current one are read into its right side first.
+        while let Some((operator, end_position)) = self.peek_infix_operator() {
+            if operator.precedence < min_precedence {
+                break;
+            }
+            let block = self.current_block();
+            let symbol: String = block.chars[block.position..end_position].iter().collect();
+            block.position = end_position;
+            let next_precedence = if operator.is_right_associative {
+                operator.precedence
+            } else {
+                operator.precedence + 1
+            };
+            // Running out of code here means the operator has nothing on its
+            // right, rather than the end of the block.
+            let right = self.read_expression(next_precedence).map_err(|err| {
+                if is_eof(&err) {
+                    Box::from(LogoError::syntax(format!(
+                        "found nothing after {}",
+                        symbol.trim()
+                    )))
+                } else {
+                    err
+                }
+            })?;
+            left = Token::Command(operator.command, vec![left, right]);
+        }
+        Ok(left)
+    }
+
+    fn read_operand(&mut self) -> Result<Token, Box<dyn Error>> {
         self.consume_whitespace();
         let block = self.current_block();
         if block.current_char() == '\0' {
@@ -77,21 +286,33 @@ impl Lexer {
         }
         // Check for Groups in Parenthesis
         if block.current_char() == '(' {
-            let token = self.read_parenthesis()?;
-            let with_infix = self.handle_parse_infix(token);
-            return Ok(with_infix);
+            return self.read_parenthesis();
+        }
+        // Check for Unary Minus, like -:x, but not negative numbers like -5
+        if block.current_char() == '-' {
+            let next_char = block.chars.get(block.position + 1).copied();
+            if next_char.is_some_and(|chr| chr == ':' || chr == '(' || chr.is_alphabetic()) {
+                block.next();
+                let power_precedence = self.language.lookup_infix("^").unwrap().precedence;
+                let operand = self.read_expression(power_precedence)?;
+                return Ok(Token::Command(Command::minus(), vec![operand]));
+            }
         }
         // Main Read
         let identifier = self.read_identifier()?;
         let token: Token;
         if let Some(command) = self.language.lookup(&identifier) {
             // Command
-            let args = self.read_arguments(&command);
+            self.read_names.push(Box::from(identifier.as_str()));
+            let args = self.read_arguments(&command)?;
             token = Token::Command(command, args);
         } else if identifier.starts_with(':') {
             // Variable
             let sanitized = identifier[1..].to_string();
             token = Token::Variable(sanitized);
+        } else if let Some(slot) = Self::template_slot(&identifier) {
+            // Template Slot
+            token = Token::Variable(slot);
         } else if identifier.starts_with('\"') {
             // Word
             let sanitized = identifier[1..].to_string();
@@ -104,8 +325,8 @@ impl Lexer {
             token = Token::Boolean(identifier == "true");
         } else if identifier.starts_with('[') {
             // List
-            let sanitized = identifier[1..identifier.len() - 1].to_string();
-            token = Token::List(sanitized);
+            let sanitized = &identifier[1..identifier.len() - 1];
+            token = Token::List(List::parse(sanitized));
         } else if identifier.ends_with(',') {
             // Object "talkto" Command Shortcut
             let sanitized = identifier[..identifier.len() - 1].to_string();
@@ -116,19 +337,30 @@ impl Lexer {
             let sanitized = identifier[..identifier.len() - 2].to_string();
             let command = Command::ask();
             let mut args = vec![Token::Word(sanitized)];
-            let rest_args = self.read_fixed_arguments(&command.name, 1);
+            let rest_args = self.read_fixed_arguments(&command.name, 1)?;
             args.extend(rest_args);
             token = Token::Command(command, args);
         } else if identifier.is_empty() {
             return Err(eof_error());
         } else {
+            // Not defined yet, but it may be later.
+            self.read_names.push(Box::from(identifier.as_str()));
             return Ok(Token::Undefined(identifier));
         }
 
-        // Look for an infix operator like +, -, * or / which will come
-        // after the first argument but before the second.
-        let with_infix = self.handle_parse_infix(token);
-        Ok(with_infix)
+        Ok(token)
+    }
+
+    fn template_slot(identifier: &str) -> Option<String> {
+        // Slots in templates are ? or ?1, ?2 and so on, where ? is the same as ?1.
+        let number = identifier.strip_prefix('?')?;
+        if number.is_empty() {
+            return Some(String::from("?1"));
+        }
+        if number.chars().all(|chr| chr.is_ascii_digit()) {
+            return Some(format!("?{}", number));
+        }
+        None
     }
 
     fn consume_whitespace(&mut self) {
@@ -170,53 +402,70 @@ impl Lexer {
             block.next();
         }
         if bracket_count != 0 {
-            return Err(Box::from("found unmatched brackets"));
+            return Err(Box::from(LogoError::syntax(String::from(
+                "found unmatched brackets",
+            ))));
         }
         if allow_whitespace {
-            return Err(Box::from("found unmatched pipes"));
+            return Err(Box::from(LogoError::syntax(String::from(
+                "found unmatched pipes",
+            ))));
         }
         Ok(identifier)
     }
 
-    fn read_arguments(&mut self, command: &Command) -> Vec<Token> {
+    fn read_arguments(&mut self, command: &Command) -> Result<Vec<Token>, Box<dyn Error>> {
         match command.params {
             Params::Fixed(count) => self.read_fixed_arguments(&command.name, count),
-            Params::Variadic(count) => self.read_variadic_arguments(count),
-            Params::None => vec![],
+            Params::Variadic(count) => self.read_variadic_arguments(&command.name, count),
+            Params::None => Ok(vec![]),
+        }
+    }
+
+    fn read_argument(&mut self, command_name: &str) -> Result<Option<Token>, Box<dyn Error>> {
+        // Missing inputs are reported when the command runs, but anything
+        // else wrong with an input is an error straight away.
+        let result = if command_name == "to" {
+            self.read_procedure()
+        } else {
+            self.read_token()
+        };
+        match result {
+            Ok(token) => Ok(Some(token)),
+            Err(err) if is_eof(&err) => Ok(None),
+            Err(err) => Err(err),
         }
     }
 
-    fn read_fixed_arguments(&mut self, command_name: &str, count: usize) -> Vec<Token> {
+    fn read_fixed_arguments(
+        &mut self,
+        command_name: &str,
+        count: usize,
+    ) -> Result<Vec<Token>, Box<dyn Error>> {
         let mut args = vec![];
         for _ in 0..count {
-            if command_name == "to" {
-                if let Ok(token) = self.read_procedure() {
-                    args.push(token);
-                }
-            } else {
-                if let Ok(token) = self.read_token() {
-                    args.push(token);
-                }
-            }
+            args.extend(self.read_argument(command_name)?);
         }
-        args
+        Ok(args)
     }
 
-    fn read_variadic_arguments(&mut self, default_count: usize) -> Vec<Token> {
+    fn read_variadic_arguments(
+        &mut self,
+        command_name: &str,
+        default_count: usize,
+    ) -> Result<Vec<Token>, Box<dyn Error>> {
         let mut args = vec![];
         let block = self.current_block();
         if block.in_paren {
-            while let Ok(token) = self.read_token() {
+            while let Some(token) = self.read_argument(command_name)? {
                 args.push(token);
             }
         } else {
             for _ in 0..default_count {
-                if let Ok(token) = self.read_token() {
-                    args.push(token);
-                }
+                args.extend(self.read_argument(command_name)?);
             }
         }
-        args
+        Ok(args)
     }
 
     fn read_parenthesis(&mut self) -> Result<Token, Box<dyn Error>> {
@@ -242,40 +491,31 @@ impl Lexer {
             block.next();
         }
         if paren_count != 0 {
-            return Err(Box::from("found unmatched parenthesis"));
+            return Err(Box::from(LogoError::syntax(String::from(
+                "found unmatched parenthesis",
+            ))));
         }
         block.next();
-        Ok(Token::Command(Command::paren(), vec![Token::List(code)]))
-    }
-
-    fn handle_parse_infix(&mut self, token: Token) -> Token {
-        if let Some(command) = self.peek_infix_operator() {
-            return self.read_infix_operator(token, command);
-        }
-        token
+        Ok(Token::Command(
+            Command::paren(),
+            vec![Token::List(List::parse(&code))],
+        ))
     }
 
-    fn peek_infix_operator(&mut self) -> Option<Command> {
-        let saved_position = self.current_block().position.clone();
+    fn peek_infix_operator(&mut self) -> Option<(InfixOperator, usize)> {
+        // Finds the operator and the position after it, without moving past it.
+        let saved_position = self.current_block().position;
         self.consume_whitespace();
         let block = self.current_block();
         let mut operator = String::new();
         while !block.current_char().is_whitespace() && block.current_char() != '\0' {
-            operator.push(block.current_char().clone());
+            operator.push(block.current_char());
             block.next();
         }
-        if let Some(command) = self.language.lookup_infix(&operator) {
-            Some(command)
-        } else {
-            self.current_block().position = saved_position;
-            None
-        }
-    }
-
-    fn read_infix_operator(&mut self, left_arg: Token, operator: Command) -> Token {
-        let mut args = self.read_fixed_arguments(&operator.name, 1);
-        args.insert(0, left_arg);
-        Token::Command(operator, args)
+        let end_position = block.position;
+        block.position = saved_position;
+        let operator = self.language.lookup_infix(&operator)?;
+        Some((operator, end_position))
     }
 
     fn read_procedure(&mut self) -> Result<Token, Box<dyn Error>> {
@@ -289,45 +529,27 @@ impl Lexer {
             block.next();
         }
 
-        // Read parameter names.
-        let mut params: Vec<String> = vec![];
-        while block.current_char() != '\n' {
-            while block.current_char() != ':'
-                && block.current_char() != '\n'
-                && block.current_char() != '\0'
-            {
-                block.next();
-            }
+        // Read the rest of the title line for the inputs.
+        let mut title = String::new();
+        while block.current_char() != '\n' && block.current_char() != '\0' {
+            title.push(block.current_char());
             block.next();
-            let mut param_name = String::new();
-            while block.current_char() != ' '
-                && block.current_char() != '\n'
-                && block.current_char() != '\0'
-            {
-                param_name.push(block.current_char().clone());
-                block.next();
-            }
-            if !param_name.is_empty() {
-                params.push(param_name);
-            }
-            while block.current_char() == ' '
-                && block.current_char() != '\n'
-                && block.current_char() != '\0'
-            {
-                block.next();
-            }
         }
+        let params = Self::read_procedure_params(&name, &title)?;
 
-        // Read the code block.
+        // Read the code block, keeping every line so that positions in
+        // the body match the lines of the source.
         self.consume_until_newline();
+        let block = self.current_block();
+        let line = block.chars[..block.position]
+            .iter()
+            .filter(|chr| **chr == '\n')
+            .count()
+            + 1;
         let mut code = String::new();
         let mut code_line = String::new();
         let mut is_proc_complete = false;
-        let block = self.current_block();
         while block.current_char() != '\0' {
-            while block.current_char().is_whitespace() && block.current_char() != '\0' {
-                block.next();
-            }
             while block.current_char() != '\n' && block.current_char() != '\0' {
                 code_line.push(block.current_char().clone());
                 block.next();
@@ -338,7 +560,7 @@ impl Lexer {
             block.next();
 
             // Find the "end" keyword to know when to stop.
-            if code_line == "end\n" {
+            if code_line.trim_start() == "end\n" {
                 is_proc_complete = true;
                 break;
             }
@@ -348,22 +570,35 @@ impl Lexer {
             code_line = String::new();
         }
         if !is_proc_complete {
-            return Err(Box::from(format!("procedure {} has no end", name)));
+            return Err(Box::from(LogoError::syntax(format!(
+                "procedure {} has no end",
+                name
+            ))));
+        }
+        Ok(Token::Procedure(name, params, code, line))
+    }
+
+    fn read_procedure_params(name: &str, title: &str) -> Result<ProcedureParams, Box<dyn Error>> {
+        match ProcedureParams::from_list(List::parse(title).items()) {
+            Ok(params) => Ok(params),
+            Err(problem) => Err(Box::from(LogoError::syntax(format!(
+                "procedure {} has {}",
+                name, problem
+            )))),
         }
-        Ok(Token::Procedure(name, params, code))
     }
 }
 
 #[derive(Debug, Clone)]
 struct CodeBlock {
-    text: String,
+    chars: Vec<char>,
     position: usize,
     in_paren: bool,
 }
 
 impl CodeBlock {
     fn current_char(&self) -> char {
-        self.text.chars().nth(self.position).unwrap_or('\0')
+        self.chars.get(self.position).copied().unwrap_or('\0')
     }
 
     fn next(&mut self) {
@@ -1,6 +1,8 @@
 use crate::interpreter::interpreter::Interpreter;
-use crate::interpreter::language::token::Token;
+use crate::interpreter::language::token::{List, Token};
 use std::error::Error;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[derive(Debug, Clone)]
 pub struct Command {
@@ -44,11 +46,118 @@ pub enum Params {
 pub type CommandAction =
     fn(data: &mut Interpreter, command: &str, args: Vec<Token>) -> Result<Token, Box<dyn Error>>;
 
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcedureParams {
+    pub required: Vec<String>,
+    // Inputs that can be left out, with the code for their default values.
+    pub optional: Vec<(String, String)>,
+    // Collects any inputs after the others into a list.
+    pub rest: Option<String>,
+}
+
+impl ProcedureParams {
+    // Inputs are written as :name, then [:name default] for optional
+    // inputs, then [:name] for the rest of the inputs. The colons can
+    // be left out, as they are in the lists from "text".
+    pub fn from_list(items: &[Token]) -> Result<Self, String> {
+        let mut params = ProcedureParams::default();
+        let input_name = |token: Option<&Token>| match token {
+            Some(Token::Word(word)) if !word.is_empty() && word != ":" => {
+                Ok(word.strip_prefix(':').unwrap_or(word).to_string())
+            }
+            _ => Err(String::from("an input that is not a name")),
+        };
+        for item in items {
+            if params.rest.is_some() {
+                return Err(String::from("inputs out of order"));
+            }
+            match item {
+                Token::List(list) if list.len() == 1 => {
+                    params.rest = Some(input_name(list.first())?);
+                }
+                Token::List(list) => {
+                    let default = list.butfirst().to_text();
+                    params.optional.push((input_name(list.first())?, default));
+                }
+                _ if !params.optional.is_empty() => {
+                    return Err(String::from("inputs out of order"));
+                }
+                token => params.required.push(input_name(Some(token))?),
+            }
+        }
+        Ok(params)
+    }
+
+    pub fn to_list(&self) -> List {
+        let mut items = vec![];
+        for param in &self.required {
+            items.push(Token::Word(param.clone()));
+        }
+        for (param, default) in &self.optional {
+            let default = List::parse(default);
+            items.push(Token::List(default.with_first(Token::Word(param.clone()))));
+        }
+        if let Some(param) = &self.rest {
+            items.push(Token::List(List::new(vec![Token::Word(param.clone())])));
+        }
+        List::new(items)
+    }
+
+    // The inputs as written on the title line.
+    pub fn title(&self) -> String {
+        let mut inputs = vec![];
+        for param in &self.required {
+            inputs.push(format!(":{}", param));
+        }
+        for (param, default) in &self.optional {
+            inputs.push(format!("[:{} {}]", param, default));
+        }
+        if let Some(param) = &self.rest {
+            inputs.push(format!("[:{}]", param));
+        }
+        inputs.join(" ")
+    }
+
+    pub fn arity(&self) -> Params {
+        // Procedures that take extra inputs read them inside parentheses.
+        if self.optional.is_empty() && self.rest.is_none() {
+            Params::Fixed(self.required.len())
+        } else {
+            Params::Variadic(self.required.len())
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Procedure {
     pub name: Box<str>,
-    pub params: Vec<String>,
+    pub params: ProcedureParams,
     pub code: String,
+    // The line in the source where the body of the procedure starts.
+    pub line: usize,
+    pub block: BlockCache,
+}
+
+impl Procedure {
+    // The definition as a list of the inputs followed by a list for each line.
+    pub fn text(&self) -> List {
+        let mut items = vec![Token::List(self.params.to_list())];
+        for line in self.code.lines() {
+            if !line.trim().is_empty() {
+                items.push(Token::List(List::parse(line.trim())));
+            }
+        }
+        List::new(items)
+    }
+
+    // The definition as it would be written in the source.
+    pub fn printout(&self) -> String {
+        let title = format!("to {} {}", self.name, self.params.title());
+        let mut lines = vec![title.trim_end().to_string()];
+        lines.extend(self.code.lines().map(String::from));
+        lines.push(String::from("end"));
+        lines.join("\n")
+    }
 }
 
 impl PartialEq for Procedure {
@@ -56,3 +165,65 @@ impl PartialEq for Procedure {
         self.name == other.name
     }
 }
+
+/// A block of code that has been read into tokens ahead of time,
+/// so it can be executed repeatedly without reading the text again.
+#[derive(Debug)]
+pub struct ParsedBlock {
+    pub chars: Vec<char>,
+    pub statements: Vec<Token>,
+    pub start_positions: Vec<usize>,
+    pub end_positions: Vec<usize>,
+    pub in_paren: bool,
+    pub generation: usize,
+    // The names the block was read with, so it is only read again when
+    // one of them is defined or erased.
+    pub names: Vec<Box<str>>,
+    // The last generation the block was found to still be up to date in.
+    pub checked: AtomicUsize,
+}
+
+impl ParsedBlock {
+    // The line and column where a statement starts, counting from 1.
+    pub fn location(&self, index: usize) -> (usize, usize) {
+        let position = self.start_positions.get(index).copied().unwrap_or(0);
+        let mut line = 1;
+        let mut column = 1;
+        for chr in &self.chars[..position] {
+            if *chr == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+}
+
+/// The block that some code was last read into, shared between clones,
+/// so that code that runs again is not read again.
+#[derive(Debug, Clone, Default)]
+pub struct BlockCache(Arc<CachedBlock>);
+
+#[derive(Debug, Default)]
+struct CachedBlock {
+    block: Mutex<Option<Arc<ParsedBlock>>>,
+    rest: OnceLock<BlockCache>,
+}
+
+impl BlockCache {
+    pub fn get(&self) -> Option<Arc<ParsedBlock>> {
+        self.0.block.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, block: Arc<ParsedBlock>) {
+        *self.0.block.lock().unwrap() = Some(block);
+    }
+
+    // The cache for the same code without its first item, like the body
+    // of a template that names its inputs first.
+    pub fn rest(&self) -> BlockCache {
+        self.0.rest.get_or_init(BlockCache::default).clone()
+    }
+}
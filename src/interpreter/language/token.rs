@@ -1,4 +1,6 @@
-use crate::interpreter::language::structure::Command;
+use crate::interpreter::language::structure::{BlockCache, Command, ProcedureParams};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone)]
 pub enum Token {
@@ -6,9 +8,10 @@ pub enum Token {
     Word(String),
     Number(f32),
     Boolean(bool),
-    List(String),
+    List(List),
+    Array(Array),
     Variable(String),
-    Procedure(String, Vec<String>, String),
+    Procedure(String, ProcedureParams, String, usize),
     Undefined(String),
     Void,
 }
@@ -20,7 +23,8 @@ impl Token {
             Self::Word(string) => string.clone(),
             Self::Number(number) => number.to_string(),
             Self::Boolean(boolean) => boolean.to_string(),
-            Self::List(list) => format!("[{}]", list),
+            Self::List(list) => format!("[{}]", list.to_text()),
+            Self::Array(array) => format!("{{{}}}", array.to_text()),
             Self::Variable(variable) => format!(":{}", variable),
             Self::Undefined(undef) => undef.clone(),
             _ => String::new(),
@@ -38,8 +42,9 @@ impl PartialEq for Token {
             (Self::Number(num1), Self::Number(num2)) => num1 == num2,
             (Self::Boolean(bool1), Self::Boolean(bool2)) => bool1 == bool2,
             (Self::List(list1), Self::List(list2)) => list1 == list2,
+            (Self::Array(array1), Self::Array(array2)) => array1 == array2,
             (Self::Variable(var1), Self::Variable(var2)) => var1 == var2,
-            (Self::Procedure(proc1, params1, _), Self::Procedure(proc2, params2, _)) => {
+            (Self::Procedure(proc1, params1, ..), Self::Procedure(proc2, params2, ..)) => {
                 proc1 == proc2 && params1 == params2
             }
             (Self::Undefined(undef1), Self::Undefined(undef2)) => undef1 == undef2,
@@ -50,29 +55,274 @@ impl PartialEq for Token {
 
 impl Eq for Token {}
 
-pub trait TokenVec {
-    fn join_to_list_string(&self) -> String;
+/// An immutable list of tokens. The items are shared between clones,
+/// so taking the rest of a list does not copy it.
+#[derive(Debug, Clone)]
+pub struct List {
+    items: Arc<[Token]>,
+    start: usize,
+    end: usize,
+    source: Option<Arc<str>>,
+    // The code a list from the source was read into when it last ran.
+    block: Option<BlockCache>,
 }
 
-impl TokenVec for Vec<Token> {
-    fn join_to_list_string(&self) -> String {
-        let mut list = String::new();
-        for token in self {
-            match token {
+impl List {
+    pub fn new(items: Vec<Token>) -> Self {
+        let end = items.len();
+        List {
+            items: Arc::from(items),
+            start: 0,
+            end,
+            source: None,
+            block: None,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(vec![])
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut position = 0;
+        let items = Self::parse_items(&chars, &mut position);
+        let mut list = Self::new(items);
+        list.source = Some(Arc::from(text));
+        list.block = Some(BlockCache::default());
+        list
+    }
+
+    fn parse_items(chars: &[char], position: &mut usize) -> Vec<Token> {
+        let mut items = vec![];
+        let mut current_item = String::new();
+        let mut allow_whitespace = false;
+        let mut has_pipes = false;
+        while let Some(chr) = chars.get(*position).copied() {
+            *position += 1;
+            if allow_whitespace {
+                if chr == '|' {
+                    allow_whitespace = false;
+                } else {
+                    current_item.push(chr);
+                }
+            } else if chr == '|' {
+                allow_whitespace = true;
+                has_pipes = true;
+            } else if chr == '[' {
+                Self::push_word(&mut items, &mut current_item, &mut has_pipes);
+                let sublist = Self::parse_items(chars, position);
+                items.push(Token::List(Self::new(sublist)));
+            } else if chr == ']' {
+                break;
+            } else if chr.is_whitespace() {
+                Self::push_word(&mut items, &mut current_item, &mut has_pipes);
+            } else {
+                current_item.push(chr);
+            }
+        }
+        Self::push_word(&mut items, &mut current_item, &mut has_pipes);
+        items
+    }
+
+    fn push_word(items: &mut Vec<Token>, word: &mut String, has_pipes: &mut bool) {
+        // Words written with pipes are always words, even when empty.
+        if *has_pipes {
+            *has_pipes = false;
+            items.push(Token::Word(std::mem::take(word)));
+            return;
+        }
+        if word.is_empty() {
+            return;
+        }
+        let word = std::mem::take(word);
+        let is_numeric =
+            word.starts_with(|chr: char| chr.is_ascii_digit() || chr == '-' || chr == '.');
+        let token = match word.as_str() {
+            "true" => Token::Boolean(true),
+            "false" => Token::Boolean(false),
+            _ if is_numeric => match word.parse::<f32>() {
+                Ok(number) => Token::Number(number),
+                Err(..) => Token::Word(word),
+            },
+            _ => Token::Word(word),
+        };
+        items.push(token);
+    }
+
+    pub fn items(&self) -> &[Token] {
+        &self.items[self.start..self.end]
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Token> {
+        self.items().get(index)
+    }
+
+    pub fn first(&self) -> Option<&Token> {
+        self.items().first()
+    }
+
+    pub fn last(&self) -> Option<&Token> {
+        self.items().last()
+    }
+
+    pub fn butfirst(&self) -> Self {
+        self.slice(1, 0)
+    }
+
+    pub fn butlast(&self) -> Self {
+        self.slice(0, 1)
+    }
+
+    fn slice(&self, skip_start: usize, skip_end: usize) -> Self {
+        let start = (self.start + skip_start).min(self.end);
+        let end = self.end.saturating_sub(skip_end).max(start);
+        let block = match (skip_start, skip_end) {
+            (1, 0) => self.block.as_ref().map(BlockCache::rest),
+            _ => None,
+        };
+        List {
+            items: self.items.clone(),
+            start,
+            end,
+            source: None,
+            block,
+        }
+    }
+
+    pub fn with_first(&self, item: Token) -> Self {
+        let mut items = Vec::with_capacity(self.len() + 1);
+        items.push(item);
+        items.extend_from_slice(self.items());
+        Self::new(items)
+    }
+
+    pub fn with_last(&self, item: Token) -> Self {
+        let mut items = Vec::with_capacity(self.len() + 1);
+        items.extend_from_slice(self.items());
+        items.push(item);
+        Self::new(items)
+    }
+
+    pub fn contains(&self, item: &Token) -> bool {
+        self.items().contains(item)
+    }
+
+    /// The list as code that can be run, using the original text when the
+    /// list was written in the source.
+    pub fn source(&self) -> Cow<'_, str> {
+        match &self.source {
+            Some(source) => Cow::Borrowed(source),
+            None => Cow::Owned(self.to_text()),
+        }
+    }
+
+    pub fn block_cache(&self) -> Option<&BlockCache> {
+        self.block.as_ref()
+    }
+
+    /// The list items as printed text, without the outer brackets.
+    pub fn to_text(&self) -> String {
+        let mut words = vec![];
+        for token in self.items() {
+            let word = match token {
                 Token::Word(word) => {
-                    if word.contains(' ') {
-                        list += &format!("|{}|", word);
+                    if word.is_empty() || word.contains([' ', '[', ']']) {
+                        format!("|{}|", word)
                     } else {
-                        list += &word
+                        word.clone()
                     }
                 }
-                Token::Number(number) => list += &number.to_string(),
-                Token::List(other) => list += &format!("[{}]", other),
-                Token::Boolean(bool) => list += &bool.to_string(),
+                Token::Number(number) => number.to_string(),
+                Token::Boolean(boolean) => boolean.to_string(),
+                Token::List(list) => format!("[{}]", list.to_text()),
+                Token::Array(array) => format!("{{{}}}", array.to_text()),
                 _ => continue,
+            };
+            words.push(word);
+        }
+        words.join(" ")
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.items() == other.items()
+    }
+}
+
+/// A mutable array of tokens. The items are shared between clones, so
+/// setting an item is seen by every copy of the array.
+#[derive(Debug, Clone)]
+pub struct Array {
+    items: Arc<Mutex<Vec<Token>>>,
+    // The index of the first item.
+    origin: i32,
+}
+
+impl Array {
+    pub fn new(items: Vec<Token>, origin: i32) -> Self {
+        Array {
+            items: Arc::new(Mutex::new(items)),
+            origin,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+
+    pub fn get(&self, index: i32) -> Option<Token> {
+        let position = usize::try_from(index - self.origin).ok()?;
+        self.items.lock().unwrap().get(position).cloned()
+    }
+
+    pub fn set(&self, index: i32, value: Token) -> bool {
+        let Ok(position) = usize::try_from(index - self.origin) else {
+            return false;
+        };
+        match self.items.lock().unwrap().get_mut(position) {
+            Some(item) => {
+                *item = value;
+                true
             }
-            list.push(' ');
+            None => false,
         }
-        list.trim().to_string()
+    }
+
+    pub fn items(&self) -> Vec<Token> {
+        self.items.lock().unwrap().clone()
+    }
+
+    pub fn origin(&self) -> i32 {
+        self.origin
+    }
+
+    pub fn contains_array(&self, other: &Array) -> bool {
+        // Checks nested arrays too, so that an array is never put inside itself.
+        self == other
+            || self.items().iter().any(|item| match item {
+                Token::Array(array) => array.contains_array(other),
+                _ => false,
+            })
+    }
+
+    pub fn to_text(&self) -> String {
+        List::new(self.items()).to_text()
+    }
+}
+
+impl PartialEq for Array {
+    // Arrays are only equal to themselves, since they can change.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.items, &other.items)
     }
 }
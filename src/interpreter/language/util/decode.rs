@@ -1,15 +1,17 @@
-use crate::interpreter::language::structure::Procedure;
-use crate::interpreter::language::token::Token;
+use crate::interpreter::language::structure::{BlockCache, Procedure};
+use crate::interpreter::language::token::{Array, List, Token};
+use crate::interpreter::state::object::{Color, Point};
+use crate::interpreter::util::error::LogoError;
 use std::error::Error;
 
 pub fn number(com: &str, args: &Vec<Token>, index: usize) -> Result<f32, Box<dyn Error>> {
     if let Some(Token::Number(num)) = args.get(index) {
         Ok(num.clone())
     } else {
-        Err(Box::from(format!(
+        Err(Box::from(LogoError::wrong_input_type(format!(
             "{} expected a number for input {}",
             com, index
-        )))
+        ))))
     }
 }
 
@@ -17,10 +19,10 @@ pub fn word(com: &str, args: &Vec<Token>, index: usize) -> Result<String, Box<dy
     if let Some(Token::Word(word)) = args.get(index) {
         Ok(word.clone())
     } else {
-        Err(Box::from(format!(
+        Err(Box::from(LogoError::wrong_input_type(format!(
             "{} expected a word for input {}",
             com, index
-        )))
+        ))))
     }
 }
 
@@ -28,36 +30,74 @@ pub fn boolean(com: &str, args: &Vec<Token>, index: usize) -> Result<bool, Box<d
     if let Some(Token::Boolean(boolean)) = args.get(index) {
         Ok(boolean.clone())
     } else {
-        Err(Box::from(format!(
+        Err(Box::from(LogoError::wrong_input_type(format!(
             "{} expected a boolean for input {}",
             com, index
-        )))
+        ))))
     }
 }
 
-pub fn list(com: &str, args: &Vec<Token>, index: usize) -> Result<String, Box<dyn Error>> {
+pub fn list(com: &str, args: &Vec<Token>, index: usize) -> Result<List, Box<dyn Error>> {
     if let Some(Token::List(list)) = args.get(index) {
         Ok(list.clone())
     } else {
-        Err(Box::from(format!(
+        Err(Box::from(LogoError::wrong_input_type(format!(
             "{} expected a list for input {}",
             com, index
-        )))
+        ))))
     }
 }
 
+pub fn array(com: &str, args: &[Token], index: usize) -> Result<Array, Box<dyn Error>> {
+    if let Some(Token::Array(array)) = args.get(index) {
+        Ok(array.clone())
+    } else {
+        Err(Box::from(LogoError::wrong_input_type(format!(
+            "{} expected an array for input {}",
+            com, index
+        ))))
+    }
+}
+
+pub fn color(com: &str, args: &[Token], index: usize) -> Result<Color, Box<dyn Error>> {
+    if let Some(color) = args.get(index).and_then(Color::from_token) {
+        Ok(color)
+    } else {
+        Err(Box::from(LogoError::wrong_input_type(format!(
+            "{} expected a color for input {}",
+            com, index
+        ))))
+    }
+}
+
+pub fn point(com: &str, args: &[Token], index: usize) -> Result<Point, Box<dyn Error>> {
+    if let Some(Token::List(list)) = args.get(index) {
+        if let (2, Some(Token::Number(x)), Some(Token::Number(y))) =
+            (list.len(), list.get(0), list.get(1))
+        {
+            return Ok(Point::new(*x, *y));
+        }
+    }
+    Err(Box::from(LogoError::wrong_input_type(format!(
+        "{} expected a point [x y] for input {}",
+        com, index
+    ))))
+}
+
 pub fn procedure(com: &str, args: &Vec<Token>, index: usize) -> Result<Procedure, Box<dyn Error>> {
-    if let Some(Token::Procedure(name, params, code)) = args.get(index) {
+    if let Some(Token::Procedure(name, params, code, line)) = args.get(index) {
         Ok(Procedure {
             name: Box::from(name.as_str()),
             params: params.clone(),
             code: code.clone(),
+            line: *line,
+            block: BlockCache::default(),
         })
     } else {
-        Err(Box::from(format!(
+        Err(Box::from(LogoError::wrong_input_type(format!(
             "{} expected a code block for input {}",
             com, index
-        )))
+        ))))
     }
 }
 
@@ -65,6 +105,9 @@ pub fn token(com: &str, args: &Vec<Token>, index: usize) -> Result<Token, Box<dy
     if let Some(token) = args.get(index) {
         Ok(token.clone())
     } else {
-        Err(Box::from(format!("{} expected an input at {}", com, index)))
+        Err(Box::from(LogoError::wrong_input_type(format!(
+            "{} expected an input at {}",
+            com, index
+        ))))
     }
 }
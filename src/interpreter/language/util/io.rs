@@ -1,3 +1,4 @@
+use crate::interpreter::language::token::{List, Token};
 use std::error::Error;
 use std::fs::{self, DirEntry};
 
@@ -44,8 +45,8 @@ impl KeyCode for u8 {
 pub fn query_files(
     base_path: &String,
     query: fn(&DirEntry) -> bool,
-) -> Result<String, Box<dyn Error>> {
-    let mut files: Vec<String> = vec![];
+) -> Result<List, Box<dyn Error>> {
+    let mut files: Vec<Token> = vec![];
     let entries = fs::read_dir(base_path)?;
     for entry in entries {
         let Ok(entry) = entry else { continue };
@@ -53,8 +54,8 @@ pub fn query_files(
             let Ok(file_name) = entry.file_name().into_string() else {
                 continue;
             };
-            files.push(file_name);
+            files.push(Token::Word(file_name));
         }
     }
-    Ok(files.join(" "))
+    Ok(List::new(files))
 }
@@ -1,13 +1,26 @@
 use crate::interpreter::language::token::Token;
-use crate::interpreter::state::object::{Line, Object, Point, Size, Text, Turtle};
-use std::collections::HashMap;
+use crate::interpreter::state::curve::Curve;
+use crate::interpreter::state::object::{Color, Drawing, Fill, Line, Object, Picture, Point};
+use crate::interpreter::state::object::{Size, Span, Text, Turtle};
+use crate::interpreter::state::pen::{Pen, PenMode, StrokePiece};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 
+const PIXEL_EDGE: f32 = 0.001;
+
 #[derive(Debug)]
 pub struct CanvasState {
     size: Size,
-    pixels: Vec<u8>,
-    bg_color: u8,
+    // Red, green, blue and alpha, where pixels that were never drawn on
+    // are fully transparent.
+    pixels: Vec<[u8; 4]>,
+    // The color numbers that were drawn with, so that "colorunder" can
+    // give back a number rather than a list.
+    color_numbers: HashMap<[u8; 4], f32>,
+    drawings: Vec<Drawing>,
+    bg_color: Color,
+    bg_picture: Option<String>,
+    pictures: Vec<Picture>,
     objects: HashMap<Box<str>, Object>,
     current_object_name: Box<str>,
 }
@@ -20,8 +33,12 @@ impl CanvasState {
         let pixel_count = (Self::DEFAULT_WIDTH * Self::DEFAULT_HEIGHT) as usize;
         CanvasState {
             size: Size::new(Self::DEFAULT_WIDTH, Self::DEFAULT_HEIGHT),
-            pixels: vec![0; pixel_count],
-            bg_color: 255,
+            pixels: vec![[0; 4]; pixel_count],
+            color_numbers: HashMap::new(),
+            drawings: vec![],
+            bg_color: Color::WHITE,
+            bg_picture: None,
+            pictures: vec![],
             objects: HashMap::new(),
             current_object_name: Box::from(""),
         }
@@ -49,6 +66,10 @@ impl CanvasState {
         }
     }
 
+    pub fn current_object_name(&self) -> &str {
+        &self.current_object_name
+    }
+
     pub fn set_current_object(&mut self, name: &str) -> bool {
         if self.objects.get(name).is_some() {
             self.current_object_name = Box::from(name);
@@ -66,6 +87,29 @@ impl CanvasState {
         }
     }
 
+    pub fn get_object_mut(&mut self, name: &str) -> Result<&mut Object, Box<dyn Error>> {
+        if let Some(object) = self.objects.get_mut(name) {
+            Ok(object)
+        } else {
+            Err(Box::from(format!("object {} does not exist", name)))
+        }
+    }
+
+    pub fn add_object(&mut self, object: Object) {
+        let name: Box<str> = Box::from(object.name());
+        self.objects.insert(name.clone(), object);
+        if self.objects.len() == 1 {
+            self.current_object_name = name;
+        }
+    }
+
+    pub fn turtles(&self) -> impl Iterator<Item = &Turtle> {
+        self.objects.values().filter_map(|object| match object {
+            Object::Turtle(turtle) => Some(turtle),
+            Object::Text(..) => None,
+        })
+    }
+
     pub fn create_turtle(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
         if self.objects.get(name).is_some() {
             return Err(Box::from(format!("object {} already exists", name)));
@@ -115,88 +159,513 @@ impl CanvasState {
     pub fn set_size(&mut self, width: f32, height: f32) {
         self.size.w = width;
         self.size.h = height;
-        self.pixels = vec![self.bg_color; (width * height) as usize];
+        self.pixels = vec![[0; 4]; (width * height) as usize];
     }
 
-    pub fn get_bg_color(&self) -> u8 {
+    pub fn get_bg_color(&self) -> Color {
         self.bg_color
     }
 
-    pub fn set_bg_color(&mut self, color: u8) {
+    pub fn set_bg_color(&mut self, color: Color) {
         self.bg_color = color;
     }
 
-    pub fn color_at_point(&self, point: &Point) -> f32 {
-        let x = (point.x + self.size.w / 2.0) as i32;
-        let y = (point.y + self.size.h / 2.0) as i32;
-        let index = (y * (self.size.w as i32) + x) as usize;
-        let color = self.pixels.get(index).unwrap_or(&self.bg_color);
-        if *color == 0 {
-            return self.bg_color as f32;
+    pub fn get_bg_picture(&self) -> Option<&String> {
+        self.bg_picture.as_ref()
+    }
+
+    pub fn set_bg_picture(&mut self, path: String) {
+        self.bg_picture = Some(path);
+    }
+
+    pub fn get_pictures(&self) -> &Vec<Picture> {
+        &self.pictures
+    }
+
+    pub fn add_picture(&mut self, picture: Picture) {
+        self.pictures.push(picture);
+    }
+
+    pub fn get_drawings(&self) -> &Vec<Drawing> {
+        &self.drawings
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.objects.values()
+    }
+
+    pub fn has_line_at_point(&self, point: &Point) -> bool {
+        self.pixel_index(point)
+            .is_some_and(|index| self.pixels[index][3] != 0)
+    }
+
+    pub fn color_at_point(&self, point: &Point) -> Color {
+        let rgba = match self.pixel_index(point) {
+            Some(index) => self.pixels[index],
+            None => [0; 4],
+        };
+        if rgba[3] == 0 {
+            return self.bg_color;
+        }
+        match self.color_numbers.get(&rgba) {
+            Some(number) => Color::Number(*number),
+            None => Color::Rgba(rgba),
         }
-        color.clone() as f32
     }
 
-    pub fn add_line(&mut self, line: Line) {
-        let color = line.color as u8;
-        let stroke = line.stroke_width as i32;
-        let start_x = (line.start.x + self.size.w / 2.0) as i32;
-        let end_x = (line.end.x + self.size.w / 2.0) as i32;
-        let start_y = (line.start.y + self.size.h / 2.0) as i32;
-        let end_y = (line.end.y + self.size.h / 2.0) as i32;
-        let (x1, x2) = if start_x < end_x {
-            (start_x, end_x)
-        } else {
-            (end_x + 1, start_x + 1)
+    fn pixel_index(&self, point: &Point) -> Option<usize> {
+        // Whole number positions are the centers of pixels.
+        let point = self.to_pixel_space(point);
+        let (x, y) = (point.x.round() as i32, point.y.round() as i32);
+        let (width, height) = (self.size.w as i32, self.size.h as i32);
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        Some((y * width + x) as usize)
+    }
+
+    fn to_pixel_space(&self, point: &Point) -> Point {
+        Point::new(point.x + self.size.w / 2.0, point.y + self.size.h / 2.0)
+    }
+
+    fn pixel_color(&mut self, color: Color) -> [u8; 4] {
+        let rgba = color.to_rgba();
+        if let Color::Number(number) = color {
+            self.color_numbers.insert(rgba, number);
+        }
+        rgba
+    }
+
+    fn paint(&mut self, index: usize, rgba: [u8; 4]) {
+        // Colors that are see-through are mixed with what is underneath.
+        let [r, g, b, a] = rgba;
+        if a == 255 {
+            self.pixels[index] = rgba;
+            return;
+        }
+        let under = self.pixels[index];
+        let alpha = a as f32 / 255.0;
+        let under_alpha = under[3] as f32 / 255.0 * (1.0 - alpha);
+        let total = alpha + under_alpha;
+        if total == 0.0 {
+            return;
+        }
+        let mix = |top: u8, bottom: u8| {
+            ((top as f32 * alpha + bottom as f32 * under_alpha) / total).round() as u8
         };
-        let (y1, y2) = if start_y < end_y {
-            (start_y, end_y)
-        } else {
-            (end_y + 1, start_y + 1)
+        self.pixels[index] = [
+            mix(r, under[0]),
+            mix(g, under[1]),
+            mix(b, under[2]),
+            (total * 255.0).round() as u8,
+        ];
+    }
+
+    fn apply_pen(&mut self, index: usize, rgba: [u8; 4], mode: PenMode) {
+        match mode {
+            PenMode::Paint => self.paint(index, rgba),
+            PenMode::Erase => self.pixels[index] = [0; 4],
+            PenMode::Reverse => {
+                // Swaps the background and the pen color, and mixes up the
+                // colors of anything else in the same way.
+                let bg = self.bg_color.to_rgba();
+                let under = match self.pixels[index] {
+                    [_, _, _, 0] => bg,
+                    pixel => pixel,
+                };
+                let mut reversed = [255; 4];
+                for channel in 0..3 {
+                    reversed[channel] = under[channel] ^ rgba[channel] ^ bg[channel];
+                }
+                if reversed[..3] == bg[..3] {
+                    reversed = [0; 4];
+                }
+                self.pixels[index] = reversed;
+            }
+        }
+    }
+
+    pub fn add_line(&mut self, line: Line) {
+        let count = self.drawings.len();
+        let mut pixels = self.line_pixels(&line, count);
+        if line.pen.mode == PenMode::Reverse {
+            // The corner overlaps the stroke it joins, and reversing those
+            // pixels again would put them back, so they are left alone.
+            if let Some(Drawing::Line(last)) = self.drawings.last() {
+                if last.continues_to(&line) {
+                    let corner = self.to_pixel_space(&line.start);
+                    let reach = line.stroke_width / 2.0 + 1.0;
+                    let width = self.size.w as usize;
+                    let last_pixels: HashSet<usize> =
+                        self.line_pixels(last, count - 1).into_iter().collect();
+                    pixels.retain(|index| {
+                        let x = (index % width) as f32 - corner.x;
+                        let y = (index / width) as f32 - corner.y;
+                        !last_pixels.contains(index) || x.hypot(y) > reach
+                    });
+                }
+            }
+        }
+        let color = self.pixel_color(line.color);
+        for index in pixels {
+            self.apply_pen(index, color, line.pen.mode);
+        }
+        self.drawings.push(Drawing::Line(line));
+    }
+
+    fn line_pixels(&self, line: &Line, position: usize) -> Vec<usize> {
+        // A line that carries on from the one before it has its corner
+        // filled in.
+        let before = position
+            .checked_sub(1)
+            .and_then(|index| self.drawings.get(index));
+        let joined_from = match before {
+            Some(Drawing::Line(last)) if last.continues_to(line) => Some(&last.start),
+            _ => None,
         };
-        let x_diff = x2 - x1;
-        let y_diff = y2 - y1;
-        if x_diff == 0 {
-            for step in 0..y_diff {
-                let y = y1 + step;
-                let span = stroke - 1;
-                for i in -span..=span {
-                    let index = (y * (self.size.w as i32) + x1 + i) as usize;
-                    if let Some(pixel) = self.pixels.get_mut(index) {
-                        *pixel = color;
+        let points = [line.start.clone(), line.end.clone()];
+        self.stroke_pixels(
+            &points,
+            joined_from,
+            line.stroke_width,
+            &line.pen,
+            line.dash_offset,
+        )
+    }
+
+    pub fn add_curve(&mut self, curve: Curve) {
+        // The start of a closed curve is a corner like any other.
+        let count = curve.points.len();
+        let joined_from = (curve.is_closed && count > 2).then(|| &curve.points[count - 2]);
+        let pixels = self.stroke_pixels(
+            &curve.points,
+            joined_from,
+            curve.stroke_width,
+            &curve.pen,
+            curve.dash_offset,
+        );
+        let color = self.pixel_color(curve.color);
+        for index in pixels {
+            self.apply_pen(index, color, curve.pen.mode);
+        }
+        self.drawings.push(Drawing::Curve(curve));
+    }
+
+    pub fn add_fill(&mut self, fill: Fill) {
+        let color = self.pixel_color(fill.color);
+        let (width, height) = (self.size.w as i32, self.size.h as i32);
+        for span in &fill.spans {
+            let left = self.to_pixel_space(&Point::new(span.left, span.y));
+            let right = self.to_pixel_space(&Point::new(span.right, span.y));
+            let y = left.y.round() as i32;
+            if y < 0 || y >= height {
+                continue;
+            }
+            let left = (left.x.round() as i32).max(0);
+            let right = (right.x.round() as i32).min(width - 1);
+            for x in left..=right {
+                self.paint((y * width + x) as usize, color);
+            }
+        }
+        self.drawings.push(Drawing::Fill(fill));
+    }
+
+    pub fn fill_region(&self, point: &Point, color: Color) -> Option<Fill> {
+        // Finds the pixels of the same color joined to the one at the point.
+        let start = self.pixel_index(point)?;
+        let target = self.pixels[start];
+        if target == color.to_rgba() {
+            return None;
+        }
+        let (width, height) = (self.size.w as usize, self.size.h as usize);
+        let mut is_filled = vec![false; self.pixels.len()];
+        let mut seeds = vec![start];
+        let mut spans = vec![];
+        while let Some(index) = seeds.pop() {
+            if is_filled[index] {
+                continue;
+            }
+            let (y, x) = (index / width, index % width);
+            let row = y * width;
+            let is_open = |x: usize| !is_filled[row + x] && self.pixels[row + x] == target;
+            let mut left = x;
+            while left > 0 && is_open(left - 1) {
+                left -= 1;
+            }
+            let mut right = x;
+            while right + 1 < width && is_open(right + 1) {
+                right += 1;
+            }
+            is_filled[row + left..=row + right].fill(true);
+            spans.push(self.to_span(y, left, right));
+
+            // Carry on from each run of open pixels above and below.
+            let next_rows = [y.checked_sub(1), (y + 1 < height).then_some(y + 1)];
+            for next_y in next_rows.into_iter().flatten() {
+                let mut in_run = false;
+                for x in left..=right {
+                    let index = next_y * width + x;
+                    let is_open = !is_filled[index] && self.pixels[index] == target;
+                    if is_open && !in_run {
+                        seeds.push(index);
                     }
+                    in_run = is_open;
                 }
             }
-        } else if y_diff == 0 {
-            for step in 0..x_diff {
-                let x = x1 + step;
-                let span = stroke - 1;
-                for i in -span..=span {
-                    let index = ((y1 + i) * (self.size.w as i32) + x) as usize;
-                    if let Some(pixel) = self.pixels.get_mut(index) {
-                        *pixel = color;
+        }
+        Some(Fill { color, spans })
+    }
+
+    pub fn fill_polygon(&self, points: &[Point], color: Color) -> Fill {
+        // Fills the pixels whose centers are inside the shape, where edges
+        // that cross an odd number of times are inside.
+        let points: Vec<Point> = points
+            .iter()
+            .map(|point| self.to_pixel_space(point))
+            .collect();
+        let (width, height) = (self.size.w as i32, self.size.h as i32);
+        let mut spans = vec![];
+        let lowest = points.iter().map(|point| point.y).fold(f32::MAX, f32::min);
+        let highest = points.iter().map(|point| point.y).fold(f32::MIN, f32::max);
+        let bottom = (lowest.floor() as i32).max(0);
+        let top = (highest.ceil() as i32).min(height - 1);
+        for y in bottom..=top {
+            let row = y as f32;
+            let mut crossings = vec![];
+            for (index, start) in points.iter().enumerate() {
+                let end = &points[(index + 1) % points.len()];
+                if (start.y <= row) != (end.y <= row) {
+                    let t = (row - start.y) / (end.y - start.y);
+                    crossings.push(start.x + t * (end.x - start.x));
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            for pair in crossings.chunks(2) {
+                if let [from, to] = pair {
+                    let left = (from.ceil() as i32).max(0);
+                    let right = (to.ceil() as i32 - 1).min(width - 1);
+                    if left <= right {
+                        spans.push(self.to_span(y as usize, left as usize, right as usize));
                     }
                 }
             }
-        } else {
-            let slope = y_diff / x_diff;
-            for step in 0..x_diff {
-                let x = x1 + step;
-                let y = y1 + slope * step;
-                let span = stroke - 1;
-                for i in -span..=span {
-                    for j in -span..=span {
-                        let index = ((y + j) * (self.size.w as i32) + x + i) as usize;
-                        if let Some(pixel) = self.pixels.get_mut(index) {
-                            *pixel = color;
-                        }
+        }
+        Fill { color, spans }
+    }
+
+    fn to_span(&self, y: usize, left: usize, right: usize) -> Span {
+        Span {
+            y: y as f32 - self.size.h / 2.0,
+            left: left as f32 - self.size.w / 2.0,
+            right: right as f32 - self.size.w / 2.0,
+        }
+    }
+
+    fn stroke_pixels(
+        &self,
+        points: &[Point],
+        joined_from: Option<&Point>,
+        stroke_width: f32,
+        pen: &Pen,
+        dash_offset: f32,
+    ) -> Vec<usize> {
+        // Every pixel that the pen passes over is painted, so that any point
+        // along the line is on a painted pixel, and a pen one pixel wide
+        // needs no width of its own.
+        let radius = (stroke_width.max(1.0) - 1.0) / 2.0;
+        let mut pixels = vec![];
+        let mut dash_offset = dash_offset;
+        for (index, pair) in points.windows(2).enumerate() {
+            let from = match index {
+                0 => joined_from,
+                _ => Some(&points[index - 1]),
+            };
+            let pieces = pen.stroke_pieces(&pair[0], &pair[1], dash_offset, from, radius);
+            for piece in pieces {
+                match piece {
+                    StrokePiece::Round(start, end, radius) => {
+                        self.round_pixels(&start, &end, radius, &mut pixels)
                     }
+                    StrokePiece::Polygon(points) => self.polygon_pixels(&points, &mut pixels),
+                }
+            }
+            dash_offset += pair[0].distance_to(&pair[1]);
+        }
+        // Pieces overlap, but each pixel is only drawn on once.
+        pixels.sort_unstable();
+        pixels.dedup();
+        pixels
+    }
+
+    fn round_pixels(&self, start: &Point, end: &Point, radius: f32, pixels: &mut Vec<usize>) {
+        let start = self.to_pixel_space(start);
+        let end = self.to_pixel_space(end);
+        let reach = radius + 0.5;
+        let (width, height) = (self.size.w as i32, self.size.h as i32);
+        let bottom = ((start.y.min(end.y) - reach).floor() as i32).max(0);
+        let top = ((start.y.max(end.y) + reach).ceil() as i32).min(height - 1);
+        for y in bottom..=top {
+            // Only look near the part of the line that passes this row.
+            let Some((left, right)) = span_near_row(&start, &end, y as f32, reach) else {
+                continue;
+            };
+            let left = ((left - reach).floor() as i32).max(0);
+            let right = ((right + reach).ceil() as i32).min(width - 1);
+            for x in left..=right {
+                if distance_to_pixel(x, y, &start, &end) <= radius {
+                    pixels.push((y * width + x) as usize);
+                }
+            }
+        }
+    }
+
+    fn polygon_pixels(&self, points: &[Point], pixels: &mut Vec<usize>) {
+        let points: Vec<Point> = points
+            .iter()
+            .map(|point| self.to_pixel_space(point))
+            .collect();
+        let (width, height) = (self.size.w as i32, self.size.h as i32);
+        let xs = points.iter().map(|point| point.x);
+        let ys = points.iter().map(|point| point.y);
+        let left = ((xs.clone().fold(f32::MAX, f32::min) - 0.5).floor() as i32).max(0);
+        let right = ((xs.fold(f32::MIN, f32::max) + 0.5).ceil() as i32).min(width - 1);
+        let bottom = ((ys.clone().fold(f32::MAX, f32::min) - 0.5).floor() as i32).max(0);
+        let top = ((ys.fold(f32::MIN, f32::max) + 0.5).ceil() as i32).min(height - 1);
+        for y in bottom..=top {
+            for x in left..=right {
+                if polygon_touches_pixel(x, y, &points) {
+                    pixels.push((y * width + x) as usize);
                 }
             }
         }
     }
 
     pub fn clear(&mut self) {
-        self.pixels = vec![0; (self.size.w * self.size.h) as usize];
+        self.drawings.clear();
+        self.pictures.clear();
+        self.pixels = vec![[0; 4]; (self.size.w * self.size.h) as usize];
     }
 }
+
+fn span_near_row(start: &Point, end: &Point, row: f32, radius: f32) -> Option<(f32, f32)> {
+    // The range of x along the part of the line within radius of the row.
+    if start.y == end.y {
+        let (left, right) = (start.x.min(end.x), start.x.max(end.x));
+        return ((start.y - row).abs() <= radius).then_some((left, right));
+    }
+    let low = (row - radius - start.y) / (end.y - start.y);
+    let high = (row + radius - start.y) / (end.y - start.y);
+    let (t1, t2) = (low.min(high).max(0.0), low.max(high).min(1.0));
+    if t1 > t2 {
+        return None;
+    }
+    let x1 = start.x + t1 * (end.x - start.x);
+    let x2 = start.x + t2 * (end.x - start.x);
+    Some((x1.min(x2), x1.max(x2)))
+}
+
+fn distance_to_pixel(x: i32, y: i32, start: &Point, end: &Point) -> f32 {
+    // Pixels hold the points that round to them, so their far edges are left
+    // out, and a line along the edge between two pixels only covers one.
+    let min = Point::new(x as f32 - 0.5, y as f32 - 0.5);
+    let max = Point::new(x as f32 + 0.5 - PIXEL_EDGE, y as f32 + 0.5 - PIXEL_EDGE);
+    if crosses_box(start, end, &min, &max) {
+        return 0.0;
+    }
+
+    // Otherwise the closest points are at a corner of the pixel or an end
+    // of the line.
+    let corners = [
+        Point::new(min.x, min.y),
+        Point::new(max.x, min.y),
+        Point::new(min.x, max.y),
+        Point::new(max.x, max.y),
+    ];
+    let to_corner = corners
+        .iter()
+        .map(|corner| distance_to_segment(corner, start, end))
+        .fold(f32::MAX, f32::min);
+    let to_start = distance_to_box(start, &min, &max);
+    let to_end = distance_to_box(end, &min, &max);
+    to_corner.min(to_start).min(to_end)
+}
+
+fn polygon_touches_pixel(x: i32, y: i32, points: &[Point]) -> bool {
+    // A convex shape misses the pixel if there is a line between them, which
+    // runs along one of the sides of either the shape or the pixel.
+    let min = Point::new(x as f32 - 0.5, y as f32 - 0.5);
+    let max = Point::new(x as f32 + 0.5 - PIXEL_EDGE, y as f32 + 0.5 - PIXEL_EDGE);
+    let corners = [
+        Point::new(min.x, min.y),
+        Point::new(max.x, min.y),
+        Point::new(min.x, max.y),
+        Point::new(max.x, max.y),
+    ];
+    let mut directions = vec![(1.0, 0.0), (0.0, 1.0)];
+    for (index, start) in points.iter().enumerate() {
+        let end = &points[(index + 1) % points.len()];
+        directions.push((start.y - end.y, end.x - start.x));
+    }
+    let range = |points: &[Point], (dx, dy): (f32, f32)| {
+        points
+            .iter()
+            .map(|point| point.x * dx + point.y * dy)
+            .fold((f32::MAX, f32::MIN), |(low, high), value| {
+                (low.min(value), high.max(value))
+            })
+    };
+    directions
+        .into_iter()
+        .filter(|(dx, dy)| *dx != 0.0 || *dy != 0.0)
+        .all(|direction| {
+            let (low, high) = range(points, direction);
+            let (pixel_low, pixel_high) = range(&corners, direction);
+            low <= pixel_high && pixel_low <= high
+        })
+}
+
+fn crosses_box(start: &Point, end: &Point, min: &Point, max: &Point) -> bool {
+    // Clips the line to the box, and checks whether any of it is left.
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let mut t1: f32 = 0.0;
+    let mut t2: f32 = 1.0;
+    let edges = [
+        (-dx, start.x - min.x),
+        (dx, max.x - start.x),
+        (-dy, start.y - min.y),
+        (dy, max.y - start.y),
+    ];
+    for (direction, distance) in edges {
+        if direction == 0.0 {
+            if distance < 0.0 {
+                return false;
+            }
+        } else {
+            let t = distance / direction;
+            if direction < 0.0 {
+                t1 = t1.max(t);
+            } else {
+                t2 = t2.min(t);
+            }
+        }
+    }
+    t1 <= t2
+}
+
+fn distance_to_box(point: &Point, min: &Point, max: &Point) -> f32 {
+    let dx = (min.x - point.x).max(point.x - max.x).max(0.0);
+    let dy = (min.y - point.y).max(point.y - max.y).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+fn distance_to_segment(point: &Point, start: &Point, end: &Point) -> f32 {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    let nearest = Point::new(start.x + t * dx, start.y + t * dy);
+    ((point.x - nearest.x).powi(2) + (point.y - nearest.y).powi(2)).sqrt()
+}
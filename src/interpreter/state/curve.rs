@@ -0,0 +1,88 @@
+use crate::interpreter::state::object::{Color, Point};
+use crate::interpreter::state::pen::Pen;
+
+// How far the straight pieces of a curve can stray from the true curve.
+const TOLERANCE: f32 = 0.1;
+// The most pieces a curve is split into, however large it is.
+const MAX_PIECES: f32 = 10000.0;
+
+// A smooth line, drawn as short straight pieces.
+#[derive(Debug, Clone)]
+pub struct Curve {
+    pub points: Vec<Point>,
+    // Whether the end joins back up with the start.
+    pub is_closed: bool,
+    pub color: Color,
+    pub stroke_width: f32,
+    pub pen: Pen,
+    // How far along the pen's pattern the curve starts.
+    pub dash_offset: f32,
+}
+
+impl Curve {
+    pub fn length(&self) -> f32 {
+        self.points
+            .windows(2)
+            .map(|pair| pair[0].distance_to(&pair[1]))
+            .sum()
+    }
+}
+
+// Points around an ellipse with the given center, from one angle to another.
+// Angles are clockwise from the heading, and the first radius is across the
+// heading while the second is along it.
+pub fn arc_points(
+    center: &Point,
+    radii: (f32, f32),
+    heading: f32,
+    start: f32,
+    sweep: f32,
+) -> Vec<Point> {
+    let (across, along) = (radii.0.abs(), radii.1.abs());
+    let radius = across.max(along);
+    let step = if radius > TOLERANCE {
+        (2.0 * (1.0 - TOLERANCE / radius).acos()).to_degrees()
+    } else {
+        90.0
+    };
+    let count = (sweep.abs() / step.max(0.01)).ceil().clamp(1.0, MAX_PIECES) as usize;
+    let (sin_h, cos_h) = heading.to_radians().sin_cos();
+    (0..=count)
+        .map(|index| {
+            let angle = (start + sweep * index as f32 / count as f32).to_radians();
+            let (x, y) = (across * angle.sin(), along * angle.cos());
+            Point::new(
+                center.x + x * cos_h + y * sin_h,
+                center.y - x * sin_h + y * cos_h,
+            )
+        })
+        .collect()
+}
+
+// Points along a cubic Bezier curve, which starts and ends at the first and
+// last points and is pulled towards the two in between.
+pub fn bezier_points(controls: [&Point; 4]) -> Vec<Point> {
+    // How far the curve bends sets how many pieces it needs.
+    let [p0, p1, p2, p3] = controls;
+    let bend = |a: &Point, b: &Point, c: &Point| {
+        ((a.x - 2.0 * b.x + c.x).powi(2) + (a.y - 2.0 * b.y + c.y).powi(2)).sqrt()
+    };
+    let bend = bend(p0, p1, p2).max(bend(p1, p2, p3));
+    let count = (0.75 * bend / TOLERANCE)
+        .sqrt()
+        .ceil()
+        .clamp(1.0, MAX_PIECES) as usize;
+    (0..=count)
+        .map(|index| {
+            let t = index as f32 / count as f32;
+            let u = 1.0 - t;
+            let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+            let mut point = Point::zero();
+            for (control, weight) in controls.iter().zip(weights) {
+                point.x += control.x * weight;
+                point.y += control.y * weight;
+            }
+            point
+        })
+        .collect()
+}
@@ -1,6 +1,7 @@
 use crate::interpreter::language::structure::Procedure;
 use crate::interpreter::language::token::Token;
 use crate::interpreter::state::object::TurtleShape;
+use crate::interpreter::util::error::LogoError;
 use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug)]
@@ -8,8 +9,10 @@ pub struct DataStore {
     scopes: VecDeque<Scope>,
     procedures: HashMap<Box<str>, Procedure>,
     shapes: HashMap<Box<str>, TurtleShape>,
+    // Properties for each name, kept in the order they were first set.
+    property_lists: HashMap<Box<str>, Vec<(Box<str>, Token)>>,
     base_file_directory: String,
-    last_error_message: String,
+    last_error: Option<LogoError>,
 }
 
 impl DataStore {
@@ -27,8 +30,9 @@ impl DataStore {
             ]
             .into_iter()
             .collect(),
+            property_lists: HashMap::new(),
             base_file_directory: String::new(),
-            last_error_message: String::new(),
+            last_error: None,
         }
     }
 
@@ -50,6 +54,37 @@ impl DataStore {
         }
     }
 
+    pub fn swap_local_scopes(&mut self, scopes: VecDeque<Scope>) -> VecDeque<Scope> {
+        // Replace every scope except the global scope, handing back the old ones.
+        let global_scope = self.scopes.pop_back().unwrap();
+        let old_scopes = std::mem::replace(&mut self.scopes, scopes);
+        self.scopes.push_back(global_scope);
+        old_scopes
+    }
+
+    pub fn copy_local_scopes(&self) -> Scope {
+        // Gathers every local variable into one scope, with the innermost
+        // value of each name winning.
+        let mut variables = HashMap::new();
+        for scope in self.scopes.iter().rev().skip(1) {
+            variables.extend(scope.variables.clone());
+        }
+        Scope { variables }
+    }
+
+    pub fn load_workspace(&mut self, mut other: DataStore) {
+        // Takes the globals, procedures, shapes and property lists of another
+        // store, leaving the local variables of whatever is running.
+        if let (Some(global_scope), Some(loaded_scope)) =
+            (self.scopes.back_mut(), other.scopes.pop_back())
+        {
+            *global_scope = loaded_scope;
+        }
+        self.procedures = other.procedures;
+        self.shapes = other.shapes;
+        self.property_lists = other.property_lists;
+    }
+
     pub fn reached_max_scope_depth(&self) -> bool {
         // Limit the number of nested scopes to prevent stack overflow.
         self.scopes.len() >= 100
@@ -102,7 +137,30 @@ impl DataStore {
         self.procedures.get(name)
     }
 
-    pub fn get_all_procedures(&mut self) -> Vec<&Procedure> {
+    pub fn get_global_variables(&self) -> Vec<(&str, &Token)> {
+        let global_scope = self.scopes.back().unwrap();
+        global_scope
+            .variables
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value))
+            .collect()
+    }
+
+    pub fn get_scopes(&self) -> Vec<Vec<(&str, &Token)>> {
+        // Innermost first, ending with the global scope.
+        self.scopes
+            .iter()
+            .map(|scope| {
+                scope
+                    .variables
+                    .iter()
+                    .map(|(name, value)| (name.as_ref(), value))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn get_all_procedures(&self) -> Vec<&Procedure> {
         self.procedures.iter().map(|(_, value)| value).collect()
     }
 
@@ -110,14 +168,72 @@ impl DataStore {
         self.procedures.insert(procedure.name.clone(), procedure);
     }
 
+    pub fn remove_procedure(&mut self, name: &str) {
+        self.procedures.remove(name);
+    }
+
     pub fn get_shape(&self, name: &str) -> Option<&TurtleShape> {
         self.shapes.get(name)
     }
 
+    pub fn get_all_shapes(&self) -> Vec<(&str, &TurtleShape)> {
+        self.shapes
+            .iter()
+            .map(|(name, shape)| (name.as_ref(), shape))
+            .collect()
+    }
+
     pub fn set_shape(&mut self, name: &str, shape: TurtleShape) {
         self.shapes.insert(Box::from(name), shape);
     }
 
+    pub fn get_property(&self, name: &str, property: &str) -> Option<&Token> {
+        let properties = self.property_lists.get(name)?;
+        properties
+            .iter()
+            .find(|(key, _)| &**key == property)
+            .map(|(_, value)| value)
+    }
+
+    pub fn set_property(&mut self, name: &str, property: &str, value: Token) {
+        let properties = self.property_lists.entry(Box::from(name)).or_default();
+        match properties.iter_mut().find(|(key, _)| &**key == property) {
+            Some((_, old_value)) => *old_value = value,
+            None => properties.push((Box::from(property), value)),
+        }
+    }
+
+    pub fn remove_property(&mut self, name: &str, property: &str) {
+        let Some(properties) = self.property_lists.get_mut(name) else {
+            return;
+        };
+        properties.retain(|(key, _)| &**key != property);
+        if properties.is_empty() {
+            self.property_lists.remove(name);
+        }
+    }
+
+    pub fn get_property_list(&self, name: &str) -> Vec<(&str, &Token)> {
+        match self.property_lists.get(name) {
+            Some(properties) => properties
+                .iter()
+                .map(|(key, value)| (key.as_ref(), value))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn get_property_list_names(&self) -> Vec<&str> {
+        self.property_lists
+            .keys()
+            .map(|name| name.as_ref())
+            .collect()
+    }
+
+    pub fn remove_all_property_lists(&mut self) {
+        self.property_lists.clear();
+    }
+
     pub fn get_base_directory(&self) -> &String {
         &self.base_file_directory
     }
@@ -130,22 +246,22 @@ impl DataStore {
         }
     }
 
-    pub fn get_last_error_message(&self) -> String {
-        self.last_error_message.clone()
+    pub fn get_last_error(&self) -> Option<&LogoError> {
+        self.last_error.as_ref()
     }
 
-    pub fn set_last_error_message(&mut self, message: String) {
-        self.last_error_message = message;
+    pub fn set_last_error(&mut self, error: LogoError) {
+        self.last_error = Some(error);
     }
 }
 
 #[derive(Debug)]
-struct Scope {
+pub struct Scope {
     variables: HashMap<Box<str>, Token>,
 }
 
 impl Scope {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Scope {
             variables: HashMap::new(),
         }
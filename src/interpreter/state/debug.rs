@@ -0,0 +1,97 @@
+use crate::interpreter::state::object::Turtle;
+use std::collections::HashSet;
+
+#[derive(Debug)]
+pub struct DebugState {
+    traced: HashSet<Box<str>>,
+    stepped: HashSet<Box<str>>,
+    // Lines of the main source to pause before.
+    breakpoints: HashSet<usize>,
+    // Set to pause before the next statement that runs.
+    pub pause_requested: bool,
+}
+
+impl DebugState {
+    pub fn new() -> Self {
+        DebugState {
+            traced: HashSet::new(),
+            stepped: HashSet::new(),
+            breakpoints: HashSet::new(),
+            pause_requested: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pause_requested || !self.stepped.is_empty() || !self.breakpoints.is_empty()
+    }
+
+    pub fn is_traced(&self, name: &str) -> bool {
+        self.traced.contains(name)
+    }
+
+    pub fn set_traced(&mut self, name: &str, is_traced: bool) {
+        if is_traced {
+            self.traced.insert(Box::from(name));
+        } else {
+            self.traced.remove(name);
+        }
+    }
+
+    pub fn is_stepped(&self, name: &str) -> bool {
+        self.stepped.contains(name)
+    }
+
+    pub fn set_stepped(&mut self, name: &str, is_stepped: bool) {
+        if is_stepped {
+            self.stepped.insert(Box::from(name));
+        } else {
+            self.stepped.remove(name);
+        }
+    }
+
+    pub fn is_breakpoint(&self, line: usize) -> bool {
+        self.breakpoints.contains(&line)
+    }
+
+    pub fn set_breakpoints(&mut self, lines: HashSet<usize>) {
+        self.breakpoints = lines;
+    }
+}
+
+// What the program was doing when it paused, for showing in the debugger.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct DebugSnapshot {
+    pub line: Option<usize>,
+    // Running procedures, innermost last.
+    pub call_stack: Vec<String>,
+    // Variables in each scope, innermost first and global last.
+    pub scopes: Vec<Vec<(String, String)>>,
+    pub turtle: Vec<(String, String)>,
+}
+
+impl DebugSnapshot {
+    pub fn turtle_properties(turtle: &Turtle) -> Vec<(String, String)> {
+        let mut properties = vec![
+            (String::from("name"), turtle.name.to_string()),
+            (
+                String::from("pos"),
+                format!("{} {}", turtle.pos.x, turtle.pos.y),
+            ),
+            (String::from("heading"), turtle.heading.to_string()),
+            (String::from("color"), turtle.color.to_string()),
+            (String::from("size"), turtle.size.w.to_string()),
+            (String::from("pensize"), turtle.pen_size.to_string()),
+            (String::from("visible?"), turtle.is_visible.to_string()),
+            (String::from("pendown?"), turtle.is_drawing.to_string()),
+        ];
+        let mut backpack: Vec<(String, String)> = turtle
+            .backpack
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        backpack.sort();
+        properties.extend(backpack);
+        properties
+    }
+}
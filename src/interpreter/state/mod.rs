@@ -1,5 +1,10 @@
 pub mod canvas;
+pub mod curve;
 pub mod datastore;
+pub mod debug;
 pub mod input;
 pub mod object;
+pub mod pen;
+pub mod process;
 pub mod state;
+pub mod trigger;
@@ -1,5 +1,8 @@
-use crate::interpreter::language::token::Token;
+use crate::interpreter::language::token::{List, Token};
+use crate::interpreter::state::curve::Curve;
+use crate::interpreter::state::pen::Pen;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum Object {
@@ -24,19 +27,19 @@ impl Object {
 
     pub fn set_pos(&mut self, pos: Point) {
         match self {
-            Self::Turtle(turtle) => turtle.pos = pos,
+            Self::Turtle(turtle) => turtle.move_to(pos),
             Self::Text(text) => text.pos = pos,
         }
     }
 
-    pub fn color(&self) -> f32 {
+    pub fn color(&self) -> Color {
         match self {
             Self::Turtle(turtle) => turtle.color,
             Self::Text(text) => text.color,
         }
     }
 
-    pub fn set_color(&mut self, color: f32) {
+    pub fn set_color(&mut self, color: Color) {
         match self {
             Self::Turtle(turtle) => turtle.color = color,
             Self::Text(text) => text.color = color,
@@ -77,14 +80,24 @@ pub struct Turtle {
     pub name: Box<str>,
     pub pos: Point,
     pub heading: f32,
-    pub color: f32,
+    pub color: Color,
     pub size: Size,
     pub pen_size: f32,
+    pub pen: Pen,
+    // How far the pen has drawn since it was last put down, so that dashes
+    // carry on from one line to the next.
+    pub pen_distance: f32,
     pub shape: TurtleShape,
     pub is_visible: bool,
     pub is_drawing: bool,
     pub is_locked: bool,
     pub backpack: HashMap<Box<str>, Token>,
+    pub onclick: Option<String>,
+    pub ontouching: Option<String>,
+    // Actions for the colors the turtle can run into.
+    pub oncolor: Vec<(Color, String)>,
+    // Where the turtle has been since beginfill.
+    pub fill_path: Option<Vec<Point>>,
 }
 
 impl Turtle {
@@ -93,17 +106,67 @@ impl Turtle {
             name,
             pos: Point::zero(),
             heading: 0.0,
-            color: 1.0, // Black
+            color: Color::BLACK,
             size: Size::equal(20.0),
             pen_size: 1.0,
+            pen: Pen::new(),
+            pen_distance: 0.0,
             shape: TurtleShape::Triangle,
             is_visible: true,
             is_drawing: true,
             is_locked: false,
             backpack: HashMap::new(),
+            onclick: None,
+            ontouching: None,
+            oncolor: vec![],
+            fill_path: None,
         }
     }
 
+    pub fn move_to(&mut self, pos: Point) {
+        if let Some(path) = &mut self.fill_path {
+            path.push(pos.clone());
+        }
+        self.pos = pos;
+    }
+
+    pub fn line_to(&mut self, start: Point, end: Point) -> Line {
+        let length = start.distance_to(&end);
+        let mut line = Line::new(start, end, self.color, self.pen_size);
+        line.pen = self.pen.clone();
+        line.dash_offset = self.pen_distance;
+        self.pen_distance += length;
+        line
+    }
+
+    pub fn curve_through(&mut self, points: Vec<Point>, is_closed: bool) -> Curve {
+        let curve = Curve {
+            points,
+            is_closed,
+            color: self.color,
+            stroke_width: self.pen_size,
+            pen: self.pen.clone(),
+            dash_offset: self.pen_distance,
+        };
+        self.pen_distance += curve.length();
+        curve
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        (point.x - self.pos.x).abs() <= self.size.w / 2.0
+            && (point.y - self.pos.y).abs() <= self.size.h / 2.0
+    }
+
+    pub fn is_touching(&self, other: &Turtle) -> bool {
+        if !self.is_visible || !other.is_visible {
+            return false;
+        }
+        self.pos.x - self.size.w / 2.0 < other.pos.x + other.size.w / 2.0
+            && self.pos.x + self.size.w / 2.0 > other.pos.x - other.size.w / 2.0
+            && self.pos.y - self.size.h / 2.0 < other.pos.y + other.size.h / 2.0
+            && self.pos.y + self.size.h / 2.0 > other.pos.y - other.size.h / 2.0
+    }
+
     pub fn true_heading(&self) -> f32 {
         // Translate heading from a "clockwise, 0 == north" to a "counterclockwise, 0 == east" system.
         (-self.heading + 90.0).to_radians()
@@ -135,7 +198,7 @@ pub struct Text {
     pub pos: Point,
     pub text: String,
     pub font_size: f32,
-    pub color: f32,
+    pub color: Color,
     pub style: HashSet<TextStyle>,
     pub is_visible: bool,
     pub is_locked: bool,
@@ -148,7 +211,7 @@ impl Text {
             pos: Point::zero(),
             text: String::from("New Text"),
             font_size: 12.0,
-            color: 1.0, // Black
+            color: Color::BLACK,
             style: HashSet::new(),
             is_visible: true,
             is_locked: false,
@@ -172,6 +235,14 @@ impl TextStyle {
             _ => None,
         }
     }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Bold => "bold",
+            Self::Italic => "italic",
+            Self::Underline => "underline",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -188,6 +259,10 @@ impl Point {
     pub fn zero() -> Self {
         Self::new(0.0, 0.0)
     }
+
+    pub fn distance_to(&self, other: &Point) -> f32 {
+        ((other.x - self.x).powi(2) + (other.y - self.y).powi(2)).sqrt()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -206,21 +281,183 @@ impl Size {
     }
 }
 
+// Either a MicroWorlds color number or red, green, blue and alpha values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Number(f32),
+    Rgba([u8; 4]),
+}
+
+impl Color {
+    pub const BLACK: Self = Self::Number(9.0);
+    pub const WHITE: Self = Self::Number(0.0);
+
+    // The middle shade of each band of ten in the palette, starting with the
+    // grays from 0 to 9.
+    const PALETTE: [(&str, [u8; 3]); 14] = [
+        ("gray", [128, 128, 128]),
+        ("red", [255, 0, 0]),
+        ("orange", [255, 165, 0]),
+        ("brown", [139, 69, 19]),
+        ("yellow", [255, 255, 0]),
+        ("green", [0, 128, 0]),
+        ("lime", [0, 255, 0]),
+        ("turquoise", [64, 224, 208]),
+        ("cyan", [0, 255, 255]),
+        ("sky", [135, 206, 235]),
+        ("blue", [0, 0, 255]),
+        ("violet", [128, 0, 128]),
+        ("magenta", [255, 0, 255]),
+        ("pink", [255, 192, 203]),
+    ];
+
+    pub fn from_token(token: &Token) -> Option<Self> {
+        match token {
+            Token::Number(number) => Some(Self::Number(*number)),
+            Token::Word(name) => Self::from_name(name),
+            Token::List(list) => {
+                if list.len() != 3 && list.len() != 4 {
+                    return None;
+                }
+                let mut rgba = [255; 4];
+                for (index, item) in list.items().iter().enumerate() {
+                    let Token::Number(value) = item else {
+                        return None;
+                    };
+                    if !(0.0..=255.0).contains(value) {
+                        return None;
+                    }
+                    rgba[index] = value.round() as u8;
+                }
+                Some(Self::Rgba(rgba))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        // Named colors are the middle shades of the palette.
+        let name = match name.to_lowercase().as_str() {
+            "black" => return Some(Self::BLACK),
+            "white" => return Some(Self::WHITE),
+            "grey" => String::from("gray"),
+            "purple" => String::from("violet"),
+            name => String::from(name),
+        };
+        let band = Self::PALETTE.iter().position(|(entry, _)| *entry == name)?;
+        Some(Self::Number(band as f32 * 10.0 + 5.0))
+    }
+
+    pub fn to_rgba(self) -> [u8; 4] {
+        let number = match self {
+            Self::Number(number) => number,
+            Self::Rgba(rgba) => return rgba,
+        };
+        // Numbers wrap around the palette of 140 colors, in bands of ten
+        // shades that go from dark to light around the named color at 5.
+        let number = number.round().rem_euclid(140.0) as usize;
+        let (band, shade) = (number / 10, number % 10);
+        if band == 0 {
+            // The grays go from white at 0 to black at 9.
+            let gray = (255 * (9 - shade) / 9) as u8;
+            return [gray, gray, gray, 255];
+        }
+        let base = Self::PALETTE[band].1;
+        let mut rgba = [255; 4];
+        for (channel, value) in base.into_iter().enumerate() {
+            let value = value as usize;
+            rgba[channel] = if shade <= 5 {
+                value * (shade + 1) / 6
+            } else {
+                value + (255 - value) * (shade - 5) / 5
+            } as u8;
+        }
+        rgba
+    }
+
+    pub fn to_token(self) -> Token {
+        match self {
+            Self::Number(number) => Token::Number(number),
+            Self::Rgba([r, g, b, a]) => {
+                let mut values = vec![r, g, b];
+                if a != 255 {
+                    values.push(a);
+                }
+                let items = values
+                    .into_iter()
+                    .map(|value| Token::Number(value as f32))
+                    .collect();
+                Token::List(List::new(items))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_token().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Picture {
+    pub path: String,
+    // The top left corner of the picture.
+    pub pos: Point,
+    pub size: Size,
+}
+
 #[derive(Debug, Clone)]
 pub struct Line {
     pub start: Point,
     pub end: Point,
-    pub color: f32,
+    pub color: Color,
     pub stroke_width: f32,
+    pub pen: Pen,
+    // How far along the pen's pattern the line starts.
+    pub dash_offset: f32,
 }
 
 impl Line {
-    pub fn new(start: Point, end: Point, color: f32, stroke_width: f32) -> Self {
+    pub fn new(start: Point, end: Point, color: Color, stroke_width: f32) -> Self {
         Line {
             start,
             end,
             color,
             stroke_width,
+            pen: Pen::new(),
+            dash_offset: 0.0,
         }
     }
+
+    // Whether the next line carries on from this one with the same pen.
+    pub fn continues_to(&self, next: &Line) -> bool {
+        self.end.x == next.start.x
+            && self.end.y == next.start.y
+            && self.color == next.color
+            && self.stroke_width == next.stroke_width
+            && self.pen == next.pen
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub color: Color,
+    pub spans: Vec<Span>,
+}
+
+// A row of filled pixels, from the center of the first to the center of the last.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub y: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+// Everything drawn on the canvas, in the order it was drawn.
+#[derive(Debug, Clone)]
+pub enum Drawing {
+    Line(Line),
+    Curve(Curve),
+    Fill(Fill),
 }
@@ -0,0 +1,250 @@
+use crate::interpreter::state::object::Point;
+
+// How far a mitered corner can stick out, as a multiple of half the pen size,
+// before it is beveled instead.
+const MITER_LIMIT: f32 = 4.0;
+
+// How a turtle's pen draws, apart from its color and size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pen {
+    pub mode: PenMode,
+    // Lengths of dashes and the gaps between them, or empty for solid lines.
+    pub pattern: Vec<f32>,
+    pub cap: LineCap,
+    pub join: LineJoin,
+}
+
+impl Pen {
+    pub fn new() -> Self {
+        Pen {
+            mode: PenMode::Paint,
+            pattern: vec![],
+            cap: LineCap::Round,
+            join: LineJoin::Round,
+        }
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Self::new()
+    }
+
+    pub fn dashes(&self, start: &Point, end: &Point, offset: f32) -> Vec<(Point, Point)> {
+        // The offset is how far along the pattern the line starts.
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        let mut pattern = self.pattern.clone();
+        if pattern.len() % 2 == 1 {
+            pattern.extend(self.pattern.clone());
+        }
+        let total: f32 = pattern.iter().sum();
+        if pattern.is_empty() || total <= 0.0 || length == 0.0 {
+            return vec![(start.clone(), end.clone())];
+        }
+        let mut index = 0;
+        let mut position = offset.rem_euclid(total);
+        while position > 0.0 && position >= pattern[index] {
+            position -= pattern[index];
+            index = (index + 1) % pattern.len();
+        }
+        let point_at = |along: f32| {
+            let t = along / length;
+            Point::new(start.x + t * dx, start.y + t * dy)
+        };
+        let mut dashes = vec![];
+        let mut along = 0.0;
+        while along < length {
+            let step = (pattern[index] - position).min(length - along);
+            if index % 2 == 0 {
+                dashes.push((point_at(along), point_at(along + step)));
+            }
+            along += step;
+            position = 0.0;
+            index = (index + 1) % pattern.len();
+        }
+        dashes
+    }
+
+    pub fn stroke_pieces(
+        &self,
+        start: &Point,
+        end: &Point,
+        dash_offset: f32,
+        joined_from: Option<&Point>,
+        radius: f32,
+    ) -> Vec<StrokePiece> {
+        // Where the line carries on from another one, the corner between them
+        // is filled in, as long as the line starts with a dash.
+        let dashes = self.dashes(start, end, dash_offset);
+        let mut pieces = vec![];
+        if let (Some(from), Some((first, _))) = (joined_from, dashes.first()) {
+            if first.x == start.x && first.y == start.y {
+                pieces.extend(join_piece(from, start, end, radius, self.join));
+            }
+        }
+        for (start, end) in dashes {
+            pieces.extend(dash_piece(start, end, radius, self.cap));
+        }
+        pieces
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PenMode {
+    Paint,
+    // Clears back to the background.
+    Erase,
+    // Swaps the background for the pen color and back again, so drawing a
+    // line twice leaves the canvas as it was.
+    Reverse,
+}
+
+impl PenMode {
+    pub fn from(string: &str) -> Option<Self> {
+        match string {
+            "paint" => Some(Self::Paint),
+            "erase" => Some(Self::Erase),
+            "reverse" => Some(Self::Reverse),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Paint => "paint",
+            Self::Erase => "erase",
+            Self::Reverse => "reverse",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCap {
+    Round,
+    Butt,
+    Square,
+}
+
+impl LineCap {
+    pub fn from(string: &str) -> Option<Self> {
+        match string {
+            "round" => Some(Self::Round),
+            "butt" => Some(Self::Butt),
+            "square" => Some(Self::Square),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Round => "round",
+            Self::Butt => "butt",
+            Self::Square => "square",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    Round,
+    Miter,
+    Bevel,
+}
+
+impl LineJoin {
+    pub fn from(string: &str) -> Option<Self> {
+        match string {
+            "round" => Some(Self::Round),
+            "miter" => Some(Self::Miter),
+            "bevel" => Some(Self::Bevel),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Round => "round",
+            Self::Miter => "miter",
+            Self::Bevel => "bevel",
+        }
+    }
+}
+
+// Part of a stroke, in canvas coordinates.
+#[derive(Debug, Clone)]
+pub enum StrokePiece {
+    // A line drawn with a round pen of the given radius.
+    Round(Point, Point, f32),
+    // A convex shape.
+    Polygon(Vec<Point>),
+}
+
+fn dash_piece(start: Point, end: Point, radius: f32, cap: LineCap) -> Option<StrokePiece> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = match (cap, length == 0.0) {
+        (LineCap::Round, _) => return Some(StrokePiece::Round(start, end, radius)),
+        (LineCap::Butt, true) => return None,
+        (LineCap::Square, true) => (1.0, 0.0),
+        _ => (dx / length, dy / length),
+    };
+
+    // Square ends stick out past the ends of the line by half the pen size.
+    let extend = if cap == LineCap::Square { radius } else { 0.0 };
+    let start = Point::new(start.x - ux * extend, start.y - uy * extend);
+    let end = Point::new(end.x + ux * extend, end.y + uy * extend);
+    let (nx, ny) = (-uy * radius, ux * radius);
+    Some(StrokePiece::Polygon(vec![
+        Point::new(start.x + nx, start.y + ny),
+        Point::new(end.x + nx, end.y + ny),
+        Point::new(end.x - nx, end.y - ny),
+        Point::new(start.x - nx, start.y - ny),
+    ]))
+}
+
+fn join_piece(
+    from: &Point,
+    corner: &Point,
+    to: &Point,
+    radius: f32,
+    join: LineJoin,
+) -> Option<StrokePiece> {
+    let unit = |start: &Point, end: &Point| {
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        let length = (dx * dx + dy * dy).sqrt();
+        (length > 0.0).then(|| (dx / length, dy / length))
+    };
+    let ((x1, y1), (x2, y2)) = (unit(from, corner)?, unit(corner, to)?);
+    if radius <= 0.0 {
+        return None;
+    }
+    if join == LineJoin::Round {
+        return Some(StrokePiece::Round(corner.clone(), corner.clone(), radius));
+    }
+
+    // The gap to fill is on the outside of the turn.
+    let cross = x1 * y2 - y1 * x2;
+    if cross.abs() < 1e-6 {
+        return None;
+    }
+    let side = if cross > 0.0 { -radius } else { radius };
+    let (n1, n2) = ((-y1 * side, x1 * side), (-y2 * side, x2 * side));
+    let outer1 = Point::new(corner.x + n1.0, corner.y + n1.1);
+    let outer2 = Point::new(corner.x + n2.0, corner.y + n2.1);
+    let cos = x1 * x2 + y1 * y2;
+    if join == LineJoin::Bevel || (2.0 / (1.0 + cos)).sqrt() > MITER_LIMIT {
+        return Some(StrokePiece::Polygon(vec![corner.clone(), outer1, outer2]));
+    }
+
+    // The miter reaches to where the outer edges of the two lines meet.
+    let scale = 1.0 / (1.0 + cos);
+    let miter = Point::new(
+        corner.x + (n1.0 + n2.0) * scale,
+        corner.y + (n1.1 + n2.1) * scale,
+    );
+    Some(StrokePiece::Polygon(vec![
+        corner.clone(),
+        outer1,
+        miter,
+        outer2,
+    ]))
+}
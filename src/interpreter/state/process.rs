@@ -0,0 +1,172 @@
+use crate::interpreter::language::structure::{BlockCache, ParsedBlock};
+use crate::interpreter::language::token::List;
+use crate::interpreter::state::datastore::Scope;
+use crate::interpreter::util::error::StackFrame;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Instant;
+
+// A piece of code that runs alongside the main program, one
+// statement at a time, taking turns with the other processes.
+#[derive(Debug)]
+pub struct Process {
+    pub id: usize,
+    pub name: Box<str>,
+    // The blocks the process is partway through, innermost last, so that
+    // it can take turns from inside its loops and procedures.
+    pub frames: Vec<Frame>,
+    // When a process that is waiting can go on.
+    pub wake_at: Option<Instant>,
+    pub object: Box<str>,
+    pub scopes: VecDeque<Scope>,
+    // Where the process was launched from, for reporting its errors.
+    pub origin: Option<StackFrame>,
+}
+
+#[derive(Debug)]
+pub struct Frame {
+    pub code: String,
+    // Keeps the code once read, so that loops don't read it again.
+    pub cache: BlockCache,
+    pub block: Option<Arc<ParsedBlock>>,
+    // The next statement to run.
+    pub index: usize,
+    pub kind: FrameKind,
+}
+
+#[derive(Debug)]
+pub enum FrameKind {
+    Block,
+    Repeat { count: usize, total: usize },
+    Forever { count: usize },
+    While { condition: List, until: bool },
+    Procedure { name: Box<str>, line: usize },
+}
+
+impl Frame {
+    pub fn new(code: String, cache: BlockCache, kind: FrameKind) -> Self {
+        Frame {
+            code,
+            cache,
+            block: None,
+            index: 0,
+            kind,
+        }
+    }
+
+    pub fn from_list(list: &List, kind: FrameKind) -> Self {
+        let cache = list.block_cache().cloned().unwrap_or_default();
+        Self::new(list.source().into_owned(), cache, kind)
+    }
+}
+
+#[derive(Debug)]
+pub struct ProcessList {
+    processes: Vec<Process>,
+    next_id: usize,
+    pub is_running: bool,
+    pub current: Option<Box<str>>,
+    pub cancel_current: bool,
+}
+
+impl ProcessList {
+    pub fn new() -> Self {
+        ProcessList {
+            processes: vec![],
+            next_id: 0,
+            is_running: false,
+            current: None,
+            cancel_current: false,
+        }
+    }
+
+    pub fn launch(
+        &mut self,
+        name: &str,
+        code: String,
+        is_forever: bool,
+        object: &str,
+        origin: Option<StackFrame>,
+        scope: Scope,
+    ) {
+        // Starting a "forever" that is already running does not start it twice.
+        if is_forever && self.contains(name) {
+            return;
+        }
+        let kind = match is_forever {
+            true => FrameKind::Forever { count: 1 },
+            false => FrameKind::Block,
+        };
+        let process = Process {
+            id: self.next_id,
+            name: Box::from(name),
+            frames: vec![Frame::new(code, BlockCache::default(), kind)],
+            wake_at: None,
+            object: Box::from(object),
+            scopes: VecDeque::from([scope]),
+            origin,
+        };
+        self.next_id += 1;
+        self.processes.push(process);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.processes.iter().any(|process| &*process.name == name)
+            || self.current.as_deref() == Some(name)
+    }
+
+    pub fn cancel(&mut self, name: &str) {
+        self.processes.retain(|process| &*process.name != name);
+        if self.current.as_deref() == Some(name) {
+            self.cancel_current = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.processes.clear();
+        self.cancel_current = self.current.is_some();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty() && self.current.is_none()
+    }
+
+    pub fn has_awake(&self) -> bool {
+        let now = Instant::now();
+        self.processes
+            .iter()
+            .any(|process| process.wake_at.map_or(true, |wake_at| wake_at <= now))
+    }
+
+    pub fn names(&self) -> Vec<Box<str>> {
+        let mut names: Vec<Box<str>> = self.current.iter().cloned().collect();
+        names.extend(self.processes.iter().map(|process| process.name.clone()));
+        names
+    }
+
+    pub fn ids(&self) -> Vec<usize> {
+        self.processes.iter().map(|process| process.id).collect()
+    }
+
+    pub fn take(&mut self, id: usize) -> Option<Process> {
+        let index = self.processes.iter().position(|process| process.id == id)?;
+        let process = self.processes.remove(index);
+        self.current = Some(process.name.clone());
+        self.cancel_current = false;
+        Some(process)
+    }
+
+    pub fn put_back(&mut self, process: Process) {
+        self.current = None;
+        if self.cancel_current {
+            self.cancel_current = false;
+        } else {
+            self.processes.push(process);
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.current = None;
+        self.cancel_current = false;
+    }
+}
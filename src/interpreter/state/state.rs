@@ -1,6 +1,9 @@
 use crate::interpreter::state::canvas::CanvasState;
 use crate::interpreter::state::datastore::DataStore;
+use crate::interpreter::state::debug::DebugState;
 use crate::interpreter::state::input::InputManager;
+use crate::interpreter::state::process::ProcessList;
+use crate::interpreter::state::trigger::TriggerList;
 use std::error::Error;
 use std::time::SystemTime;
 
@@ -10,6 +13,9 @@ pub struct State {
     pub data: DataStore,
     pub canvas: CanvasState,
     pub input: InputManager,
+    pub processes: ProcessList,
+    pub triggers: TriggerList,
+    pub debug: DebugState,
 }
 
 impl State {
@@ -19,6 +25,9 @@ impl State {
             data: DataStore::new(),
             canvas: CanvasState::new(),
             input: InputManager::new(),
+            processes: ProcessList::new(),
+            triggers: TriggerList::new(),
+            debug: DebugState::new(),
         }
     }
 
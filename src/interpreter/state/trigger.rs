@@ -0,0 +1,70 @@
+use crate::interpreter::language::token::List;
+use crate::interpreter::state::object::Color;
+use crate::interpreter::util::error::StackFrame;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+// Runs an action each time its condition becomes true, set up with "when".
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub condition: List,
+    pub action: Arc<str>,
+    pub object: Box<str>,
+    pub was_true: bool,
+    // Where the trigger was set up, for reporting errors in its condition.
+    pub origin: Option<StackFrame>,
+}
+
+#[derive(Debug)]
+pub struct TriggerList {
+    pub whens: Vec<Trigger>,
+    pub has_turtle_events: bool,
+    // Turtles that were clicked since triggers were last checked.
+    pub clicked: Vec<Box<str>>,
+    // What each turtle was doing when triggers were last checked, so
+    // that events only fire when something changes.
+    pub touching: HashSet<Box<str>>,
+    pub colors_under: HashMap<Box<str>, Color>,
+}
+
+impl TriggerList {
+    pub fn new() -> Self {
+        TriggerList {
+            whens: vec![],
+            has_turtle_events: false,
+            clicked: vec![],
+            touching: HashSet::new(),
+            colors_under: HashMap::new(),
+        }
+    }
+
+    pub fn add_when(
+        &mut self,
+        condition: List,
+        action: &str,
+        object: &str,
+        origin: Option<StackFrame>,
+    ) {
+        self.cancel(condition.source().trim());
+        self.whens.push(Trigger {
+            condition,
+            action: Arc::from(action),
+            object: Box::from(object),
+            was_true: false,
+            origin,
+        });
+    }
+
+    pub fn cancel(&mut self, condition: &str) {
+        self.whens
+            .retain(|trigger| trigger.condition.source().trim() != condition);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.whens.is_empty() && !self.has_turtle_events
+    }
+
+    pub fn clear(&mut self) {
+        *self = TriggerList::new();
+    }
+}
@@ -1,17 +1,287 @@
+use crate::interpreter::language::token::Token;
 use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum LogoError {
+    WrongInputType(ErrorInfo),
+    UnknownProcedure(ErrorInfo),
+    UnboundVariable(ErrorInfo),
+    Syntax(ErrorInfo),
+    Io(ErrorInfo),
+    Runtime(ErrorInfo),
+    // Raised by the program itself with throw "error.
+    User(ErrorInfo),
+    Interrupt,
+    Eof,
+    // Carry "output" and "stop" back to the procedure that is running.
+    Output(Token),
+    Stop,
+    // Ends the whole program from "stopall".
+    StopAll,
+    // Carries a value from "throw" to the "catch" with the same tag.
+    Throw(String, Token),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorInfo {
+    pub message: String,
+    // Innermost frame first, ending with the top level of the program.
+    pub stack: Vec<StackFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub procedure: Option<Box<str>>,
+    pub location: SourceLocation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub file: Box<str>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ErrorInfo {
+    fn new(message: String) -> Self {
+        ErrorInfo {
+            message,
+            stack: vec![],
+        }
+    }
+}
+
+impl LogoError {
+    pub fn wrong_input_type(message: String) -> Self {
+        LogoError::WrongInputType(ErrorInfo::new(message))
+    }
+
+    pub fn unknown_procedure(message: String) -> Self {
+        LogoError::UnknownProcedure(ErrorInfo::new(message))
+    }
+
+    pub fn unbound_variable(message: String) -> Self {
+        LogoError::UnboundVariable(ErrorInfo::new(message))
+    }
+
+    pub fn syntax(message: String) -> Self {
+        LogoError::Syntax(ErrorInfo::new(message))
+    }
+
+    pub fn runtime(message: String) -> Self {
+        LogoError::Runtime(ErrorInfo::new(message))
+    }
+
+    pub fn user(message: String) -> Self {
+        LogoError::User(ErrorInfo::new(message))
+    }
+
+    pub fn from_error(err: Box<dyn Error>) -> Self {
+        match err.downcast::<LogoError>() {
+            Ok(logo_error) => *logo_error,
+            Err(err) => match err.downcast::<std::io::Error>() {
+                Ok(io_error) => LogoError::Io(ErrorInfo::new(io_error.to_string())),
+                Err(err) => LogoError::runtime(err.to_string()),
+            },
+        }
+    }
+
+    pub fn info(&self) -> Option<&ErrorInfo> {
+        match self {
+            LogoError::WrongInputType(info)
+            | LogoError::UnknownProcedure(info)
+            | LogoError::UnboundVariable(info)
+            | LogoError::Syntax(info)
+            | LogoError::Io(info)
+            | LogoError::Runtime(info)
+            | LogoError::User(info) => Some(info),
+            LogoError::Interrupt | LogoError::Eof => None,
+            LogoError::Output(..) | LogoError::Stop | LogoError::StopAll => None,
+            LogoError::Throw(..) => None,
+        }
+    }
+
+    fn info_mut(&mut self) -> Option<&mut ErrorInfo> {
+        match self {
+            LogoError::WrongInputType(info)
+            | LogoError::UnknownProcedure(info)
+            | LogoError::UnboundVariable(info)
+            | LogoError::Syntax(info)
+            | LogoError::Io(info)
+            | LogoError::Runtime(info)
+            | LogoError::User(info) => Some(info),
+            LogoError::Interrupt | LogoError::Eof => None,
+            LogoError::Output(..) | LogoError::Stop | LogoError::StopAll => None,
+            LogoError::Throw(..) => None,
+        }
+    }
+
+    // A name for the kind of error, as reported by "error".
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LogoError::WrongInputType(..) => "wrong-input-type",
+            LogoError::UnknownProcedure(..) => "unknown-procedure",
+            LogoError::UnboundVariable(..) => "unbound-variable",
+            LogoError::Syntax(..) => "syntax",
+            LogoError::Io(..) => "io",
+            LogoError::Runtime(..) => "runtime",
+            LogoError::User(..) => "user",
+            LogoError::Interrupt => "interrupt",
+            LogoError::Eof => "eof",
+            LogoError::Output(..) => "output",
+            LogoError::Stop => "stop",
+            LogoError::StopAll => "stopall",
+            LogoError::Throw(..) => "throw",
+        }
+    }
+
+    pub fn push_frame(&mut self, procedure: Option<&str>, location: SourceLocation) {
+        if let Some(info) = self.info_mut() {
+            info.stack.push(StackFrame {
+                procedure: procedure.map(Box::from),
+                location,
+            });
+        }
+    }
+
+    pub fn location(&self) -> Option<&SourceLocation> {
+        let info = self.info()?;
+        info.stack.first().map(|frame| &frame.location)
+    }
+
+    // The message along with where it happened, for showing to the user.
+    pub fn describe(&self) -> String {
+        let Some(info) = self.info() else {
+            return self.to_string();
+        };
+        match info.stack.first() {
+            Some(StackFrame {
+                procedure: Some(procedure),
+                location,
+            }) => format!(
+                "{} in procedure {}, line {}",
+                info.message, procedure, location.line
+            ),
+            Some(StackFrame {
+                procedure: None,
+                location,
+            }) => format!("{} at line {}", info.message, location.line),
+            None => info.message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for LogoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogoError::Interrupt => write!(f, "interrupt"),
+            LogoError::Eof => write!(f, "eof"),
+            LogoError::Output(..) => write!(f, "output can only be used in a procedure"),
+            LogoError::Stop => write!(f, "stop can only be used in a procedure"),
+            LogoError::StopAll => write!(f, "stopall"),
+            LogoError::Throw(tag, _) => write!(f, "can't find catch tag for {}", tag),
+            other => write!(f, "{}", other.info().unwrap().message),
+        }
+    }
+}
+
+impl Error for LogoError {}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+// The message to show in the console for an error.
+pub fn describe_error(err: &(dyn Error + 'static)) -> String {
+    match err.downcast_ref::<LogoError>() {
+        Some(logo_error) => logo_error.describe(),
+        None => err.to_string(),
+    }
+}
+
+// A single line describing an error, for printing to a terminal.
+pub fn error_report(err: &(dyn Error + 'static)) -> String {
+    let Some(logo_error) = err.downcast_ref::<LogoError>() else {
+        return format!("error: {}", err);
+    };
+    match logo_error.location() {
+        Some(location) => format!("{}: error: {}", location, logo_error.describe()),
+        None => format!("error: {}", logo_error.describe()),
+    }
+}
 
 pub fn eof_error() -> Box<dyn Error> {
-    Box::from("eof")
+    Box::from(LogoError::Eof)
 }
 
 pub fn interrupt_error() -> Box<dyn Error> {
-    Box::from("interrupt")
+    Box::from(LogoError::Interrupt)
 }
 
 pub fn is_eof(err: &Box<dyn Error>) -> bool {
-    err.to_string() == "eof"
+    matches!(err.downcast_ref::<LogoError>(), Some(LogoError::Eof))
 }
 
 pub fn is_interrupt(err: &Box<dyn Error>) -> bool {
-    err.to_string() == "interrupt"
+    matches!(err.downcast_ref::<LogoError>(), Some(LogoError::Interrupt))
+}
+
+pub fn output_signal(value: Token) -> Box<dyn Error> {
+    Box::from(LogoError::Output(value))
+}
+
+pub fn stop_signal() -> Box<dyn Error> {
+    Box::from(LogoError::Stop)
+}
+
+pub fn stop_all_signal() -> Box<dyn Error> {
+    Box::from(LogoError::StopAll)
+}
+
+pub fn is_stop_all(err: &(dyn Error + 'static)) -> bool {
+    matches!(err.downcast_ref::<LogoError>(), Some(LogoError::StopAll))
+}
+
+pub fn is_return(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<LogoError>(),
+        Some(LogoError::Output(..) | LogoError::Stop)
+    )
+}
+
+pub fn throw_signal(tag: String, value: Token) -> Box<dyn Error> {
+    Box::from(LogoError::Throw(tag, value))
+}
+
+// Signals unwind the program to the place that handles them, rather than
+// being errors that "carefully" or catch "error should handle.
+pub fn is_signal(err: &(dyn Error + 'static)) -> bool {
+    matches!(
+        err.downcast_ref::<LogoError>(),
+        Some(
+            LogoError::Interrupt
+                | LogoError::Output(..)
+                | LogoError::Stop
+                | LogoError::StopAll
+                | LogoError::Throw(..)
+        )
+    )
+}
+
+// Turns a return from "output" or "stop" into the result.
+pub fn catch_return(result: Result<Token, Box<dyn Error>>) -> Result<Token, Box<dyn Error>> {
+    match result {
+        Err(err) => match err.downcast::<LogoError>() {
+            Ok(logo_error) => match *logo_error {
+                LogoError::Output(value) => Ok(value),
+                LogoError::Stop => Ok(Token::Void),
+                other => Err(Box::from(other)),
+            },
+            Err(err) => Err(err),
+        },
+        ok => ok,
+    }
 }
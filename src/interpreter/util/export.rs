@@ -0,0 +1,321 @@
+use crate::interpreter::state::canvas::CanvasState;
+use crate::interpreter::state::curve::Curve;
+use crate::interpreter::state::object::{Color, Drawing, Fill, Line, Object, Point, Size};
+use crate::interpreter::state::object::{Text, Turtle, TurtleShape};
+use crate::interpreter::state::pen::PenMode;
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use image::imageops::{self, FilterType};
+use image::{Pixel, Rgba, RgbaImage};
+use std::error::Error;
+
+// The same font as the canvas in the app uses.
+const FONT: &[u8] = include_bytes!("../../../assets/fonts/Ubuntu-Light.ttf");
+
+pub fn save_png(canvas: &CanvasState, path: &str) -> Result<(), Box<dyn Error>> {
+    let image = render_image(canvas)?;
+    image.save(path)?;
+    Ok(())
+}
+
+pub fn render_image(canvas: &CanvasState) -> Result<RgbaImage, Box<dyn Error>> {
+    let size = canvas.get_size().clone();
+    let (width, height) = (size.w as u32, size.h as u32);
+    let bg_color = Rgba(canvas.get_bg_color().to_rgba());
+    let mut image = RgbaImage::from_pixel(width, height, bg_color);
+
+    // Background
+    if let Some(path) = canvas.get_bg_picture() {
+        let picture = load_picture(path, &size)?;
+        imageops::overlay(&mut image, &picture, 0, 0);
+    }
+
+    // Pictures
+    for picture in canvas.get_pictures() {
+        let (x, y) = to_image_coordinates(&picture.pos, &size);
+        let loaded = load_picture(&picture.path, &picture.size)?;
+        imageops::overlay(&mut image, &loaded, x as i64, y as i64);
+    }
+
+    // Lines, from the same pixels that "colorunder" reads.
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let point = Point::new(
+            x as f32 - size.w / 2.0,
+            (height - 1 - y) as f32 - size.h / 2.0,
+        );
+        if canvas.has_line_at_point(&point) {
+            pixel.blend(&Rgba(canvas.color_at_point(&point).to_rgba()));
+        }
+    }
+
+    // Turtles and Text
+    for object in canvas.objects() {
+        match object {
+            Object::Turtle(turtle) if turtle.is_visible => {
+                draw_turtle(&mut image, turtle, &size)?;
+            }
+            Object::Text(text) if text.is_visible => {
+                draw_text(&mut image, text, &size)?;
+            }
+            _ => (),
+        }
+    }
+    Ok(image)
+}
+
+fn to_image_coordinates(point: &Point, size: &Size) -> (f32, f32) {
+    (point.x + size.w / 2.0, size.h / 2.0 - point.y)
+}
+
+fn load_picture(path: &str, size: &Size) -> Result<RgbaImage, Box<dyn Error>> {
+    let Ok(picture) = image::open(path) else {
+        return Err(Box::from(format!("could not open path: {}", path)));
+    };
+    let (width, height) = (size.w.max(1.0) as u32, size.h.max(1.0) as u32);
+    Ok(imageops::resize(
+        &picture.to_rgba8(),
+        width,
+        height,
+        FilterType::Triangle,
+    ))
+}
+
+fn draw_turtle(image: &mut RgbaImage, turtle: &Turtle, size: &Size) -> Result<(), Box<dyn Error>> {
+    let (cx, cy) = to_image_coordinates(&turtle.pos, size);
+    let color = Rgba(turtle.color.to_rgba());
+    let (w, h) = (turtle.size.w, turtle.size.h);
+    match &turtle.shape {
+        TurtleShape::Triangle => {
+            // The first corner points in the direction of the heading.
+            let radius = w / 2.0;
+            let corners: Vec<(f32, f32)> = [0.0, 120.0, 240.0]
+                .iter()
+                .map(|offset: &f32| {
+                    let angle = (turtle.heading + offset).to_radians();
+                    (cx + radius * angle.sin(), cy - radius * angle.cos())
+                })
+                .collect();
+            fill_where(image, cx - radius, cy - radius, w, w, color, |x, y| {
+                in_triangle((x, y), corners[0], corners[1], corners[2])
+            });
+        }
+        TurtleShape::Circle => {
+            let radius = w / 2.0;
+            fill_where(image, cx - radius, cy - radius, w, w, color, |x, y| {
+                (x - cx).powi(2) + (y - cy).powi(2) <= radius.powi(2)
+            });
+        }
+        TurtleShape::Square => {
+            fill_where(image, cx - w / 2.0, cy - h / 2.0, w, h, color, |_, _| true);
+        }
+        TurtleShape::Image(_, path) => {
+            let picture = load_picture(path, &turtle.size)?;
+            let (x, y) = (cx - w / 2.0, cy - h / 2.0);
+            imageops::overlay(image, &picture, x as i64, y as i64);
+        }
+    }
+    Ok(())
+}
+
+fn fill_where(
+    image: &mut RgbaImage,
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+    color: Rgba<u8>,
+    is_inside: impl Fn(f32, f32) -> bool,
+) {
+    let (x_start, y_start) = (left.floor().max(0.0) as u32, top.floor().max(0.0) as u32);
+    let x_end = ((left + width).ceil().max(0.0) as u32).min(image.width());
+    let y_end = ((top + height).ceil().max(0.0) as u32).min(image.height());
+    for y in y_start..y_end {
+        for x in x_start..x_end {
+            // Test the center of each pixel.
+            if is_inside(x as f32 + 0.5, y as f32 + 0.5) {
+                image.get_pixel_mut(x, y).blend(&color);
+            }
+        }
+    }
+}
+
+fn in_triangle(p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    let side = |p1: (f32, f32), p2: (f32, f32)| {
+        (p.0 - p2.0) * (p1.1 - p2.1) - (p1.0 - p2.0) * (p.1 - p2.1)
+    };
+    let (d1, d2, d3) = (side(a, b), side(b, c), side(c, a));
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+fn draw_text(image: &mut RgbaImage, text: &Text, size: &Size) -> Result<(), Box<dyn Error>> {
+    let font = FontRef::try_from_slice(FONT)?;
+    let scaled = font.as_scaled(PxScale::from(text.font_size));
+    let [r, g, b, a] = text.color.to_rgba();
+    let (cx, top) = to_image_coordinates(&text.pos, size);
+
+    // Each line is centered on the position of the text, like on the canvas.
+    for (index, line) in text.text.lines().enumerate() {
+        let width: f32 = line
+            .chars()
+            .map(|chr| scaled.h_advance(font.glyph_id(chr)))
+            .sum();
+        let mut x = cx - width / 2.0;
+        let baseline = top + scaled.ascent() + index as f32 * scaled.height();
+        for chr in line.chars() {
+            let glyph_id = font.glyph_id(chr);
+            let glyph = glyph_id.with_scale_and_position(scaled.scale, (x, baseline));
+            x += scaled.h_advance(glyph_id);
+            let Some(outline) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= image.width() as i32 || py >= image.height() as i32 {
+                    return;
+                }
+                let pixel = image.get_pixel_mut(px as u32, py as u32);
+                let coverage = coverage * a as f32 / 255.0;
+                for (channel, value) in pixel.0.iter_mut().zip([r, g, b]) {
+                    *channel = (*channel as f32 * (1.0 - coverage) + value as f32 * coverage) as u8;
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn svg_document(canvas: &CanvasState) -> String {
+    let size = canvas.get_size();
+    let mut document = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        size.w, size.h, size.w, size.h
+    );
+    document += &format!(
+        "  <rect width=\"100%\" height=\"100%\" {}/>\n",
+        svg_paint("fill", canvas.get_bg_color())
+    );
+
+    // Lines that continue from the end of the last one, with the same pen,
+    // are joined into a single path.
+    let bg_color = canvas.get_bg_color();
+    let mut path: Option<(String, &Line, &Line)> = None;
+    for drawing in canvas.get_drawings() {
+        let line = match drawing {
+            Drawing::Line(line) => line,
+            Drawing::Curve(curve) => {
+                if let Some((data, first, _)) = path.take() {
+                    document += &svg_path(&data, first, bg_color);
+                }
+                document += &svg_curve(curve, size, bg_color);
+                continue;
+            }
+            Drawing::Fill(fill) => {
+                if let Some((data, first, _)) = path.take() {
+                    document += &svg_path(&data, first, bg_color);
+                }
+                document += &svg_fill(fill, size);
+                continue;
+            }
+        };
+        let (x1, y1) = to_image_coordinates(&line.start, size);
+        let (x2, y2) = to_image_coordinates(&line.end, size);
+        if let Some((data, first, last)) = &mut path {
+            if last.continues_to(line) {
+                *data += &format!(" L {} {}", x2, y2);
+                *last = line;
+                continue;
+            }
+            document += &svg_path(data, first, bg_color);
+        }
+        let data = format!("M {} {} L {} {}", x1, y1, x2, y2);
+        path = Some((data, line, line));
+    }
+    if let Some((data, first, _)) = &path {
+        document += &svg_path(data, first, bg_color);
+    }
+    document += "</svg>\n";
+    document
+}
+
+fn svg_curve(curve: &Curve, size: &Size, bg_color: Color) -> String {
+    let mut data = vec![];
+    for (index, point) in curve.points.iter().enumerate() {
+        let (x, y) = to_image_coordinates(point, size);
+        let command = if index == 0 { "M" } else { "L" };
+        data.push(format!("{} {} {}", command, x, y));
+    }
+    if curve.is_closed {
+        data.push(String::from("Z"));
+    }
+    // The curve is drawn the same way as a line with its pen.
+    let (Some(first), Some(last)) = (curve.points.first(), curve.points.last()) else {
+        return String::new();
+    };
+    let line = Line {
+        pen: curve.pen.clone(),
+        dash_offset: curve.dash_offset,
+        ..Line::new(first.clone(), last.clone(), curve.color, curve.stroke_width)
+    };
+    svg_path(&data.join(" "), &line, bg_color)
+}
+
+fn svg_fill(fill: &Fill, size: &Size) -> String {
+    // Each span is a rectangle one pixel high.
+    let mut data = vec![];
+    for span in &fill.spans {
+        let top_left = Point::new(span.left - 0.5, span.y + 0.5);
+        let (x, y) = to_image_coordinates(&top_left, size);
+        let width = span.right - span.left + 1.0;
+        data.push(format!("M {} {} h {} v 1 h {} z", x, y, width, -width));
+    }
+    format!(
+        "  <path d=\"{}\" {}/>\n",
+        data.join(" "),
+        svg_paint("fill", fill.color)
+    )
+}
+
+fn svg_path(data: &str, line: &Line, bg_color: Color) -> String {
+    // Erasing draws the background color. Reversing can't be shown in SVG,
+    // so it draws the pen color, which is what it does on the background.
+    let pen = &line.pen;
+    let color = match pen.mode {
+        PenMode::Erase => bg_color,
+        PenMode::Paint | PenMode::Reverse => line.color,
+    };
+    let mut dashes = String::new();
+    if !pen.pattern.is_empty() {
+        let pattern: Vec<String> = pen
+            .pattern
+            .iter()
+            .map(|length| length.to_string())
+            .collect();
+        dashes = format!(
+            " stroke-dasharray=\"{}\" stroke-dashoffset=\"{}\"",
+            pattern.join(" "),
+            line.dash_offset
+        );
+    }
+    format!(
+        "  <path d=\"{}\" fill=\"none\" {} stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"{}\"{}/>\n",
+        data,
+        svg_paint("stroke", color),
+        line.stroke_width,
+        pen.cap.name(),
+        pen.join.name(),
+        dashes
+    )
+}
+
+fn svg_paint(property: &str, color: Color) -> String {
+    // See-through colors are given an opacity, as well as the color.
+    let [r, g, b, a] = color.to_rgba();
+    let mut paint = format!("{}=\"rgb({},{},{})\"", property, r, g, b);
+    if a != 255 {
+        paint += &format!(" {}-opacity=\"{}\"", property, a as f32 / 255.0);
+    }
+    paint
+}
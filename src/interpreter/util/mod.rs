@@ -1,2 +1,4 @@
 pub mod error;
+pub mod export;
 pub mod performance;
+pub mod project;
@@ -0,0 +1,669 @@
+use crate::interpreter::language::structure::{BlockCache, Procedure, ProcedureParams};
+use crate::interpreter::language::token::{Array, List, Token};
+use crate::interpreter::state::canvas::CanvasState;
+use crate::interpreter::state::curve::Curve;
+use crate::interpreter::state::datastore::DataStore;
+use crate::interpreter::state::object::{Color, Drawing, Fill, Line, Object, Picture, Point};
+use crate::interpreter::state::object::{Size, Span};
+use crate::interpreter::state::object::{Text, TextStyle, Turtle, TurtleShape};
+use crate::interpreter::state::pen::{LineCap, LineJoin, Pen, PenMode};
+use crate::interpreter::state::state::State;
+use std::error::Error;
+
+// A project file has a header line followed by one record per line. Each record
+// is a kind followed by fields, which are numbers, booleans, quoted strings,
+// lists of fields in square brackets, or arrays of fields in braces followed by
+// their origin, like {1 2}@0.
+const HEADER: &str = "macroworlds-project 1";
+
+pub struct Project {
+    pub data: DataStore,
+    pub canvas: CanvasState,
+    pub source: String,
+}
+
+pub fn write_project(state: &State, source: &str) -> String {
+    let data = &state.data;
+    let canvas = &state.canvas;
+    let mut records = vec![String::from(HEADER)];
+
+    let size = canvas.get_size();
+    records.push(format!("size {} {}", size.w, size.h));
+    records.push(format!("bg {}", canvas.get_bg_color()));
+    if let Some(path) = canvas.get_bg_picture() {
+        records.push(format!("bgpict {}", quote(path)));
+    }
+    for picture in canvas.get_pictures() {
+        records.push(format!(
+            "pict {} {} {} {} {}",
+            quote(&picture.path),
+            picture.pos.x,
+            picture.pos.y,
+            picture.size.w,
+            picture.size.h
+        ));
+    }
+
+    // Shapes go before turtles so they can be found when loading.
+    let mut shapes = data.get_all_shapes();
+    shapes.sort_by_key(|(name, _)| *name);
+    for (name, shape) in shapes {
+        if let TurtleShape::Image(_, path) = shape {
+            records.push(format!("shape {} {}", quote(name), quote(path)));
+        }
+    }
+
+    let mut variables = data.get_global_variables();
+    variables.sort_by_key(|(name, _)| *name);
+    for (name, value) in variables {
+        if let Some(value) = encode_token(value) {
+            records.push(format!("global {} {}", quote(name), value));
+        }
+    }
+
+    let mut names = data.get_property_list_names();
+    names.sort();
+    for name in names {
+        for (property, value) in data.get_property_list(name) {
+            if let Some(value) = encode_token(value) {
+                records.push(format!(
+                    "property {} {} {}",
+                    quote(name),
+                    quote(property),
+                    value
+                ));
+            }
+        }
+    }
+
+    let mut procedures = data.get_all_procedures();
+    procedures.sort_by_key(|procedure| procedure.name.clone());
+    for procedure in procedures {
+        // Inputs are written like the title line, as "name, ["name" "default"]
+        // for optional inputs and ["name"] for the rest of the inputs.
+        let mut params: Vec<String> = procedure
+            .params
+            .required
+            .iter()
+            .map(|param| quote(param))
+            .collect();
+        for (param, default) in &procedure.params.optional {
+            params.push(format!("[{} {}]", quote(param), quote(default)));
+        }
+        if let Some(param) = &procedure.params.rest {
+            params.push(format!("[{}]", quote(param)));
+        }
+        records.push(format!(
+            "procedure {} [{}] {} {}",
+            quote(&procedure.name),
+            params.join(" "),
+            procedure.line,
+            quote(&procedure.code)
+        ));
+    }
+
+    let mut objects: Vec<&Object> = canvas.objects().collect();
+    objects.sort_by_key(|object| object.name().to_string());
+    for object in objects {
+        match object {
+            Object::Turtle(turtle) => write_turtle(&mut records, turtle),
+            Object::Text(text) => write_text(&mut records, text),
+        }
+    }
+
+    for drawing in canvas.get_drawings() {
+        match drawing {
+            Drawing::Line(line) => {
+                let mut record = format!(
+                    "line {} {} {} {} {} {}",
+                    line.start.x,
+                    line.start.y,
+                    line.end.x,
+                    line.end.y,
+                    line.color,
+                    line.stroke_width
+                );
+                // Lines drawn with a plain pen leave the pen out.
+                if !line.pen.is_plain() {
+                    record += &format!(" {} {}", pen_fields(&line.pen), line.dash_offset);
+                }
+                records.push(record);
+            }
+            Drawing::Curve(curve) => {
+                // Points are written as [x y x y ...]
+                let points: Vec<String> = curve
+                    .points
+                    .iter()
+                    .map(|point| format!("{} {}", point.x, point.y))
+                    .collect();
+                let mut record = format!(
+                    "curve {} {} {} [{}]",
+                    curve.color,
+                    curve.stroke_width,
+                    curve.is_closed,
+                    points.join(" ")
+                );
+                if !curve.pen.is_plain() {
+                    record += &format!(" {} {}", pen_fields(&curve.pen), curve.dash_offset);
+                }
+                records.push(record);
+            }
+            Drawing::Fill(fill) => {
+                // Spans are written as [y left right y left right ...]
+                let spans: Vec<String> = fill
+                    .spans
+                    .iter()
+                    .map(|span| format!("{} {} {}", span.y, span.left, span.right))
+                    .collect();
+                records.push(format!("fill {} [{}]", fill.color, spans.join(" ")));
+            }
+        }
+    }
+    if !canvas.current_object_name().is_empty() {
+        records.push(format!("current {}", quote(canvas.current_object_name())));
+    }
+    records.push(format!("source {}", quote(source)));
+
+    let mut document = records.join("\n");
+    document.push('\n');
+    document
+}
+
+fn write_turtle(records: &mut Vec<String>, turtle: &Turtle) {
+    let name = quote(&turtle.name);
+    let shape = match &turtle.shape {
+        TurtleShape::Image(shape_name, _) => shape_name.to_string(),
+        shape => shape.to_string(),
+    };
+    records.push(format!(
+        "turtle {} {} {} {} {} {} {} {} {} {} {} {}",
+        name,
+        turtle.pos.x,
+        turtle.pos.y,
+        turtle.heading,
+        turtle.color,
+        turtle.size.w,
+        turtle.size.h,
+        turtle.pen_size,
+        quote(&shape),
+        turtle.is_visible,
+        turtle.is_drawing,
+        turtle.is_locked
+    ));
+    let mut backpack: Vec<_> = turtle.backpack.iter().collect();
+    backpack.sort_by_key(|(key, _)| key.to_string());
+    for (key, value) in backpack {
+        if let Some(value) = encode_token(value) {
+            records.push(format!("backpack {} {} {}", name, quote(key), value));
+        }
+    }
+    if !turtle.pen.is_plain() {
+        records.push(format!("pen {} {}", name, pen_fields(&turtle.pen)));
+    }
+    if let Some(code) = &turtle.onclick {
+        records.push(format!("onclick {} {}", name, quote(code)));
+    }
+    if let Some(code) = &turtle.ontouching {
+        records.push(format!("ontouching {} {}", name, quote(code)));
+    }
+    for (color, code) in &turtle.oncolor {
+        records.push(format!("oncolor {} {} {}", name, color, quote(code)));
+    }
+}
+
+fn pen_fields(pen: &Pen) -> String {
+    let pattern: Vec<String> = pen
+        .pattern
+        .iter()
+        .map(|length| length.to_string())
+        .collect();
+    format!(
+        "{} {} {} [{}]",
+        quote(pen.mode.name()),
+        quote(pen.cap.name()),
+        quote(pen.join.name()),
+        pattern.join(" ")
+    )
+}
+
+fn write_text(records: &mut Vec<String>, text: &Text) {
+    let mut style: Vec<&str> = text.style.iter().map(|style| style.name()).collect();
+    style.sort();
+    let style: Vec<String> = style.iter().map(|style| quote(style)).collect();
+    records.push(format!(
+        "text {} {} {} {} {} {} [{}] {} {}",
+        quote(&text.name),
+        text.pos.x,
+        text.pos.y,
+        quote(&text.text),
+        text.font_size,
+        text.color,
+        style.join(" "),
+        text.is_visible,
+        text.is_locked
+    ));
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for chr in text.chars() {
+        match chr {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(chr),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn encode_token(token: &Token) -> Option<String> {
+    match token {
+        Token::Word(word) => Some(quote(word)),
+        Token::Number(number) => Some(number.to_string()),
+        Token::Boolean(boolean) => Some(boolean.to_string()),
+        Token::List(list) => {
+            let items: Vec<String> = list.items().iter().filter_map(encode_token).collect();
+            Some(format!("[{}]", items.join(" ")))
+        }
+        Token::Array(array) => {
+            let items: Vec<String> = array.items().iter().filter_map(encode_token).collect();
+            Some(format!("{{{}}}@{}", items.join(" "), array.origin()))
+        }
+        // Only data is saved, anything else only exists while a program runs.
+        _ => None,
+    }
+}
+
+pub fn read_project(text: &str) -> Result<Project, Box<dyn Error>> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == HEADER => (),
+        _ => return Err(Box::from("not a project file")),
+    }
+    let mut project = Project {
+        data: DataStore::new(),
+        canvas: CanvasState::new(),
+        source: String::new(),
+    };
+    let mut current_object = None;
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut record = Record::parse(line, index + 1)?;
+        let data = &mut project.data;
+        let canvas = &mut project.canvas;
+        match record.kind.as_str() {
+            "size" => {
+                let (w, h) = (record.number()?, record.number()?);
+                canvas.set_size(w, h);
+            }
+            "bg" => canvas.set_bg_color(record.color()?),
+            "bgpict" => canvas.set_bg_picture(record.text()?),
+            "pict" => canvas.add_picture(Picture {
+                path: record.text()?,
+                pos: Point::new(record.number()?, record.number()?),
+                size: Size::new(record.number()?, record.number()?),
+            }),
+            "shape" => {
+                let name = record.text()?.into_boxed_str();
+                let path = record.text()?;
+                data.set_shape(&name, TurtleShape::Image(name.clone(), path));
+            }
+            "global" => {
+                let name = record.text()?;
+                data.set_variable(&name, record.token()?);
+            }
+            "property" => {
+                let name = record.text()?;
+                let property = record.text()?;
+                data.set_property(&name, &property, record.token()?);
+            }
+            "procedure" => {
+                let name = record.text()?.into_boxed_str();
+                let mut params = ProcedureParams::default();
+                for param in record.list()? {
+                    match param {
+                        Field::Text(param) => params.required.push(param),
+                        Field::List(fields) => match fields.as_slice() {
+                            [Field::Text(param), Field::Text(default)] => {
+                                params.optional.push((param.clone(), default.clone()))
+                            }
+                            [Field::Text(param)] => params.rest = Some(param.clone()),
+                            _ => return Err(record.error("procedure inputs")),
+                        },
+                        _ => return Err(record.error("procedure inputs")),
+                    }
+                }
+                let line = record.number()? as usize;
+                let code = record.text()?;
+                data.set_procedure(Procedure {
+                    name,
+                    params,
+                    code,
+                    line,
+                    block: BlockCache::default(),
+                });
+            }
+            "turtle" => {
+                let mut turtle = Turtle::new(record.text()?.into_boxed_str());
+                turtle.pos = Point::new(record.number()?, record.number()?);
+                turtle.heading = record.number()?;
+                turtle.color = record.color()?;
+                turtle.size = Size::new(record.number()?, record.number()?);
+                turtle.pen_size = record.number()?;
+                let shape = record.text()?;
+                let Some(shape) = data.get_shape(&shape) else {
+                    return Err(record.error("a known shape"));
+                };
+                turtle.shape = shape.clone();
+                turtle.is_visible = record.boolean()?;
+                turtle.is_drawing = record.boolean()?;
+                turtle.is_locked = record.boolean()?;
+                canvas.add_object(Object::Turtle(turtle));
+            }
+            "backpack" | "onclick" | "ontouching" | "oncolor" | "pen" => {
+                let name = record.text()?;
+                let Ok(Object::Turtle(turtle)) = canvas.get_object_mut(&name) else {
+                    return Err(record.error("the name of a turtle"));
+                };
+                match record.kind.as_str() {
+                    "backpack" => {
+                        let key = record.text()?.into_boxed_str();
+                        turtle.backpack.insert(key, record.token()?);
+                    }
+                    "onclick" => turtle.onclick = Some(record.text()?),
+                    "pen" => turtle.pen = record.pen()?,
+                    "ontouching" => turtle.ontouching = Some(record.text()?),
+                    _ => {
+                        let color = record.color()?;
+                        turtle.oncolor.push((color, record.text()?));
+                    }
+                }
+            }
+            "text" => {
+                let mut text = Text::new(record.text()?.into_boxed_str());
+                text.pos = Point::new(record.number()?, record.number()?);
+                text.text = record.text()?;
+                text.font_size = record.number()?;
+                text.color = record.color()?;
+                for style in record.list()? {
+                    let Field::Text(style) = style else {
+                        return Err(record.error("a text style"));
+                    };
+                    text.style.extend(TextStyle::from(style));
+                }
+                text.is_visible = record.boolean()?;
+                text.is_locked = record.boolean()?;
+                canvas.add_object(Object::Text(text));
+            }
+            "line" => {
+                let start = Point::new(record.number()?, record.number()?);
+                let end = Point::new(record.number()?, record.number()?);
+                let (color, width) = (record.color()?, record.number()?);
+                let mut line = Line::new(start, end, color, width);
+                if record.fields.len() > 0 {
+                    line.pen = record.pen()?;
+                    line.dash_offset = record.number()?;
+                }
+                canvas.add_line(line);
+            }
+            "curve" => {
+                let (color, stroke_width) = (record.color()?, record.number()?);
+                let is_closed = record.boolean()?;
+                let mut numbers = vec![];
+                for field in record.list()? {
+                    let Field::Number(number) = field else {
+                        return Err(record.error("a list of numbers"));
+                    };
+                    numbers.push(number);
+                }
+                let points = numbers
+                    .chunks_exact(2)
+                    .map(|point| Point::new(point[0], point[1]))
+                    .collect();
+                let mut curve = Curve {
+                    points,
+                    is_closed,
+                    color,
+                    stroke_width,
+                    pen: Pen::new(),
+                    dash_offset: 0.0,
+                };
+                if record.fields.len() > 0 {
+                    curve.pen = record.pen()?;
+                    curve.dash_offset = record.number()?;
+                }
+                canvas.add_curve(curve);
+            }
+            "fill" => {
+                let color = record.color()?;
+                let mut numbers = vec![];
+                for field in record.list()? {
+                    let Field::Number(number) = field else {
+                        return Err(record.error("a list of numbers"));
+                    };
+                    numbers.push(number);
+                }
+                let spans = numbers
+                    .chunks_exact(3)
+                    .map(|span| Span {
+                        y: span[0],
+                        left: span[1],
+                        right: span[2],
+                    })
+                    .collect();
+                canvas.add_fill(Fill { color, spans });
+            }
+            "current" => current_object = Some(record.text()?),
+            "source" => project.source = record.text()?,
+            _ => return Err(record.error("a known record")),
+        }
+    }
+    if let Some(name) = current_object {
+        project.canvas.set_current_object(&name);
+    }
+    Ok(project)
+}
+
+enum Field {
+    Text(String),
+    Number(f32),
+    Boolean(bool),
+    List(Vec<Field>),
+    Array(Vec<Field>, i32),
+}
+
+impl Field {
+    fn into_token(self) -> Token {
+        match self {
+            Field::Text(text) => Token::Word(text),
+            Field::Number(number) => Token::Number(number),
+            Field::Boolean(boolean) => Token::Boolean(boolean),
+            Field::List(fields) => Token::List(List::new(
+                fields.into_iter().map(Field::into_token).collect(),
+            )),
+            Field::Array(fields, origin) => Token::Array(Array::new(
+                fields.into_iter().map(Field::into_token).collect(),
+                origin,
+            )),
+        }
+    }
+}
+
+struct Record {
+    kind: String,
+    fields: std::vec::IntoIter<Field>,
+    line: usize,
+}
+
+impl Record {
+    fn parse(text: &str, line: usize) -> Result<Self, Box<dyn Error>> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut position = 0;
+        let fields = Self::parse_fields(&chars, &mut position, None)
+            .map_err(|err| format!("project line {}: {}", line, err))?;
+        let mut fields = fields.into_iter();
+        let Some(Field::Text(kind)) = fields.next() else {
+            return Err(Box::from(format!("project line {}: missing record", line)));
+        };
+        Ok(Record { kind, fields, line })
+    }
+
+    fn parse_fields(
+        chars: &[char],
+        position: &mut usize,
+        closing: Option<char>,
+    ) -> Result<Vec<Field>, String> {
+        let mut fields = vec![];
+        while *position < chars.len() {
+            let chr = chars[*position];
+            *position += 1;
+            match chr {
+                ' ' | '\t' | '\r' => (),
+                ']' | '}' if closing == Some(chr) => return Ok(fields),
+                '[' => fields.push(Field::List(Self::parse_fields(chars, position, Some(']'))?)),
+                '{' => {
+                    let items = Self::parse_fields(chars, position, Some('}'))?;
+                    if chars.get(*position) != Some(&'@') {
+                        return Err(String::from("missing array origin"));
+                    }
+                    let start = *position + 1;
+                    *position = start;
+                    while *position < chars.len() && "-0123456789".contains(chars[*position]) {
+                        *position += 1;
+                    }
+                    let origin: String = chars[start..*position].iter().collect();
+                    let Ok(origin) = origin.parse::<i32>() else {
+                        return Err(String::from("missing array origin"));
+                    };
+                    fields.push(Field::Array(items, origin));
+                }
+                '"' => fields.push(Field::Text(Self::parse_string(chars, position)?)),
+                _ => {
+                    let start = *position - 1;
+                    while *position < chars.len() && !" \t\r[]{}\"".contains(chars[*position]) {
+                        *position += 1;
+                    }
+                    let word: String = chars[start..*position].iter().collect();
+                    fields.push(match word.as_str() {
+                        "true" => Field::Boolean(true),
+                        "false" => Field::Boolean(false),
+                        _ => match word.parse::<f32>() {
+                            Ok(number) => Field::Number(number),
+                            Err(..) => Field::Text(word),
+                        },
+                    });
+                }
+            }
+        }
+        if closing.is_some() {
+            return Err(String::from("unmatched brackets"));
+        }
+        Ok(fields)
+    }
+
+    fn parse_string(chars: &[char], position: &mut usize) -> Result<String, String> {
+        let mut string = String::new();
+        while *position < chars.len() {
+            let chr = chars[*position];
+            *position += 1;
+            match chr {
+                '"' => return Ok(string),
+                '\\' => {
+                    let Some(escaped) = chars.get(*position) else {
+                        break;
+                    };
+                    *position += 1;
+                    string.push(match escaped {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        other => *other,
+                    });
+                }
+                _ => string.push(chr),
+            }
+        }
+        Err(String::from("unmatched quotes"))
+    }
+
+    fn error(&self, expected: &str) -> Box<dyn Error> {
+        Box::from(format!(
+            "project line {}: {} expected {}",
+            self.line, self.kind, expected
+        ))
+    }
+
+    fn text(&mut self) -> Result<String, Box<dyn Error>> {
+        match self.fields.next() {
+            Some(Field::Text(text)) => Ok(text),
+            _ => Err(self.error("a string")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, Box<dyn Error>> {
+        match self.fields.next() {
+            Some(Field::Number(number)) => Ok(number),
+            _ => Err(self.error("a number")),
+        }
+    }
+
+    fn boolean(&mut self) -> Result<bool, Box<dyn Error>> {
+        match self.fields.next() {
+            Some(Field::Boolean(boolean)) => Ok(boolean),
+            _ => Err(self.error("true or false")),
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<Field>, Box<dyn Error>> {
+        match self.fields.next() {
+            Some(Field::List(fields)) => Ok(fields),
+            _ => Err(self.error("a list")),
+        }
+    }
+
+    fn color(&mut self) -> Result<Color, Box<dyn Error>> {
+        let field = self.fields.next().map(Field::into_token);
+        match field.as_ref().and_then(Color::from_token) {
+            Some(color) => Ok(color),
+            None => Err(self.error("a color")),
+        }
+    }
+
+    fn pen(&mut self) -> Result<Pen, Box<dyn Error>> {
+        let Some(mode) = PenMode::from(&self.text()?) else {
+            return Err(self.error("a pen mode"));
+        };
+        let Some(cap) = LineCap::from(&self.text()?) else {
+            return Err(self.error("a line cap"));
+        };
+        let Some(join) = LineJoin::from(&self.text()?) else {
+            return Err(self.error("a line join"));
+        };
+        let mut pattern = vec![];
+        for field in self.list()? {
+            let Field::Number(length) = field else {
+                return Err(self.error("a list of numbers"));
+            };
+            pattern.push(length);
+        }
+        Ok(Pen {
+            mode,
+            pattern,
+            cap,
+            join,
+        })
+    }
+
+    fn token(&mut self) -> Result<Token, Box<dyn Error>> {
+        match self.fields.next() {
+            Some(field) => Ok(field.into_token()),
+            None => Err(self.error("a value")),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+mod headless;
+mod interpreter;
+
+use headless::handler::{ConsoleHandler, HeadlessContext};
+use interpreter::interpreter::Interpreter;
+use interpreter::util::error::error_report;
+use interpreter::util::export::save_png;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::{env, fs};
+
+const USAGE: &str = "usage: macroworlds-run [--png <output.png>] <file.logo>";
+
+struct Options {
+    file: String,
+    png: Option<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Option<Self> {
+        let mut file = None;
+        let mut png = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--png" => png = Some(args.next()?.clone()),
+                _ if file.is_none() && !arg.starts_with("--") => file = Some(arg.clone()),
+                _ => return None,
+            }
+        }
+        Some(Options { file: file?, png })
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some(options) = Options::parse(&args) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let code = match fs::read_to_string(&options.file) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: could not read {}: {}", options.file, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut interpreter = Interpreter::new();
+    let file_name = Path::new(&options.file)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or(options.file.clone());
+    interpreter.set_source_name(&file_name);
+    interpreter.bind_ui_handler(
+        Arc::from(Mutex::from(ConsoleHandler)),
+        Arc::from(Mutex::from(HeadlessContext)),
+    );
+    let result = interpreter.interpret_main(&code);
+
+    if let Some(path) = &options.png {
+        if let Err(err) = save_png(&interpreter.state.canvas, path) {
+            eprintln!("error: could not save {}: {}", path, err);
+            return ExitCode::FAILURE;
+        }
+    }
+    match result {
+        Ok(..) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", error_report(err.as_ref()));
+            ExitCode::FAILURE
+        }
+    }
+}
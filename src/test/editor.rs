@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use crate::gui::editor::files::FileHandle;
+    use eframe::egui::TextBuffer;
+    use std::collections::HashSet;
+
+    #[test]
+    fn breakpoints_follow_edits() {
+        let mut file = FileHandle::new(String::from("test.logo"));
+        file.content = String::from("fd 10\nrt 90\nfd 20\nlt 45\n");
+        file.breakpoints = HashSet::from([2, 4]);
+
+        // New lines above a breakpoint move it down.
+        file.insert_text("pu\npd\n", 0);
+        assert!(file.breakpoints == HashSet::from([4, 6]));
+
+        // A new line in the middle of a line only moves the lines below.
+        file.insert_text("\n", 10);
+        assert!(file.content.starts_with("pu\npd\nfd 1\n0\nrt 90"));
+        assert!(file.breakpoints == HashSet::from([5, 7]));
+
+        // Deleted lines lose their breakpoints, and the lines below move up.
+        file.delete_char_range(2..18);
+        assert!(file.content == "pu\nfd 20\nlt 45\n");
+        assert!(file.breakpoints == HashSet::from([3]));
+
+        file.replace_with("home\n");
+        assert!(file.breakpoints.is_empty());
+    }
+}
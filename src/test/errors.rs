@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::interpreter::language::token::{List, Token};
+    use crate::interpreter::util::error::LogoError;
+
+    fn run_with_error(code: &str) -> LogoError {
+        let mut int = Interpreter::new();
+        int.set_source_name("test.logo");
+        match int.interpret(code) {
+            Ok(..) => panic!("test failed: expected an error"),
+            Err(err) => LogoError::from_error(err),
+        }
+    }
+
+    #[test]
+    fn error_kinds() {
+        let error = run_with_error("fd \"far");
+        assert!(matches!(error, LogoError::WrongInputType(..)));
+
+        let error = run_with_error("jump 10");
+        assert!(matches!(error, LogoError::UnknownProcedure(..)));
+        assert_eq!(error.to_string(), "I don't know how to jump");
+
+        let error = run_with_error("show :missing");
+        assert!(matches!(error, LogoError::UnboundVariable(..)));
+
+        let error = run_with_error("[1 2");
+        assert!(matches!(error, LogoError::Syntax(..)));
+
+        // Errors on the right of an operator, or in an input, are not dropped.
+        let error = run_with_error("show 1 + |a");
+        assert!(matches!(error, LogoError::Syntax(..)));
+        let error = run_with_error("make \"x 2 *");
+        assert!(matches!(error, LogoError::Syntax(..)));
+        assert_eq!(error.to_string(), "found nothing after *");
+    }
+
+    #[test]
+    fn error_location_and_stack() {
+        let code = "
+        to inner :value
+          show :value
+          show :missing
+        end
+
+        to outer
+          inner 5
+        end
+
+        outer
+        ";
+        let error = run_with_error(code);
+        let info = error.info().unwrap();
+        let procedures: Vec<Option<&str>> = info
+            .stack
+            .iter()
+            .map(|frame| frame.procedure.as_deref())
+            .collect();
+        assert_eq!(procedures, vec![Some("inner"), Some("outer"), None]);
+
+        let location = error.location().unwrap();
+        assert_eq!(&*location.file, "test.logo");
+        assert_eq!(location.line, 4);
+        assert_eq!(location.column, 11);
+        assert_eq!(info.stack[1].location.line, 8);
+        assert_eq!(info.stack[2].location.line, 11);
+        assert_eq!(
+            error.describe(),
+            "missing has no value in procedure inner, line 4"
+        );
+    }
+
+    #[test]
+    fn carefully_reports_location() {
+        let code = "
+        to broken
+          fd \"far
+        end
+
+        carefully [broken] [make \"message errormessage]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let message = Token::Word(String::from(
+            "forward expected a number for input 0 in procedure broken, line 3",
+        ));
+        assert_eq!(int.state.data.get_variable("message"), Some(&message));
+    }
+
+    #[test]
+    fn catch_and_throw() {
+        let code = "
+        to search :depth
+          if :depth = 5 [(throw \"found :depth * 10)]
+          search :depth + 1
+        end
+
+        to check :num
+          if :num < 0 [(throw \"error [negative input])]
+          output :num
+        end
+
+        make \"result1 catch \"found [search 0]
+        make \"result2 catch \"outer [catch \"inner [(throw \"outer 7)] 0]
+        make \"result3 catch \"error [check 3]
+        catch \"error [check -1]
+        make \"info error
+        carefully [check -2] [make \"message errormessage]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert_eq!(
+            int.state.data.get_variable("result1"),
+            Some(&Token::Number(50.0))
+        );
+        assert_eq!(
+            int.state.data.get_variable("result2"),
+            Some(&Token::Number(7.0))
+        );
+        assert_eq!(
+            int.state.data.get_variable("result3"),
+            Some(&Token::Number(3.0))
+        );
+        let info = Token::List(List::parse("user |negative input| check 8"));
+        assert_eq!(int.state.data.get_variable("info"), Some(&info));
+        let message = Token::Word(String::from("negative input in procedure check, line 8"));
+        assert_eq!(int.state.data.get_variable("message"), Some(&message));
+        assert!(int.interpret("throw \"nowhere").is_err());
+    }
+}
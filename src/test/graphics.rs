@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
     use crate::interpreter::interpreter::Interpreter;
-    use crate::interpreter::language::token::Token;
+    use crate::interpreter::language::token::{List, Token};
+    use crate::interpreter::state::canvas::CanvasState;
+    use crate::interpreter::state::object::{Color, Drawing, Line, Point};
+    use crate::interpreter::util::export;
 
     #[test]
     fn turtle_movement() {
@@ -16,14 +19,591 @@ mod tests {
         lt 90
         bk 20
         make \"result pos
+        make \"x 30
+        setpos [:x -10]
+        make \"result2 pos
         ";
         let mut int = Interpreter::new();
         match int.interpret(&code) {
             Ok(..) => (),
             Err(err) => panic!("test failed: {}", err),
         };
+        assert!(int.state.data.get_variable("result") == Some(&Token::List(List::parse("100 30"))));
         assert!(
-            int.state.data.get_variable("result") == Some(&Token::List(String::from("100 30")))
+            int.state.data.get_variable("result2") == Some(&Token::List(List::parse("30 -10")))
         );
     }
+
+    #[test]
+    fn triggers() {
+        let code = "
+        newturtle \"t1
+        newturtle \"t2
+        tto \"t2
+        setpos [100 0]
+        tto \"t1
+        make \"passed 0
+        make \"bumps 0
+        when [xcor > 40] [make \"passed :passed + 1]
+        setontouching [make \"bumps :bumps + 1]
+        seth 90
+        repeat 12 [fd 10]
+        make \"result who
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("passed") == Some(&Token::Number(1.0)));
+        assert!(int.state.data.get_variable("bumps") == Some(&Token::Number(1.0)));
+        assert!(int.state.data.get_variable("result") == Some(&Token::Word(String::from("t1"))));
+
+        // Without an input source, waiting triggers don't keep the program
+        // running once everything else has finished.
+        assert!(int.run_processes().is_ok());
+        assert!(!int.state.triggers.is_empty());
+    }
+
+    #[test]
+    fn color_triggers() {
+        // A named color matches a line drawn in the same color as a list.
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc [0 0 255]
+        seth 90
+        fd 50
+        newturtle \"t2
+        tto \"t2
+        pu
+        setpos [20 30]
+        make \"hits 0
+        setoncolor \"blue [make \"hits :hits + 1]
+        seth 180
+        repeat 6 [fd 10]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.run_processes().is_ok());
+        assert!(int.state.data.get_variable("hits") == Some(&Token::Number(1.0)));
+    }
+
+    #[test]
+    fn export_drawing() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        pu
+        setpos [0 0]
+        pd
+        seth 0
+        fd 50
+        rt 90
+        fd 100
+        setc 15
+        fd 10
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.canvas.get_drawings().len() == 3);
+        let svg = export::svg_document(&int.state.canvas);
+        assert!(svg.contains("<path d=\"M 300 200 L 300 150 L 400 150\""));
+        assert!(svg.contains("<path d=\"M 400 150 L 410 150\""));
+        let image = export::render_image(&int.state.canvas).unwrap();
+        assert!(image.get_pixel(300, 175).0 == [0, 0, 0, 255]);
+        assert!(image.get_pixel(301, 175).0 == [255, 255, 255, 255]);
+        assert!(image.get_pixel(200, 100).0 == [255, 255, 255, 255]);
+    }
+
+    fn painted_pixels(canvas: &CanvasState) -> Vec<Point> {
+        let mut pixels = vec![];
+        for y in -200..200 {
+            for x in -300..300 {
+                let point = Point::new(x as f32, y as f32);
+                if canvas.has_line_at_point(&point) {
+                    pixels.push(point);
+                }
+            }
+        }
+        pixels
+    }
+
+    fn distance_to_line(point: &Point, line: &Line) -> f32 {
+        let (dx, dy) = (line.end.x - line.start.x, line.end.y - line.start.y);
+        let t =
+            ((point.x - line.start.x) * dx + (point.y - line.start.y) * dy) / (dx * dx + dy * dy);
+        let t = t.clamp(0.0, 1.0);
+        let (x, y) = (line.start.x + t * dx, line.start.y + t * dy);
+        ((point.x - x).powi(2) + (point.y - y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn line_pixels_at_many_angles() {
+        for width in [1.0, 2.0, 5.0] {
+            for angle in (0..360).step_by(15) {
+                let radians = (angle as f32).to_radians();
+                let start = Point::new(-10.0, 5.0);
+                let end = Point::new(-10.0 + 80.0 * radians.cos(), 5.0 + 80.0 * radians.sin());
+                let line = Line::new(start.clone(), end.clone(), Color::Number(15.0), width);
+                let mut canvas = CanvasState::new();
+                canvas.add_line(line.clone());
+
+                // Nothing is painted away from the line.
+                let pixels = painted_pixels(&canvas);
+                let radius = (width - 1.0) / 2.0;
+                for pixel in &pixels {
+                    assert!(distance_to_line(pixel, &line) <= radius + 0.71);
+                    assert!(canvas.color_at_point(pixel) == Color::Number(15.0));
+                }
+
+                // Any point the pen passes over is on a painted pixel, so
+                // there are no gaps, even at the ends.
+                let (dx, dy) = (end.x - start.x, end.y - start.y);
+                let (nx, ny) = (-dy / 80.0, dx / 80.0);
+                for step in 0..=320 {
+                    let t = step as f32 / 320.0;
+                    for offset in [-radius, 0.0, radius] {
+                        let x = start.x + t * dx + offset * nx;
+                        let y = start.y + t * dy + offset * ny;
+                        assert!(canvas.has_line_at_point(&Point::new(x, y)));
+                    }
+                }
+
+                // The pen width is about the same at any angle.
+                let count = pixels.len() as f32;
+                let least = dx.abs().max(dy.abs()) * width * 0.9;
+                assert!(count >= least && count <= (82.0 + width) * (width + 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn line_pixel_widths() {
+        let mut canvas = CanvasState::new();
+        canvas.add_line(Line::new(
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 50.0),
+            Color::Number(15.0),
+            1.0,
+        ));
+        assert!(canvas.has_line_at_point(&Point::new(0.0, 20.0)));
+        assert!(!canvas.has_line_at_point(&Point::new(-1.0, 20.0)));
+        assert!(!canvas.has_line_at_point(&Point::new(1.0, 20.0)));
+        assert!(canvas.has_line_at_point(&Point::new(0.0, 50.0)));
+        assert!(!canvas.has_line_at_point(&Point::new(0.0, 51.0)));
+
+        // A diagonal line is one pixel in each column.
+        let mut canvas = CanvasState::new();
+        canvas.add_line(Line::new(
+            Point::new(0.0, 0.0),
+            Point::new(40.0, 40.0),
+            Color::Number(15.0),
+            1.0,
+        ));
+        assert!(painted_pixels(&canvas).len() == 41);
+
+        // A wide pen covers that many rows, with a rounded end.
+        let mut canvas = CanvasState::new();
+        canvas.add_line(Line::new(
+            Point::new(-50.0, 0.0),
+            Point::new(50.0, 0.0),
+            Color::Number(15.0),
+            4.0,
+        ));
+        let rows = (-5..=5)
+            .filter(|y| canvas.has_line_at_point(&Point::new(10.0, *y as f32)))
+            .count();
+        assert!(rows == 4);
+        assert!(canvas.has_line_at_point(&Point::new(51.0, 0.0)));
+        assert!(!canvas.has_line_at_point(&Point::new(51.0, 2.0)));
+        assert!(!canvas.has_line_at_point(&Point::new(53.0, 0.0)));
+    }
+
+    #[test]
+    fn colorunder_along_trails() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc 15
+        seth 20
+        fd 90
+        bk 45
+        make \"result1 colorunder
+        pu
+        rt 90
+        fd 3
+        make \"result2 colorunder
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(15.0)));
+        assert!(int.state.data.get_variable("result2") != Some(&Token::Number(15.0)));
+    }
+
+    #[test]
+    fn fills() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc 15
+        repeat 4 [fd 40 rt 90]
+        pu
+        setpos [20 20]
+        setc 25
+        fill
+        setpos [-100 -100]
+        seth 0
+        setc 45
+        beginfill
+        fd 30
+        rt 90
+        fd 30
+        rt 90
+        fd 30
+        endfill
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let canvas = &int.state.canvas;
+        assert!(canvas.get_drawings().len() == 6);
+
+        // The square is filled inside its sides, and nowhere else.
+        assert!(canvas.color_at_point(&Point::new(20.0, 20.0)) == Color::Number(25.0));
+        assert!(canvas.color_at_point(&Point::new(1.0, 39.0)) == Color::Number(25.0));
+        assert!(canvas.color_at_point(&Point::new(40.0, 20.0)) == Color::Number(15.0));
+        assert!(canvas.color_at_point(&Point::new(60.0, 20.0)) != Color::Number(25.0));
+
+        // The path is closed back to where it began.
+        assert!(canvas.color_at_point(&Point::new(-90.0, -90.0)) == Color::Number(45.0));
+        assert!(canvas.color_at_point(&Point::new(-71.0, -99.0)) == Color::Number(45.0));
+        assert!(canvas.color_at_point(&Point::new(-110.0, -90.0)) != Color::Number(45.0));
+        assert!(canvas.color_at_point(&Point::new(-90.0, -60.0)) != Color::Number(45.0));
+
+        assert!(int.interpret("endfill").is_err());
+        let svg = export::svg_document(&int.state.canvas);
+        assert!(svg.contains("h 39 v 1 h -39 z"));
+    }
+
+    #[test]
+    fn rgba_colors() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc [120 60 30]
+        make \"result1 color
+        fd 40
+        bk 20
+        make \"result2 colorunder
+        setc \"grey
+        make \"result3 color
+        setc 15
+        make \"result4 color
+        pu
+        setpos [50 0]
+        pd
+        setc [255 0 0 128]
+        fd 40
+        make \"result5 colorunder
+        setbg [10 20 30]
+        pu
+        setpos [-100 0]
+        make \"result6 colorunder
+        make \"result7 bg
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let list = |text: &str| Some(Token::List(List::parse(text)));
+        assert!(int.state.data.get_variable("result1").cloned() == list("120 60 30"));
+        assert!(int.state.data.get_variable("result2").cloned() == list("120 60 30"));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Number(5.0)));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(15.0)));
+        assert!(int.state.data.get_variable("result5").cloned() == list("255 0 0 128"));
+        assert!(int.state.data.get_variable("result6").cloned() == list("10 20 30"));
+        assert!(int.state.data.get_variable("result7").cloned() == list("10 20 30"));
+
+        // See-through colors are mixed with the pixels underneath.
+        int.interpret("setpos [0 30] pd setc [0 0 255 128] setpos [100 30]")
+            .unwrap();
+        let canvas = &int.state.canvas;
+        assert!(canvas.color_at_point(&Point::new(0.0, 30.0)) == Color::Rgba([60, 30, 143, 255]));
+        assert!(canvas.color_at_point(&Point::new(50.0, 30.0)) == Color::Rgba([85, 0, 170, 192]));
+
+        let svg = export::svg_document(canvas);
+        assert!(svg.contains("fill=\"rgb(10,20,30)\""));
+        assert!(svg.contains("stroke=\"rgb(0,0,255)\" stroke-opacity=\"0.5019608\""));
+        let image = export::render_image(canvas).unwrap();
+        assert!(image.get_pixel(300, 169).0 == [60, 30, 143, 255]);
+
+        assert!(int.interpret("setc [1 2]").is_err());
+        assert!(int.interpret("setc [1 2 300]").is_err());
+        assert!(int.interpret("setc \"nocolor").is_err());
+    }
+
+    #[test]
+    fn palette_colors() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc \"red
+        make \"result1 color
+        fd 20
+        bk 10
+        make \"result2 colorunder
+        setbg \"black
+        make \"result3 bg
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(15.0)));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::Number(15.0)));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Number(9.0)));
+
+        // Numbers go through the palette in bands of ten shades.
+        assert!(Color::WHITE.to_rgba() == [255, 255, 255, 255]);
+        assert!(Color::BLACK.to_rgba() == [0, 0, 0, 255]);
+        assert!(Color::Number(15.0).to_rgba() == [255, 0, 0, 255]);
+        assert!(Color::Number(10.0).to_rgba() == [42, 0, 0, 255]);
+        assert!(Color::Number(19.0).to_rgba() == [255, 204, 204, 255]);
+        assert!(Color::Number(105.0).to_rgba() == [0, 0, 255, 255]);
+        assert!(Color::Number(155.0).to_rgba() == Color::Number(15.0).to_rgba());
+        assert!(Color::from_name("Sky") == Some(Color::Number(95.0)));
+    }
+
+    #[test]
+    fn pen_modes() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setc 15
+        fd 50
+        penerase
+        bk 50
+        make \"result1 penmode
+        pu
+        setpos [-40 20]
+        seth 90
+        penpaint
+        fd 80
+        pu
+        setpos [20 0]
+        seth 0
+        setc 45
+        penreverse
+        fd 40
+        make \"result2 colorunder
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(
+            int.state.data.get_variable("result1") == Some(&Token::Word(String::from("erase")))
+        );
+        assert!(int.state.data.get_variable("result2") == Some(&Token::Number(45.0)));
+        let canvas = &int.state.canvas;
+        assert!(!canvas.has_line_at_point(&Point::new(0.0, 25.0)));
+        assert!(canvas.color_at_point(&Point::new(0.0, 25.0)) == Color::WHITE);
+
+        // Reversing swaps the background with the pen color, and changes
+        // other colors too.
+        assert!(canvas.color_at_point(&Point::new(20.0, 10.0)) == Color::Number(45.0));
+        let crossing = canvas.color_at_point(&Point::new(20.0, 20.0));
+        assert!(crossing != Color::Number(15.0) && crossing != Color::Number(45.0));
+        assert!(crossing != Color::WHITE);
+
+        // Reversing again puts everything back.
+        int.interpret("bk 40").unwrap();
+        let canvas = &int.state.canvas;
+        assert!(!canvas.has_line_at_point(&Point::new(20.0, 10.0)));
+        assert!(canvas.color_at_point(&Point::new(20.0, 20.0)) == Color::Number(15.0));
+
+        // The corner of a reversed line that turns is only reversed once.
+        let code = "
+        clean
+        pu
+        setpos [0 0]
+        seth 0
+        setc 15
+        penreverse
+        fd 20
+        rt 90
+        fd 20
+        ";
+        int.interpret(code).unwrap();
+        let canvas = &int.state.canvas;
+        for point in [
+            [0.0, 20.0],
+            [0.0, 19.0],
+            [1.0, 20.0],
+            [0.0, 10.0],
+            [10.0, 20.0],
+        ] {
+            let point = Point::new(point[0], point[1]);
+            assert!(canvas.color_at_point(&point) == Color::Number(15.0));
+        }
+    }
+
+    #[test]
+    fn pen_patterns_caps_and_joins() {
+        let run = |code: &str| {
+            let mut int = Interpreter::new();
+            let code = format!("newturtle \"t1 tto \"t1 {}", code);
+            match int.interpret(&code) {
+                Ok(..) => (),
+                Err(err) => panic!("test failed: {}", err),
+            };
+            int
+        };
+
+        // Dashes carry on from one line to the next.
+        let int = run("setpenpattern [5 5] fd 20 fd 20");
+        let canvas = &int.state.canvas;
+        let painted: Vec<bool> = [2.0, 7.0, 12.0, 17.0, 22.0, 27.0, 32.0]
+            .iter()
+            .map(|y| canvas.has_line_at_point(&Point::new(0.0, *y)))
+            .collect();
+        assert!(painted == [true, false, true, false, true, false, true]);
+        let svg = export::svg_document(canvas);
+        assert!(svg.contains("stroke-dasharray=\"5 5\" stroke-dashoffset=\"0\""));
+
+        // Ends of lines.
+        let ends = |cap: &str| {
+            let int = run(&format!("setpensize 5 setlinecap \"{} seth 90 fd 20", cap));
+            (21..=23)
+                .map(|x| {
+                    int.state
+                        .canvas
+                        .has_line_at_point(&Point::new(x as f32, 0.0))
+                })
+                .collect::<Vec<bool>>()
+        };
+        assert!(ends("round") == [true, true, false]);
+        assert!(ends("butt") == [false, false, false]);
+        assert!(ends("square") == [true, true, false]);
+
+        // Corners, on the outside of a left turn at [30 0].
+        let corner = |join: &str| {
+            let code = format!(
+                "setpensize 9 setlinecap \"butt setlinejoin \"{} seth 90 fd 30 lt 90 fd 30",
+                join
+            );
+            let int = run(&code);
+            [Point::new(33.0, -3.0), Point::new(34.0, -4.0)]
+                .iter()
+                .map(|point| int.state.canvas.has_line_at_point(point))
+                .collect::<Vec<bool>>()
+        };
+        assert!(corner("miter") == [true, true]);
+        assert!(corner("round") == [true, false]);
+        assert!(corner("bevel") == [false, false]);
+
+        let mut int = run("");
+        assert!(int.interpret("setpenpattern [-1 2]").is_err());
+        assert!(int.interpret("setpenpattern [0 0]").is_err());
+        assert!(int.interpret("setlinecap \"flat").is_err());
+        assert!(int.interpret("setlinejoin \"sharp").is_err());
+    }
+
+    #[test]
+    fn curves() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        setpensize 3
+        setc 15
+        circle 50
+        pu
+        setpos [-200 0]
+        pd
+        setc 25
+        arc 90 50
+        pu
+        setpos [200 0]
+        pd
+        setc 35
+        ellipse 30 60
+        pu
+        setpos [0 -150]
+        pd
+        setc 45
+        beginfill
+        curveto [0 -100] [50 -100] [50 -150]
+        endfill
+        make \"result pos
+        make \"result2 heading
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let canvas = &int.state.canvas;
+        assert!(canvas.get_drawings().len() == 5);
+
+        // Circles go all the way round the turtle, which stays at the center.
+        for (x, y) in [
+            (50.0, 0.0),
+            (0.0, 50.0),
+            (-50.0, 0.0),
+            (0.0, -50.0),
+            (35.36, 35.36),
+        ] {
+            assert!(canvas.color_at_point(&Point::new(x, y)) == Color::Number(15.0));
+        }
+        assert!(canvas.color_at_point(&Point::new(0.0, 0.0)) != Color::Number(15.0));
+
+        // Arcs start ahead of the turtle and go clockwise.
+        assert!(canvas.color_at_point(&Point::new(-200.0, 50.0)) == Color::Number(25.0));
+        assert!(canvas.color_at_point(&Point::new(-164.64, 35.36)) == Color::Number(25.0));
+        assert!(canvas.color_at_point(&Point::new(-150.0, 0.0)) == Color::Number(25.0));
+        assert!(canvas.color_at_point(&Point::new(-235.36, 35.36)) != Color::Number(25.0));
+
+        // The first radius of an ellipse is across the heading.
+        assert!(canvas.color_at_point(&Point::new(230.0, 0.0)) == Color::Number(35.0));
+        assert!(canvas.color_at_point(&Point::new(200.0, 60.0)) == Color::Number(35.0));
+        assert!(canvas.color_at_point(&Point::new(200.0, 30.0)) != Color::Number(35.0));
+
+        // The turtle follows a curve to its end, and fills take in its shape.
+        assert!(
+            int.state.data.get_variable("result") == Some(&Token::List(List::parse("50 -150")))
+        );
+        assert!(int.state.data.get_variable("result2") == Some(&Token::Number(0.0)));
+        assert!(canvas.color_at_point(&Point::new(25.0, -112.5)) == Color::Number(45.0));
+        assert!(canvas.color_at_point(&Point::new(25.0, -130.0)) == Color::Number(45.0));
+        assert!(canvas.color_at_point(&Point::new(25.0, -105.0)) != Color::Number(45.0));
+
+        let svg = export::svg_document(&int.state.canvas);
+        assert!(svg.matches(" Z\"").count() == 2);
+        assert!(int.interpret("curveto [0 0] [1 1] 5").is_err());
+
+        // Huge curves are split into a limited number of pieces.
+        assert!(int.interpret("circle 1000000000").is_ok());
+        assert!(int.interpret("curveto [0 0] [1e30 1e30] [0 10]").is_ok());
+        for drawing in &int.state.canvas.get_drawings()[5..] {
+            let Drawing::Curve(curve) = drawing else {
+                panic!("test failed: expected a curve");
+            };
+            assert!(curve.points.len() <= 10001);
+        }
+    }
 }
@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod tests {
     use crate::interpreter::language::lexer::Lexer;
-    use crate::interpreter::language::structure::Command;
-    use crate::interpreter::language::token::Token;
+    use crate::interpreter::language::structure::{BlockCache, Command, Params, ProcedureParams};
+    use crate::interpreter::language::token::{List, Token};
+    use std::sync::Arc;
 
     fn read_code_into_tokens(code: &str) -> Vec<Token> {
         let mut lexer = Lexer::new();
@@ -25,7 +26,7 @@ mod tests {
             Token::Command(Command::right(), vec![Token::Number(90.0)]),
             Token::Command(Command::back(), vec![Token::Number(50.0)]),
             Token::Command(Command::left(), vec![Token::Number(90.0)]),
-            Token::Command(Command::setpos(), vec![Token::List(String::from("20 -30"))]),
+            Token::Command(Command::setpos(), vec![Token::List(List::parse("20 -30"))]),
             Token::Command(Command::forward(), vec![Token::Number(-50.0)]),
             Token::Command(Command::setheading(), vec![Token::Number(180.0)]),
         ];
@@ -47,7 +48,7 @@ mod tests {
         let tokens = read_code_into_tokens(code);
 
         let expect = vec![
-            Token::Command(Command::setpos(), vec![Token::List(String::from("-15 45"))]),
+            Token::Command(Command::setpos(), vec![Token::List(List::parse("-15 45"))]),
             Token::Command(Command::back(), vec![Token::Number(30.0)]),
             Token::Command(Command::forward(), vec![Token::Number(50.0)]),
             Token::Command(Command::left(), vec![Token::Number(90.0)]),
@@ -98,12 +99,12 @@ mod tests {
                         Command::equal(),
                         vec![Token::Variable(String::from("var")), Token::Number(45.0)],
                     ),
-                    Token::List(String::from("bk 20")),
+                    Token::List(List::parse("bk 20")),
                 ],
             ),
             Token::Command(
                 Command::repeat(),
-                vec![Token::Number(3.0), Token::List(String::from("fd :var"))],
+                vec![Token::Number(3.0), Token::List(List::parse("fd :var"))],
             ),
         ];
 
@@ -130,16 +131,21 @@ mod tests {
                 Command::to(),
                 vec![Token::Procedure(
                     String::from("function1"),
-                    vec![],
+                    ProcedureParams::default(),
                     String::from("code"),
+                    3,
                 )],
             ),
             Token::Command(
                 Command::to(),
                 vec![Token::Procedure(
                     String::from("function2"),
-                    vec![String::from("param")],
+                    ProcedureParams {
+                        required: vec![String::from("param")],
+                        ..Default::default()
+                    },
                     String::from("code"),
+                    8,
                 )],
             ),
         ];
@@ -163,7 +169,7 @@ mod tests {
                 Command::make(),
                 vec![
                     Token::Word(String::from("lst")),
-                    Token::List(String::from("one two three")),
+                    Token::List(List::parse("one two three")),
                 ],
             ),
             Token::Command(
@@ -174,21 +180,21 @@ mod tests {
                 Command::list(),
                 vec![
                     Token::Variable(String::from("lst")),
-                    Token::List(String::from("four five")),
+                    Token::List(List::parse("four five")),
                 ],
             ),
             Token::Command(
                 Command::dotimes(),
                 vec![
-                    Token::List(String::from("i 5")),
-                    Token::List(String::from("show :i")),
+                    Token::List(List::parse("i 5")),
+                    Token::List(List::parse("show :i")),
                 ],
             ),
             Token::Command(
                 Command::dolist(),
                 vec![
-                    Token::List(String::from("i [one two :var]")),
-                    Token::List(String::from("show :i")),
+                    Token::List(List::parse("i [one two :var]")),
+                    Token::List(List::parse("show :i")),
                 ],
             ),
         ];
@@ -230,7 +236,7 @@ mod tests {
                             Token::Word(String::from("hello")),
                         ],
                     ),
-                    Token::List(String::from("show \"yes")),
+                    Token::List(List::parse("show \"yes")),
                 ],
             ),
             Token::Command(
@@ -252,7 +258,7 @@ mod tests {
                             ),
                         ],
                     ),
-                    Token::List(String::from("show \"yes")),
+                    Token::List(List::parse("show \"yes")),
                 ],
             ),
             Token::Command(
@@ -265,15 +271,15 @@ mod tests {
                             vec![Token::Variable(String::from("var2")), Token::Number(5.0)],
                         )],
                     ),
-                    Token::List(String::from("show \"yes")),
-                    Token::List(String::from("show \"no")),
+                    Token::List(List::parse("show \"yes")),
+                    Token::List(List::parse("show \"no")),
                 ],
             ),
             Token::Command(
                 Command::carefully(),
                 vec![
-                    Token::List(String::from("show :noexist")),
-                    Token::List(String::from("show \"no")),
+                    Token::List(List::parse("show :noexist")),
+                    Token::List(List::parse("show \"no")),
                 ],
             ),
         ];
@@ -370,8 +376,8 @@ mod tests {
                 vec![Token::Command(
                     Command::product(),
                     vec![
-                        Token::Command(Command::paren(), vec![Token::List(String::from("5 + 2"))]),
-                        Token::Command(Command::paren(), vec![Token::List(String::from("3 - 6"))]),
+                        Token::Command(Command::paren(), vec![Token::List(List::parse("5 + 2"))]),
+                        Token::Command(Command::paren(), vec![Token::List(List::parse("3 - 6"))]),
                     ],
                 )],
             ),
@@ -382,11 +388,11 @@ mod tests {
                     vec![
                         Token::Command(
                             Command::paren(),
-                            vec![Token::List(String::from("(minus 4) + 3"))],
+                            vec![Token::List(List::parse("(minus 4) + 3"))],
                         ),
                         Token::Command(
                             Command::paren(),
-                            vec![Token::List(String::from("exp 6 - 1"))],
+                            vec![Token::List(List::parse("exp 6 - 1"))],
                         ),
                     ],
                 )],
@@ -416,14 +422,14 @@ mod tests {
             Token::Command(Command::right(), vec![Token::Number(90.0)]),
             Token::Command(Command::left(), vec![Token::Number(45.0)]),
             Token::Command(
-                Command::list(),
+                Command::sentence(),
                 vec![
                     Token::Word(String::from("one")),
                     Token::Word(String::from("two")),
                 ],
             ),
             Token::Command(
-                Command::list(),
+                Command::sentence(),
                 vec![
                     Token::Word(String::from("one")),
                     Token::Word(String::from("two")),
@@ -438,4 +444,56 @@ mod tests {
 
         assert_eq!(tokens, expect);
     }
+
+    #[test]
+    fn parse_code_once() {
+        let code = "repeat 4 [fd 50 rt 90] ; square";
+        let mut lexer = Lexer::new();
+        let cache = BlockCache::default();
+        let block1 = lexer.parse_cached(code, false, &cache).unwrap();
+        let block2 = lexer.parse_cached(code, false, &cache).unwrap();
+
+        let expect = vec![Token::Command(
+            Command::repeat(),
+            vec![Token::Number(4.0), Token::List(List::parse("fd 50 rt 90"))],
+        )];
+
+        assert_eq!(block1.statements, expect);
+        assert!(Arc::ptr_eq(&block1, &block2));
+
+        // Lists written in the source keep the code they were read into.
+        let Token::Command(_, args) = &block1.statements[0] else {
+            panic!("expected a command");
+        };
+        let Token::List(body) = &args[1] else {
+            panic!("expected a list");
+        };
+        let block3 = lexer.parse_list(body, false).unwrap();
+        let block4 = lexer.parse_list(&body.clone(), false).unwrap();
+        assert!(Arc::ptr_eq(&block3, &block4));
+    }
+
+    #[test]
+    fn parse_code_again_after_define() {
+        let code = "fd 10 spiral";
+        let mut lexer = Lexer::new();
+        let cache = BlockCache::default();
+        let block1 = lexer.parse_cached(code, false, &cache).unwrap();
+
+        // Only definitions of names the code uses make it read again.
+        lexer
+            .define("hexagon", Params::Fixed(1), |_, _, _| Ok(Token::Void))
+            .unwrap();
+        let block2 = lexer.parse_cached(code, false, &cache).unwrap();
+        assert!(Arc::ptr_eq(&block1, &block2));
+
+        lexer
+            .define("spiral", Params::None, |_, _, _| Ok(Token::Void))
+            .unwrap();
+        let block3 = lexer.parse_cached(code, false, &cache).unwrap();
+        assert!(!Arc::ptr_eq(&block1, &block3));
+        assert!(
+            matches!(&block3.statements[1], Token::Command(command, _) if command.is("spiral"))
+        );
+    }
 }
@@ -1,7 +1,40 @@
 #[cfg(test)]
 mod tests {
+    use crate::interpreter::event::{InputEvent, UiContext, UiEvent, UiEventHandler};
     use crate::interpreter::interpreter::Interpreter;
-    use crate::interpreter::language::token::Token;
+    use crate::interpreter::language::token::{List, Token};
+    use crate::interpreter::util::error::{describe_error, is_interrupt};
+    use std::any::Any;
+    use std::collections::HashSet;
+    use std::error::Error;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // Keeps every event the interpreter sends to the UI.
+    struct RecordingHandler {
+        events: Vec<UiEvent>,
+    }
+
+    impl UiEventHandler for RecordingHandler {
+        fn handle_ui_event(&mut self, _ctx: Arc<Mutex<dyn UiContext>>, event: UiEvent) {
+            self.events.push(event);
+        }
+    }
+
+    struct TestContext;
+
+    impl UiContext for TestContext {
+        fn update_ui(&self) {}
+
+        fn load_image(
+            &self,
+            _name: Box<str>,
+            _path: String,
+        ) -> Result<Box<dyn Any>, Box<dyn Error>> {
+            Err(Box::from("no images in tests"))
+        }
+    }
 
     #[test]
     fn math_operators() {
@@ -28,6 +61,52 @@ mod tests {
         assert!(int.state.data.get_variable("result6") == Some(&Token::Number(0.0)));
     }
 
+    #[test]
+    fn operator_precedence() {
+        let code = "
+        make \"x 4
+        make \"result1 2 * 3 + 4
+        make \"result2 2 + 3 * 4
+        make \"result3 10 - 4 - 3
+        make \"result4 64 / 4 / 2
+        make \"result5 2 ^ 3 ^ 2
+        make \"result6 2 * 3 ^ 2
+        make \"result7 17 % 5 * 2
+        make \"result8 -:x + 10
+        make \"result9 -:x ^ 2
+        make \"result10 3 - -:x
+        make \"result11 (2 + 3) * 4
+        make \"result12 1 + 2 = 3
+        make \"result13 2 * 3 > 5 + 1
+        make \"result14 :x >= 4
+        make \"result15 :x <= 3
+        make \"result16 :x <> 2 + 2
+        make \"result17 sum 1 2 * 3
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(10.0)));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::Number(14.0)));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Number(3.0)));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(8.0)));
+        assert!(int.state.data.get_variable("result5") == Some(&Token::Number(512.0)));
+        assert!(int.state.data.get_variable("result6") == Some(&Token::Number(18.0)));
+        assert!(int.state.data.get_variable("result7") == Some(&Token::Number(4.0)));
+        assert!(int.state.data.get_variable("result8") == Some(&Token::Number(6.0)));
+        assert!(int.state.data.get_variable("result9") == Some(&Token::Number(-16.0)));
+        assert!(int.state.data.get_variable("result10") == Some(&Token::Number(7.0)));
+        assert!(int.state.data.get_variable("result11") == Some(&Token::Number(20.0)));
+        assert!(int.state.data.get_variable("result12") == Some(&Token::Boolean(true)));
+        assert!(int.state.data.get_variable("result13") == Some(&Token::Boolean(false)));
+        assert!(int.state.data.get_variable("result14") == Some(&Token::Boolean(true)));
+        assert!(int.state.data.get_variable("result15") == Some(&Token::Boolean(false)));
+        assert!(int.state.data.get_variable("result16") == Some(&Token::Boolean(false)));
+        assert!(int.state.data.get_variable("result17") == Some(&Token::Number(7.0)));
+    }
+
     #[test]
     fn list_processing() {
         let code = "
@@ -43,14 +122,624 @@ mod tests {
         };
         assert!(
             int.state.data.get_variable("result1")
-                == Some(&Token::List(String::from("one two three four five")))
+                == Some(&Token::List(List::parse("one two three four five")))
         );
         assert!(
             int.state.data.get_variable("result2")
-                == Some(&Token::List(String::from("two three four")))
+                == Some(&Token::List(List::parse("two three four")))
         );
         assert!(
             int.state.data.get_variable("result3") == Some(&Token::Word(String::from("three")))
         );
     }
+
+    #[test]
+    fn procedures() {
+        let code = "
+        to double :num
+        output :num * 2
+        end
+
+        to countdown :num
+        make \"calls :calls + 1
+        if :num > 0 [countdown :num - 1]
+        end
+
+        make \"result1 double 21
+        make \"calls 0
+        countdown 5
+        make \"total 0
+        repeat 3 [make \"total :total + double 1]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(42.0)));
+        assert!(int.state.data.get_variable("calls") == Some(&Token::Number(6.0)));
+        assert!(int.state.data.get_variable("total") == Some(&Token::Number(6.0)));
+    }
+
+    #[test]
+    fn higher_order_lists() {
+        let code = "
+        to double :num
+        output :num * 2
+        end
+
+        make \"num 100
+        make \"result1 map [? * 2] [1 2 3]
+        make \"result2 map \"double [4 5]
+        make \"result3 (map [?1 + ?2] [1 2] [10 20])
+        make \"result4 filter [[n] :n > 2] [1 3 2 4]
+        make \"result5 reduce \"sum [1 2 3 4]
+        make \"result6 reduce [?1 - ?2] [1 2 3]
+        make \"result7 find [? > 2] [1 3 5]
+        make \"result8 apply \"sum [3 4]
+        make \"result9 (invoke [[a b] :a * :b] 6 7)
+        make \"total 0
+        foreach [1 2 3] [make \"total :total + ?]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::List(List::parse("2 4 6"))));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::List(List::parse("8 10"))));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::List(List::parse("11 22"))));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::List(List::parse("3 4"))));
+        assert!(int.state.data.get_variable("result5") == Some(&Token::Number(10.0)));
+        assert!(int.state.data.get_variable("result6") == Some(&Token::Number(2.0)));
+        assert!(int.state.data.get_variable("result7") == Some(&Token::Number(3.0)));
+        assert!(int.state.data.get_variable("result8") == Some(&Token::Number(7.0)));
+        assert!(int.state.data.get_variable("result9") == Some(&Token::Number(42.0)));
+        assert!(int.state.data.get_variable("total") == Some(&Token::Number(6.0)));
+        // Inputs to procedures and templates are local.
+        assert!(int.state.data.get_variable("num") == Some(&Token::Number(100.0)));
+    }
+
+    #[test]
+    fn reporter_conditionals() {
+        let code = "
+        to sign :num
+        if :num < 0 [output \"negative]
+        output ifelse :num = 0 [\"zero] [\"positive]
+        end
+
+        to findeven :items
+        foreach :items [if (remainder ? 2) = 0 [output ?]]
+        output \"none
+        end
+
+        make \"result1 sign -5
+        make \"result2 sign 0
+        make \"result3 sign 7
+        make \"result4 run [sum 1 2]
+        make \"result5 findeven [1 3 4 6]
+        make \"result6 findeven [1 3]
+        make \"result7 runresult [sum 1 2]
+        make \"result8 runresult [make \"unused 1]
+        make \"result9 (ifelse 1 > 2 [10] [20]) + 1
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let word = |text: &str| Some(Token::Word(text.to_string()));
+        assert!(int.state.data.get_variable("result1").cloned() == word("negative"));
+        assert!(int.state.data.get_variable("result2").cloned() == word("zero"));
+        assert!(int.state.data.get_variable("result3").cloned() == word("positive"));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(3.0)));
+        assert!(int.state.data.get_variable("result5") == Some(&Token::Number(4.0)));
+        assert!(int.state.data.get_variable("result6").cloned() == word("none"));
+        assert!(int.state.data.get_variable("result7") == Some(&Token::List(List::parse("3"))));
+        assert!(int.state.data.get_variable("result8") == Some(&Token::List(List::parse(""))));
+        assert!(int.state.data.get_variable("result9") == Some(&Token::Number(21.0)));
+    }
+
+    #[test]
+    fn loops() {
+        let code = "
+        to countdown :num
+        for [i :num 1] [if :i < 3 [stop] make \"seen lput :i :seen]
+        make \"seen lput \"unreached :seen
+        end
+
+        make \"i 100
+        make \"seen []
+        countdown 5
+        make \"odds []
+        for [i 1 10 2] [make \"odds lput :i :odds]
+        make \"counts []
+        repeat 2 [repeat 2 [make \"counts lput # :counts] make \"counts lput repcount :counts]
+        make \"num 0
+        while [:num < 5] [make \"num :num + 1]
+        until [:num < 0] [make \"num :num - 2]
+        make \"once 0
+        do.while [make \"once :once + 1] [false]
+        do.until [make \"once :once + 1] [:once > 3]
+        make \"outside repcount
+        forever [make \"ticks repcount if repcount = 3 [stop]]
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("seen") == Some(&Token::List(List::parse("5 4 3"))));
+        assert!(int.state.data.get_variable("i") == Some(&Token::Number(100.0)));
+        let odds = Token::List(List::parse("1 3 5 7 9"));
+        assert!(int.state.data.get_variable("odds") == Some(&odds));
+        let counts = Token::List(List::parse("1 2 1 1 2 2"));
+        assert!(int.state.data.get_variable("counts") == Some(&counts));
+        assert!(int.state.data.get_variable("num") == Some(&Token::Number(-1.0)));
+        assert!(int.state.data.get_variable("once") == Some(&Token::Number(4.0)));
+        assert!(int.state.data.get_variable("outside") == Some(&Token::Number(-1.0)));
+
+        // Stopping at the top of a forever ends it.
+        assert!(int.run_processes().is_ok());
+        assert!(int.state.data.get_variable("ticks") == Some(&Token::Number(3.0)));
+        assert!(int.state.processes.is_empty());
+    }
+
+    #[test]
+    fn procedure_inputs() {
+        let code = "
+        to poly :side [:sides 4] [:angle 360 / :sides] [:rest]
+        output (list :side :sides :angle :rest)
+        end
+
+        make \"sides 100
+        make \"result1 poly 10
+        make \"result2 (poly 10 6)
+        make \"result3 (poly 10 5 72 \"extra 1)
+        make \"result4 count poly 10
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let result1 = Token::List(List::parse("10 4 90 []"));
+        let result2 = Token::List(List::parse("10 6 60 []"));
+        let result3 = Token::List(List::parse("10 5 72 [extra 1]"));
+        assert!(int.state.data.get_variable("result1") == Some(&result1));
+        assert!(int.state.data.get_variable("result2") == Some(&result2));
+        assert!(int.state.data.get_variable("result3") == Some(&result3));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(4.0)));
+        assert!(int.state.data.get_variable("sides") == Some(&Token::Number(100.0)));
+        assert!(int.interpret("make \"result5 (poly)").is_err());
+    }
+
+    #[test]
+    fn property_lists() {
+        let code = "
+        pprop \"board 1 \"x
+        pprop \"board 2 \"o
+        pprop \"board \"turn \"x
+        pprop \"board 1 \"o
+        remprop \"board 2
+        make \"result1 gprop \"board 1
+        make \"result2 gprop \"board 2
+        make \"result3 plist \"board
+        pprop \"other \"size 3
+        erpl
+        make \"result4 plist \"other
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let result1 = Token::Word(String::from("o"));
+        assert!(int.state.data.get_variable("result1") == Some(&result1));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::List(List::parse(""))));
+        let result3 = Token::List(List::parse("1 o turn x"));
+        assert!(int.state.data.get_variable("result3") == Some(&result3));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::List(List::parse(""))));
+    }
+
+    #[test]
+    fn arrays() {
+        let code = "
+        make \"board array 3
+        make \"same :board
+        setitem 2 :same \"x
+        make \"result1 item 2 :board
+        make \"result2 arraytolist :board
+        make \"zero (listtoarray [a b c] 0)
+        make \"result3 item 0 :zero
+        make \"grid mdarray [2 3]
+        mdsetitem [2 3] :grid 5
+        make \"result4 mditem [2 3] :grid
+        make \"result5 count mditem [1] :grid
+        make \"result6 array? :grid
+        make \"result7 equal? :board :same
+        make \"result8 equal? :board array 3
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let result1 = Token::Word(String::from("x"));
+        assert!(int.state.data.get_variable("result1") == Some(&result1));
+        let result2 = Token::List(List::parse("[] x []"));
+        assert!(int.state.data.get_variable("result2") == Some(&result2));
+        let result3 = Token::Word(String::from("a"));
+        assert!(int.state.data.get_variable("result3") == Some(&result3));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(5.0)));
+        assert!(int.state.data.get_variable("result5") == Some(&Token::Number(3.0)));
+        assert!(int.state.data.get_variable("result6") == Some(&Token::Boolean(true)));
+        assert!(int.state.data.get_variable("result7") == Some(&Token::Boolean(true)));
+        assert!(int.state.data.get_variable("result8") == Some(&Token::Boolean(false)));
+        assert!(int.interpret("setitem 4 :board 1").is_err());
+        assert!(int.interpret("setitem 1 :board :same").is_err());
+        assert!(int.interpret("make \"a array 1e19").is_err());
+        assert!(int.interpret("make \"a array -1").is_err());
+        assert!(int.interpret("make \"a array 2.5").is_err());
+        assert!(int.interpret("make \"a mdarray [100000 100000]").is_err());
+    }
+
+    #[test]
+    fn procedure_editing() {
+        let code = "
+        to square :size [:turns 4]
+        repeat :turns [fd :size rt 90]
+        end
+
+        define \"double [[num] [output :num * 2]]
+        make \"result1 double 21
+        make \"result2 (text \"square)
+        newtext \"label
+        label,
+        print \"hi
+        make \"result5 word text \"abc
+        copydef \"twice \"double
+        make \"result3 twice 4
+        copydef \"add \"sum
+        make \"result4 add 2 3
+        erase \"double
+        po \"square
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(42.0)));
+        let result2 = Token::List(List::parse(
+            "[size [turns 4]] [repeat :turns [fd :size rt 90]]",
+        ));
+        assert!(int.state.data.get_variable("result2") == Some(&result2));
+        // Without parentheses, text reports the text box even before a word.
+        assert!(
+            int.state.data.get_variable("result5") == Some(&Token::Word(String::from("hiabc")))
+        );
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Number(8.0)));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(5.0)));
+        let printout = int.state.data.get_procedure("square").unwrap().printout();
+        assert!(printout.starts_with("to square :size [:turns 4]\n"));
+        assert!(printout.ends_with("\nend"));
+
+        assert!(int.interpret("make \"x double 2").is_err());
+        assert!(int.interpret("erall make \"x twice 2").is_err());
+        assert!(int.state.data.get_all_procedures().is_empty());
+        // Copies of primitives are erased too, but not the primitives.
+        assert!(int.interpret("make \"x add 2 3").is_err());
+        assert!(int.interpret("make \"x sum 2 3").is_ok());
+        assert!(int.interpret("erase \"sum").is_err());
+    }
+
+    #[test]
+    fn trace_and_step() {
+        let code = "
+        to addup :a :b
+        make \"total :a + :b
+        output :total
+        end
+
+        trace \"addup
+        step [addup]
+        make \"result1 addup 2 3
+        untrace \"addup
+        ";
+        let mut int = Interpreter::new();
+        let handler = Arc::new(Mutex::new(RecordingHandler { events: vec![] }));
+        int.bind_ui_handler(handler.clone(), Arc::new(Mutex::new(TestContext)));
+        let (sender, receiver) = mpsc::channel();
+        int.bind_input_receiver(receiver);
+        thread::spawn(move || {
+            for _ in 0..2 {
+                thread::sleep(Duration::from_millis(100));
+                let _ = sender.send(InputEvent::KeyDown(String::from("enter")));
+            }
+        });
+        let start = Instant::now();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(5.0)));
+
+        // The program paused on both lines of addup, and waited for each enter.
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        let events = &handler.lock().unwrap().events;
+        let pauses = events
+            .iter()
+            .filter(|event| matches!(event, UiEvent::Paused(..)))
+            .count();
+        assert!(pauses == 2);
+        // Each enter went to a paused line rather than the key buffer.
+        assert!(!int.state.input.has_key());
+
+        // Tracing prints the call and its output around the stepped lines.
+        let printed: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                UiEvent::ConsolePrint(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let expected = vec![
+            "( addup 2 3 )",
+            "[addup] make \"total :a + :b",
+            "[addup] output :total",
+            "addup outputs 5",
+        ];
+        assert!(printed == expected);
+        assert!(!int.state.debug.is_traced("addup"));
+        assert!(int.state.debug.is_stepped("addup"));
+        assert!(int.interpret("unstep \"addup").is_ok());
+        assert!(!int.state.debug.is_stepped("addup"));
+    }
+
+    #[test]
+    fn breakpoints() {
+        let code = "
+        make \"x 1
+        make \"x 2
+        make \"x 3
+        ";
+        let mut int = Interpreter::new();
+        let (sender, receiver) = mpsc::channel();
+        int.bind_input_receiver(receiver);
+        int.state.debug.set_breakpoints(HashSet::from([2]));
+        thread::spawn(move || {
+            // Enter is a key for the program rather than a way to go on.
+            thread::sleep(Duration::from_millis(100));
+            let _ = sender.send(InputEvent::KeyDown(String::from("enter")));
+
+            // Step from the breakpoint to the next line, then stop there.
+            thread::sleep(Duration::from_millis(100));
+            let _ = sender.send(InputEvent::Step);
+            thread::sleep(Duration::from_millis(100));
+            let _ = sender.send(InputEvent::Interrupt);
+        });
+        match int.interpret(code) {
+            Err(err) if is_interrupt(&err) => (),
+            Err(err) => panic!("test failed: {}", err),
+            Ok(..) => panic!("test failed: program did not pause"),
+        };
+        assert!(int.state.data.get_variable("x") == Some(&Token::Number(1.0)));
+        assert!(int.state.input.has_key());
+        assert!(!int.state.debug.pause_requested);
+    }
+
+    #[test]
+    fn nested_lists() {
+        let code = "
+        make \"lst [a [b c] |d e| 3 true]
+        make \"result1 count :lst
+        make \"result2 item 1 :lst
+        make \"result3 first butfirst butfirst :lst
+        make \"result4 fput [x] butlast :lst
+        make \"result5 list :result2 \"f
+        make \"result6 sentence [a] [b [c]]
+        make \"result7 (se \"x :result2 [])
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(5.0)));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::List(List::parse("b c"))));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Word(String::from("d e"))));
+        assert!(
+            int.state.data.get_variable("result4")
+                == Some(&Token::List(List::parse("[x] a [b c] |d e| 3")))
+        );
+        assert!(
+            int.state.data.get_variable("result5") == Some(&Token::List(List::parse("[b c] f")))
+        );
+        assert!(
+            int.state.data.get_variable("result6") == Some(&Token::List(List::parse("a b [c]")))
+        );
+        assert!(int.state.data.get_variable("result7") == Some(&Token::List(List::parse("x b c"))));
+    }
+
+    #[test]
+    fn list_text_round_trip() {
+        let text = "fd 50 [rt 90 |a word|] -2.5 false ||";
+        let list = List::parse(text);
+        assert_eq!(list.len(), 6);
+        assert_eq!(list.to_text(), text);
+        assert_eq!(List::parse(&list.to_text()), list);
+        assert_eq!(
+            list.butfirst().butlast().to_text(),
+            "50 [rt 90 |a word|] -2.5 false"
+        );
+    }
+
+    #[test]
+    fn processes() {
+        let code = "
+        make \"log []
+        launch [make \"log lput \"p1 :log make \"log lput \"p2 :log]
+        make \"log lput \"m1 :log
+        make \"log lput \"m2 :log
+        make \"count 0
+        forever [make \"count :count + 1 if :count > 4 [stopall]]
+        make \"running processes
+        ";
+        let mut int = Interpreter::new();
+        match int.interpret(code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        let log = Token::List(List::parse("p1 m1 p2 m2"));
+        assert!(int.state.data.get_variable("log") == Some(&log));
+        let running = Token::List(List::parse(
+            "|make \"count :count + 1 if :count > 4 [stopall]|",
+        ));
+        assert!(int.state.data.get_variable("running") == Some(&running));
+
+        // The forever process runs until it stops everything.
+        assert!(int.run_processes().is_ok());
+        assert!(int.state.data.get_variable("count") == Some(&Token::Number(5.0)));
+        assert!(int.state.processes.is_empty());
+
+        // Stopping everything is a clean end of the program.
+        let result =
+            int.interpret_main("forever [make \"count :count + 1 if :count > 9 [stopall]]");
+        assert!(result.is_ok() && int.state.processes.is_empty());
+
+        // Processes can see the inputs of the procedure that launched them.
+        let code = "
+        to spin :n
+        forever [make \"total :total + :n if :total > 20 [stopall]]
+        end
+        make \"total 0
+        spin 7
+        ";
+        assert!(int.interpret_main(code).is_ok());
+        assert!(int.state.data.get_variable("total") == Some(&Token::Number(21.0)));
+    }
+
+    #[test]
+    fn process_errors() {
+        let code = "
+        newturtle \"t1
+        to spin
+        launch [rt 90 fd \"far]
+        end
+        spin
+        make \"after 1
+        ";
+        let mut int = Interpreter::new();
+        let Err(err) = int.interpret_main(code) else {
+            panic!("test failed: expected an error");
+        };
+
+        // The error ends the program, and is reported where the process was
+        // launched.
+        assert!(
+            describe_error(err.as_ref())
+                == "forward expected a number for input 0 in procedure spin, line 4"
+        );
+        assert!(int.state.data.get_variable("after") == Some(&Token::Number(1.0)));
+        assert!(int.state.processes.is_empty());
+    }
+
+    #[test]
+    fn procedures_read_once() {
+        let code = "
+        to walk
+        repeat 4 [fd 10 rt 90]
+        end
+        newturtle \"t1
+        tto \"t1
+        walk
+        ";
+        let mut int = Interpreter::new();
+        assert!(int.interpret(code).is_ok());
+        let block = int.state.data.get_procedure("walk").unwrap().block.get();
+        let Some(block1) = block else {
+            panic!("test failed: expected the body to be kept");
+        };
+
+        // Defining a procedure that the body doesn't use keeps what was read.
+        assert!(int
+            .interpret(
+                "to jump
+fd 20
+end
+jump walk"
+            )
+            .is_ok());
+        let block2 = int.state.data.get_procedure("walk").unwrap().block.get();
+        assert!(block2.is_some_and(|block2| Arc::ptr_eq(&block1, &block2)));
+    }
+
+    #[test]
+    fn trigger_errors() {
+        let code = "
+        newturtle \"t1
+        tto \"t1
+        to watch
+        when [xcor > \"far] [make \"seen 1]
+        end
+        watch
+        fd 10
+        ";
+        let mut int = Interpreter::new();
+        let Err(err) = int.interpret_main(code) else {
+            panic!("test failed: expected an error");
+        };
+
+        // Like an error in a process, the error ends the program, and is
+        // reported where the trigger was set up.
+        assert!(
+            describe_error(err.as_ref())
+                == "greater? expected a number for input 1 in procedure watch, line 5"
+        );
+        assert!(int.state.triggers.is_empty());
+    }
+
+    #[test]
+    fn nested_processes() {
+        // Processes take turns from inside their loops.
+        let code = "
+        make \"log []
+        launch [repeat 3 [make \"log lput \"a :log]]
+        launch [repeat 3 [make \"log lput \"b :log]]
+        ";
+        let mut int = Interpreter::new();
+        assert!(int.interpret(code).is_ok() && int.run_processes().is_ok());
+        let log = Token::List(List::parse("a b a b a b"));
+        assert!(int.state.data.get_variable("log") == Some(&log));
+
+        // A wait only holds up the process that waits.
+        let code = "
+        make \"log []
+        launch [wait 100 make \"log lput \"late :log]
+        launch [repeat 3 [make \"log lput \"b :log]]
+        wait 50
+        make \"log lput \"main :log
+        ";
+        assert!(int.interpret(code).is_ok() && int.run_processes().is_ok());
+        let log = Token::List(List::parse("b b b main late"));
+        assert!(int.state.data.get_variable("log") == Some(&log));
+
+        // Errors inside a procedure that a process runs are reported there.
+        let code = "
+        newturtle \"t1
+        to walk
+        repeat 2 [
+        fd \"far]
+        end
+        launch [walk]
+        ";
+        let Err(err) = int.interpret_main(code) else {
+            panic!("test failed: expected an error");
+        };
+        assert!(
+            describe_error(err.as_ref())
+                == "forward expected a number for input 0 in procedure walk, line 4"
+        );
+    }
 }
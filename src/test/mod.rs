@@ -1,4 +1,7 @@
+mod editor;
+mod errors;
 mod graphics;
 mod lexer;
 mod logic;
 mod performance;
+mod project;
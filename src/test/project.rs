@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::interpreter::Interpreter;
+    use crate::interpreter::language::token::{List, Token};
+    use crate::interpreter::state::object::{Color, Drawing, Object, Point};
+    use std::{env, fs, process};
+
+    #[test]
+    fn save_and_load_project() {
+        // Each run saves to its own file, so runs at the same time don't clash.
+        let directory = env::temp_dir().to_string_lossy().to_string();
+        let file_name = format!("macroworlds-test-{}.mwx", process::id());
+        let code = format!(
+            "
+        to double :num [:factor 2] [:rest]
+        output :num * :factor
+        end
+
+        chdir \"{}
+        turtlesown \"speed
+        newturtle \"t1
+        tto \"t1
+        setspeed 7
+        setc 15
+        fd 50
+        rt 90
+        pu
+        fd 10
+        setc 25
+        fill
+        pd
+        setc [128 64 0 100]
+        setpenpattern [4 2]
+        setlinecap \"square
+        fd 10
+        circle 5
+        setbg \"grey
+        newtext \"label
+        make \"items [1 [2 3] abc]
+        make \"name \"|two words|
+        pprop \"board 1 [x o]
+        make \"grid (array 2 0)
+        setitem 1 :grid list \"a array 1
+        saveproject \"{}
+        ",
+            directory, file_name
+        );
+        let mut int = Interpreter::new();
+        match int.interpret_main(&code) {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+
+        let load = format!(
+            "
+        to reload :file
+        loadproject :file
+        make \"result10 :file
+        end
+
+        chdir \"{}
+        reload \"{}
+        tto \"t1
+        make \"result1 double 21
+        make \"result2 speed
+        make \"result3 heading
+        make \"result4 (double 5 3 \"extra)
+        make \"result5 gprop \"board 1
+        make \"result6 color
+        make \"result7 bg
+        make \"result8 penpattern
+        make \"result9 linecap
+        make \"result11 item 1 :grid
+        make \"result12 count :grid
+        ",
+            directory, file_name
+        );
+        let mut int = Interpreter::new();
+        let result = int.interpret(&load);
+        let _ = fs::remove_file(env::temp_dir().join(&file_name));
+        match result {
+            Ok(..) => (),
+            Err(err) => panic!("test failed: {}", err),
+        };
+        assert!(int.state.data.get_variable("result1") == Some(&Token::Number(42.0)));
+        assert!(int.state.data.get_variable("result2") == Some(&Token::Number(7.0)));
+        let Some(Token::List(list)) = int.state.data.get_variable("result11") else {
+            panic!("test failed: expected a list");
+        };
+        assert!(list.first() == Some(&Token::Word(String::from("a"))));
+        assert!(matches!(list.get(1), Some(Token::Array(array)) if array.origin() == 1));
+        assert!(int.state.data.get_variable("result12") == Some(&Token::Number(2.0)));
+        assert!(int.state.data.get_variable("result10") == Some(&Token::Word(file_name.clone())));
+        assert!(int.state.data.get_variable("result3") == Some(&Token::Number(90.0)));
+        assert!(int.state.data.get_variable("result4") == Some(&Token::Number(15.0)));
+        assert!(int.state.data.get_variable("result5") == Some(&Token::List(List::parse("x o"))));
+        assert!(
+            int.state.data.get_variable("result6")
+                == Some(&Token::List(List::parse("128 64 0 100")))
+        );
+        assert!(int.state.data.get_variable("result7") == Some(&Token::Number(5.0)));
+        assert!(
+            int.state.data.get_variable("items") == Some(&Token::List(List::parse("1 [2 3] abc")))
+        );
+        assert!(
+            int.state.data.get_variable("name") == Some(&Token::Word(String::from("two words")))
+        );
+        assert!(int.state.data.get_variable("result8") == Some(&Token::List(List::parse("4 2"))));
+        assert!(
+            int.state.data.get_variable("result9") == Some(&Token::Word(String::from("square")))
+        );
+        assert!(int.state.canvas.get_drawings().len() == 4);
+        let Some(Drawing::Line(line)) = int.state.canvas.get_drawings().get(2) else {
+            panic!("test failed: expected a line");
+        };
+        assert!(line.pen.pattern == [4.0, 2.0] && line.dash_offset == 0.0);
+        let Some(Drawing::Curve(curve)) = int.state.canvas.get_drawings().last() else {
+            panic!("test failed: expected a curve");
+        };
+        assert!(curve.is_closed && curve.pen.pattern == [4.0, 2.0] && curve.dash_offset == 10.0);
+        assert!(int.state.canvas.color_at_point(&Point::new(100.0, 100.0)) == Color::Number(25.0));
+        assert!(int.state.canvas.color_at_point(&Point::new(0.0, 25.0)) == Color::Number(15.0));
+        assert!(matches!(
+            int.state.canvas.get_object("label"),
+            Ok(Object::Text(..))
+        ));
+        assert!(int.source() == code);
+    }
+}